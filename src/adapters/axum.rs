@@ -0,0 +1,382 @@
+//! Axum integration for Wyvern
+//!
+//! `FilterCriteria` and `Pagination` can be extracted straight from the query
+//! string of a request, using the format of [`crate::query_string`], and
+//! `Page<T>` can be returned from handlers as a JSON body with RFC 8288 `Link`
+//! headers.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use axum::{Extension, Router, routing::get};
+//! use wyvern::{FilterCriteria, Page, Pagination, PageResponse, QueryConfig, Queryable};
+//!
+//! async fn list_users(
+//!     criteria: FilterCriteria,
+//!     pagination: Pagination,
+//! ) -> Result<PageResponse<User>, AppError> {
+//!     let page = repository.paginate(criteria.clone(), pagination).await?;
+//!     Ok(PageResponse::new(page, &criteria)?)
+//! }
+//!
+//! let app = Router::new()
+//!     .route("/users", get(list_users))
+//!     .layer(Extension(QueryConfig::new().with_max_per_page(50)));
+//! ```
+//!
+//! Invalid query strings are rejected with `400 Bad Request` and an RFC 9457
+//! `application/problem+json` body.
+
+use crate::query_string::{self, PER_PAGE_KEY};
+use crate::{FilterCriteria, Page, Pagination, RepositoryError};
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum::http::header::{CONTENT_TYPE, LINK};
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use std::fmt;
+
+/// Defaults and limits applied by the `Pagination` extractor.
+///
+/// Add it to the router with `axum::Extension`; requests without one use
+/// `QueryConfig::default()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryConfig {
+    default_per_page: i64,
+    max_per_page: i64,
+}
+
+impl QueryConfig {
+    /// Creates the default configuration: 20 items per page, at most 100.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the page size used when the request does not give `per_page`.
+    ///
+    /// Values below 1 are raised to 1, and a default above the maximum page
+    /// size is lowered to it when a request is parsed.
+    pub fn with_default_per_page(mut self, per_page: i64) -> Self {
+        self.default_per_page = per_page.max(1);
+        self
+    }
+
+    /// Sets the largest `per_page` a request may ask for.
+    ///
+    /// Values below 1 are raised to 1.
+    pub fn with_max_per_page(mut self, per_page: i64) -> Self {
+        self.max_per_page = per_page.max(1);
+        self
+    }
+
+    /// Parses the pagination of a query string with these defaults and limits.
+    pub fn pagination(&self, query: &str) -> Result<Pagination, RepositoryError> {
+        let (_, mut pagination) = query_string::parse(query)?;
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let has_per_page =
+            form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == PER_PAGE_KEY);
+
+        if !has_per_page {
+            pagination.per_page = self.default_per_page.min(self.max_per_page);
+        }
+        pagination.validate_max(self.max_per_page)?;
+        Ok(pagination)
+    }
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            default_per_page: Pagination::default().per_page,
+            max_per_page: 100,
+        }
+    }
+}
+
+/// Rejection returned when a query string cannot be extracted.
+///
+/// Responds with `400 Bad Request` and an `application/problem+json` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryRejection {
+    detail: String,
+}
+
+impl QueryRejection {
+    /// Returns the reason the query string was rejected.
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+}
+
+impl From<RepositoryError> for QueryRejection {
+    fn from(error: RepositoryError) -> Self {
+        let detail = match error {
+            RepositoryError::InvalidInput(detail) => detail,
+            other => other.to_string(),
+        };
+        Self { detail }
+    }
+}
+
+impl fmt::Display for QueryRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid query string: {}", self.detail)
+    }
+}
+
+impl std::error::Error for QueryRejection {}
+
+impl IntoResponse for QueryRejection {
+    fn into_response(self) -> Response {
+        let status = StatusCode::BAD_REQUEST;
+        let body = serde_json::json!({
+            "type": "about:blank",
+            "title": status.canonical_reason(),
+            "status": status.as_u16(),
+            "detail": self.detail,
+        });
+
+        let mut response = (status, Json(body)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        response
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for FilterCriteria {
+    type Rejection = QueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let (criteria, _) = query_string::parse(parts.uri.query().unwrap_or(""))?;
+        Ok(criteria)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Pagination {
+    type Rejection = QueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<QueryConfig>()
+            .copied()
+            .unwrap_or_default();
+        Ok(config.pagination(parts.uri.query().unwrap_or(""))?)
+    }
+}
+
+/// A page response whose `Link` headers keep the filters and sort of the
+/// request.
+///
+/// Returning `Page<T>` directly links with `page` and `per_page` only.
+#[derive(Debug, Clone)]
+pub struct PageResponse<T> {
+    page: Page<T>,
+    link: Option<HeaderValue>,
+}
+
+impl<T> PageResponse<T> {
+    /// Builds the response, serializing `criteria` into the page links.
+    ///
+    /// Fails with `RepositoryError::InvalidInput` when the criteria cannot be
+    /// expressed as a query string (see [`query_string::serialize`]).
+    pub fn new(page: Page<T>, criteria: &FilterCriteria) -> Result<Self, RepositoryError> {
+        let link = link_header(&page, criteria)?;
+        Ok(Self { page, link })
+    }
+}
+
+impl<T: Serialize> IntoResponse for PageResponse<T> {
+    fn into_response(self) -> Response {
+        let mut response = Json(self.page).into_response();
+        if let Some(link) = self.link {
+            response.headers_mut().insert(LINK, link);
+        }
+        response
+    }
+}
+
+impl<T: Serialize> IntoResponse for Page<T> {
+    fn into_response(self) -> Response {
+        let criteria = FilterCriteria::new();
+        match PageResponse::new(self, &criteria) {
+            Ok(response) => response.into_response(),
+            Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+        }
+    }
+}
+
+/// Renders the `first`, `prev`, `next` and `last` links of a page
+///
+/// Links are query-only references, resolved against the request URI.
+fn link_header<T>(
+    page: &Page<T>,
+    criteria: &FilterCriteria,
+) -> Result<Option<HeaderValue>, RepositoryError> {
+    let mut relations = vec![("first", 1)];
+    if let Some(previous) = page.previous_page() {
+        relations.push(("prev", previous));
+    }
+    if let Some(next) = page.next_page() {
+        relations.push(("next", next));
+    }
+    if page.total_pages > 0 {
+        relations.push(("last", page.total_pages));
+    }
+
+    let links = relations
+        .into_iter()
+        .map(|(rel, number)| {
+            let query = query_string::serialize(criteria, &Pagination::new(number, page.per_page))?;
+            Ok(format!("<?{}>; rel=\"{}\"", query, rel))
+        })
+        .collect::<Result<Vec<_>, RepositoryError>>()?;
+
+    HeaderValue::from_str(&links.join(", "))
+        .map(Some)
+        .map_err(|e| RepositoryError::Internal(format!("invalid Link header: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Condition;
+    use axum::http::Request;
+
+    fn parts(uri: &str, config: Option<QueryConfig>) -> Parts {
+        let mut request = Request::builder().uri(uri).body(()).unwrap();
+        if let Some(config) = config {
+            request.extensions_mut().insert(config);
+        }
+        request.into_parts().0
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_extract_filter_criteria() {
+        let mut parts = parts("/users?status=active&age[gt]=18&sort=-name", None);
+
+        let criteria = FilterCriteria::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(criteria.conditions.len(), 2);
+        assert_eq!(criteria.sort.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_extract_pagination_with_config() {
+        let config = QueryConfig::new()
+            .with_default_per_page(10)
+            .with_max_per_page(50);
+
+        let mut defaulted = parts("/users?page=3", Some(config));
+        let pagination = Pagination::from_request_parts(&mut defaulted, &())
+            .await
+            .unwrap();
+        assert_eq!(pagination, Pagination::new(3, 10));
+
+        let mut explicit = parts("/users?per_page=50", Some(config));
+        let pagination = Pagination::from_request_parts(&mut explicit, &())
+            .await
+            .unwrap();
+        assert_eq!(pagination, Pagination::new(1, 50));
+
+        let mut too_large = parts("/users?per_page=51", Some(config));
+        let rejection = Pagination::from_request_parts(&mut too_large, &())
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.detail(), "'per_page' must be at most 50, got 51");
+
+        let mut unconfigured = parts("/users", None);
+        let pagination = Pagination::from_request_parts(&mut unconfigured, &())
+            .await
+            .unwrap();
+        assert_eq!(pagination, Pagination::default());
+    }
+
+    #[test]
+    fn test_config_is_clamped() {
+        let config = QueryConfig::new()
+            .with_default_per_page(0)
+            .with_max_per_page(-5);
+        assert_eq!(config.pagination("").unwrap(), Pagination::new(1, 1));
+
+        // A default above the maximum is lowered, whichever is set first
+        let config = QueryConfig::new()
+            .with_default_per_page(80)
+            .with_max_per_page(50);
+        assert_eq!(
+            config.pagination("?page=2").unwrap(),
+            Pagination::new(2, 50)
+        );
+        assert!(config.pagination("?per_page=80").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejection_is_problem_json() {
+        let mut parts = parts("/users?age[between]=1", None);
+        let rejection = FilterCriteria::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        let response = rejection.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/problem+json");
+
+        let body = body_json(response).await;
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["title"], "Bad Request");
+        assert!(body["detail"].as_str().unwrap().contains("between"));
+    }
+
+    #[tokio::test]
+    async fn test_page_response_links() {
+        let page = Page::new(vec![1, 2], 2, 2, 5);
+        let response = page.into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[LINK],
+            "<?page=1&per_page=2>; rel=\"first\", <?page=1&per_page=2>; rel=\"prev\", \
+             <?page=3&per_page=2>; rel=\"next\", <?page=3&per_page=2>; rel=\"last\""
+        );
+
+        let body = body_json(response).await;
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "items": [1, 2],
+                "page": 2,
+                "per_page": 2,
+                "total_items": 5,
+                "total_pages": 3,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_response_keeps_filters() {
+        let criteria =
+            FilterCriteria::new().with_condition(Condition::eq("status", "active".into()));
+        let response = PageResponse::new(Page::new(vec!["a"], 1, 1, 1), &criteria)
+            .unwrap()
+            .into_response();
+
+        assert_eq!(
+            response.headers()[LINK],
+            "<?status=active&page=1&per_page=1>; rel=\"first\", \
+             <?status=active&page=1&per_page=1>; rel=\"last\""
+        );
+    }
+}
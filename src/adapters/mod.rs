@@ -1,10 +1,18 @@
 //! Database adapters for Wyvern
 //!
-//! This module provides adapters for various database libraries to work
+//! This module provides adapters for various database and web libraries to work
 //! seamlessly with Wyvern's repository traits.
 
+#[cfg(feature = "axum")]
+pub mod axum;
 #[cfg(feature = "sqlx")]
 pub mod sqlx;
+#[cfg(feature = "sqlx")]
+pub mod sqlx_repository;
 
+#[cfg(feature = "axum")]
+pub use self::axum::{PageResponse, QueryConfig, QueryRejection};
+#[cfg(feature = "sqlx")]
+pub use self::sqlx::{Identifier, SqlxAdapter, WyvernSqlxExt};
 #[cfg(feature = "sqlx")]
-pub use self::sqlx::{SqlxAdapter, WyvernSqlxExt};
+pub use self::sqlx_repository::SqlxRepository;
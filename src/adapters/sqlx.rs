@@ -11,9 +11,24 @@
 //!
 //! # Solution
 //!
-//! This adapter uses SQLx's `QueryBuilder` with string interpolation for values
-//! (with proper escaping for safety) or alternatively builds queries that can
-//! be safely executed with PostgreSQL.
+//! This adapter never inlines values into the SQL text. Every `ConditionValue`
+//! is rendered as a positional placeholder (`$1`, `$2`, ...) and encoded into
+//! `PgArguments` with the Postgres type matching its variant, so values are
+//! sent separately from the statement and Postgres can cache the query plan.
+//!
+//! Strings and NULLs are the exception: they are sent without a type, and
+//! Postgres gives them the type of the column they are compared with or
+//! written to. A NULL therefore fits any column, and a string fits text-like,
+//! enum and domain-over-text columns, which a `text` parameter would not.
+//!
+//! SQLx sends every parameter in binary format, so Postgres reads a string's
+//! bytes with the binary reader of the column's type. Columns such as `uuid`,
+//! `date`, `timestamptz` or `integer` need the matching typed value
+//! (`uuid::Uuid`, `chrono`/`time` dates, integers); filters parsed from query
+//! strings, RSQL or OData get them from `FilterSchema::coerce`, and
+//! `FilterSchema::validate` rejects strings left on fields of those kinds. A
+//! string that still reaches such a column fails with
+//! `RepositoryError::InvalidInput` rather than a query error.
 //!
 //! # Usage
 //!
@@ -23,14 +38,204 @@
 //! let criteria = FilterCriteria::new()
 //!     .with_condition(Condition::eq("status", "active".into()));
 //!
-//! let (query, args) = SqlxAdapter::build_select("users", criteria);
-//! let results = sqlx::query_as_with(&query, args)
+//! let (query, args) = SqlxAdapter::build_select_query("users", &criteria)?;
+//! let results: Vec<User> = sqlx::query_as_with(&query, args)
 //!     .fetch_all(&pool)
 //!     .await?;
 //! ```
+//!
+//! # Identifiers
+//!
+//! Table, field and sort names are parsed into an [`Identifier`] before being
+//! rendered. Names that are not plain (optionally dot-qualified) SQL identifiers
+//! are rejected with `RepositoryError::InvalidInput`, and accepted names are always
+//! emitted double-quoted, so names coming from user input cannot inject SQL.
+//!
+//! Condition fields may follow a JSON path into a `jsonb` column
+//! (`metadata->plan->>tier`). The column is validated as above and the keys are
+//! bound as parameters, rendering `"metadata"->$1->>$2`.
+//!
+//! # Projections
+//!
+//! A [`FilterCriteria`] projection replaces `SELECT *` with the listed columns,
+//! so rows can be read into a type holding only those columns:
+//!
+//! ```rust,ignore
+//! #[derive(sqlx::FromRow)]
+//! struct UserSummary {
+//!     id: i64,
+//!     contact: String,
+//! }
+//!
+//! let criteria = FilterCriteria::new()
+//!     .with_columns(["id"])
+//!     .with_projection(Projection::new("email").with_alias("contact"));
+//!
+//! // SELECT "id", "email" AS "contact" FROM "users"
+//! let summaries: Vec<UserSummary> = pool.filter_entities("users", &criteria).await?;
+//! ```
+//!
+//! # Relations
+//!
+//! Conditions may reference the fields of declared [`Relation`]s. Each such
+//! condition renders as an `EXISTS` subquery correlated with the root table,
+//! so root rows are never duplicated:
+//!
+//! ```rust,ignore
+//! let customer = Relation::many_to_one("customer", "customers", "customer_id");
+//! let criteria = FilterCriteria::new()
+//!     .with_condition(Condition::eq("customer.country", "FR".into()));
+//!
+//! // SELECT * FROM "orders" WHERE EXISTS (SELECT 1 FROM "customers" AS "customer"
+//! //   WHERE "customer"."id" = "orders"."customer_id" AND "customer"."country" = $1)
+//! let (query, args) =
+//!     SqlxAdapter::build_select_query_with_relations("orders", &[customer], &criteria)?;
+//! ```
+//!
+//! Sorting by the field of a many-to-one relation orders by a scalar subquery
+//! selecting it; sorting through to-many relations is rejected.
+
+use crate::aggregate::{AggregateFunction, AggregateQuery, AggregateRow};
+use crate::cursor::Seek;
+use crate::query::FieldPath;
+use crate::relation::{RelatedRow, Relation, RelationKind};
+use crate::search::TextSearch;
+use crate::{
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, Projection, RepositoryError,
+    SortDirection,
+};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+#[cfg(not(feature = "rust_decimal"))]
+use sqlx::postgres::PgValueFormat;
+use sqlx::postgres::types::Oid;
+use sqlx::postgres::{PgArgumentBuffer, PgArguments, PgPool, PgRow, PgTypeInfo, PgTypeKind};
+use sqlx::{Arguments, Column, Encode, Postgres, Row, Type, TypeInfo, ValueRef};
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum length of a single identifier part (PostgreSQL's `NAMEDATALEN - 1`)
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Maximum number of dot-separated parts (`schema.table.column`)
+const MAX_IDENTIFIER_PARTS: usize = 3;
+
+/// The column of related rows holding the key of the root row they belong to
+const PARENT_KEY_COLUMN: &str = "wyvern_parent_key";
+
+/// A validated SQL identifier, optionally qualified (`schema.table`, `table.column`)
+///
+/// Each part must start with an ASCII letter or underscore, contain only ASCII
+/// letters, digits, underscores or `$`, and be at most 63 bytes long.
+///
+/// Identifiers are rendered quoted (`"schema"."column"`). Quoted identifiers are
+/// case-sensitive in PostgreSQL, so `userId` refers to a column created as
+/// `"userId"`, not to `userid`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identifier {
+    parts: Vec<String>,
+}
 
-use crate::{ConditionValue, FilterCriteria, Operator, SortDirection};
-use sqlx::postgres::PgPool;
+impl Identifier {
+    /// Parses and validates an identifier
+    pub fn parse(name: &str) -> Result<Self, RepositoryError> {
+        let parts: Vec<&str> = name.split('.').collect();
+
+        if parts.len() > MAX_IDENTIFIER_PARTS {
+            return Err(RepositoryError::InvalidInput(format!(
+                "identifier '{}' has more than {} parts",
+                name, MAX_IDENTIFIER_PARTS
+            )));
+        }
+
+        for part in &parts {
+            if !Self::is_valid_part(part) {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "invalid identifier '{}'",
+                    name
+                )));
+            }
+        }
+
+        Ok(Self {
+            parts: parts.into_iter().map(String::from).collect(),
+        })
+    }
+
+    /// Returns the dot-separated parts of this identifier
+    pub fn parts(&self) -> &[String] {
+        &self.parts
+    }
+
+    fn is_valid_part(part: &str) -> bool {
+        let mut chars = part.chars();
+        let starts_ok = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+
+        starts_ok
+            && part.len() <= MAX_IDENTIFIER_LENGTH
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = RepositoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            // Validated parts never contain quotes; doubling them keeps rendering safe regardless
+            write!(f, "\"{}\"", part.replace('"', "\"\""))?;
+        }
+        Ok(())
+    }
+}
+
+/// A parameter sent without a type, so Postgres infers it from where it is used
+///
+/// The value is still encoded as `T`, which only matches the inferred type for
+/// NULLs and for strings read by text-like columns.
+struct Untyped<T>(T);
+
+impl<T> Type<Postgres> for Untyped<T> {
+    fn type_info() -> PgTypeInfo {
+        // OID 0 leaves the parameter type unspecified
+        PgTypeInfo::with_oid(Oid(0))
+    }
+}
+
+impl<'q, T: Encode<'q, Postgres>> Encode<'q, Postgres> for Untyped<T> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        self.0.encode_by_ref(buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+}
+
+/// The table conditions are rendered against and the relations they may traverse
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    /// The rendered root table, qualifying its keys in relation subqueries
+    table: &'a str,
+    relations: &'a [Relation],
+}
+
+impl Scope<'_> {
+    /// A scope without relations
+    const PLAIN: Scope<'static> = Scope {
+        table: "",
+        relations: &[],
+    };
+}
 
 /// Adapter for converting wyvern FilterCriteria to SQLx queries
 pub struct SqlxAdapter;
@@ -38,12 +243,85 @@ pub struct SqlxAdapter;
 impl SqlxAdapter {
     /// Builds a SELECT query with WHERE, ORDER BY, LIMIT, and OFFSET clauses
     ///
-    /// Returns a SQL string that can be executed with sqlx
-    pub fn build_select_query(table_name: &str, criteria: &FilterCriteria) -> String {
-        let mut query = format!("SELECT * FROM {}", table_name);
+    /// Returns the SQL string together with the arguments to bind to its placeholders
+    pub fn build_select_query(
+        table_name: &str,
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_select_columns(&Identifier::parse(table_name)?, &[], &[], criteria)
+    }
+
+    /// Builds a SELECT query whose conditions and sort may reference the
+    /// fields of `relations`
+    pub fn build_select_query_with_relations(
+        table_name: &str,
+        relations: &[Relation],
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_select_columns(&Identifier::parse(table_name)?, relations, &[], criteria)
+    }
+
+    /// Builds a SELECT query for the given columns, or `*` when `columns` is empty
+    ///
+    /// A non-empty projection in the criteria takes precedence over `columns`.
+    pub(crate) fn build_select_columns(
+        table: &Identifier,
+        relations: &[Relation],
+        columns: &[Identifier],
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_select_seek(table, relations, columns, criteria, None)
+    }
+
+    /// Builds a SELECT query that also seeks past a keyset position
+    pub(crate) fn build_select_seek(
+        table: &Identifier,
+        relations: &[Relation],
+        columns: &[Identifier],
+        criteria: &FilterCriteria,
+        seek: Option<&Seek>,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        let mut args = PgArguments::default();
+        let select_list = if !criteria.projection.is_empty() {
+            Self::build_projection(&criteria.projection, &mut args)?
+        } else if columns.is_empty() {
+            "*".to_string()
+        } else {
+            Self::column_list(columns)
+        };
+        let table = table.to_string();
+        let scope = Scope {
+            table: &table,
+            relations,
+        };
+        let query =
+            Self::build_select_from(&select_list, &table, scope, criteria, seek, &mut args)?;
+
+        Ok((query, args))
+    }
+
+    /// Builds a SELECT of `select_list` from `source`, which is a table or a
+    /// parenthesized subquery
+    fn build_select_from(
+        select_list: &str,
+        source: &str,
+        scope: Scope,
+        criteria: &FilterCriteria,
+        seek: Option<&Seek>,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        let mut query = format!("SELECT {} FROM {}", select_list, source);
 
         // Build WHERE clause
-        let where_clause = Self::build_where_clause(criteria);
+        let mut where_clause = Self::build_where_clause(criteria, scope, args)?;
+        if let Some(seek) = seek {
+            let seek_clause = Self::build_seek(seek, scope, args)?;
+            where_clause = if where_clause.is_empty() {
+                seek_clause
+            } else {
+                format!("({}) AND ({})", where_clause, seek_clause)
+            };
+        }
         if !where_clause.is_empty() {
             query.push_str(" WHERE ");
             query.push_str(&where_clause);
@@ -52,143 +330,1179 @@ impl SqlxAdapter {
         // Build ORDER BY clause
         if !criteria.sort.is_empty() {
             query.push_str(" ORDER BY ");
-            let sort_clauses: Vec<String> = criteria
-                .sort
-                .iter()
-                .map(|s| {
-                    let direction = match s.direction {
-                        SortDirection::Ascending => "ASC",
-                        SortDirection::Descending => "DESC",
-                    };
-                    format!("{} {}", s.field, direction)
-                })
-                .collect();
+            let mut sort_clauses = Vec::with_capacity(criteria.sort.len());
+            for sort in &criteria.sort {
+                let direction = match sort.direction {
+                    SortDirection::Ascending => "ASC",
+                    SortDirection::Descending => "DESC",
+                };
+                let field = Self::build_sort_field(&sort.field, scope)?;
+                let expression = match &sort.rank {
+                    Some(search) => {
+                        let (document, query) = Self::build_text_search(&field, search, args)?;
+                        format!("ts_rank({}, {})", document, query)
+                    }
+                    None => field,
+                };
+                sort_clauses.push(format!("{} {}", expression, direction));
+            }
             query.push_str(&sort_clauses.join(", "));
         }
 
+        let (limit, offset) = criteria.bounds()?;
+
         // Add LIMIT
-        if let Some(limit) = criteria.limit {
+        if let Some(limit) = limit {
             query.push_str(&format!(" LIMIT {}", limit));
         }
 
         // Add OFFSET
-        if let Some(offset) = criteria.offset {
+        if let Some(offset) = offset {
             query.push_str(&format!(" OFFSET {}", offset));
         }
 
-        query
+        Ok(query)
+    }
+
+    /// Builds a grouped aggregation query
+    ///
+    /// Groups are computed in a subquery so that `having`, the sort, limit and
+    /// offset can refer to aggregates by alias: `SELECT * FROM (SELECT
+    /// "status", COUNT(*) AS "orders" FROM "orders" GROUP BY "status") AS
+    /// "groups" WHERE "orders" > $1`. Group fields and aliases must be
+    /// unqualified column names.
+    pub fn build_aggregate_query(
+        table_name: &str,
+        query: &AggregateQuery,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_aggregate(&Identifier::parse(table_name)?, &[], query)
     }
 
     /// Builds a COUNT query
-    pub fn build_count_query(table_name: &str, criteria: &FilterCriteria) -> String {
-        let mut query = format!("SELECT COUNT(*) FROM {}", table_name);
+    pub fn build_count_query(
+        table_name: &str,
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_count(&Identifier::parse(table_name)?, &[], criteria)
+    }
+
+    /// Builds a COUNT query whose conditions may reference the fields of `relations`
+    pub fn build_count_query_with_relations(
+        table_name: &str,
+        relations: &[Relation],
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_count(&Identifier::parse(table_name)?, relations, criteria)
+    }
+
+    /// Builds a `SELECT EXISTS(...)` query
+    ///
+    /// Sorting, limit and offset are ignored.
+    pub fn build_exists_query(
+        table_name: &str,
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        Self::build_exists(&Identifier::parse(table_name)?, &[], criteria)
+    }
+
+    /// Builds a query loading the rows of `relation` for the root rows whose
+    /// key is one of `keys`
+    ///
+    /// The keys are bound as one array, so a single query loads the relation
+    /// for any number of root rows. Each row holds the relation's declared
+    /// columns followed by the key of the root row it belongs to, in a
+    /// `wyvern_parent_key` column: `SELECT "customer"."id",
+    /// "customer"."name", "customer"."id" AS "wyvern_parent_key" FROM
+    /// "customers" AS "customer" WHERE "customer"."id" = ANY($1)`. A relation
+    /// without declared columns is rejected with
+    /// `RepositoryError::InvalidInput`.
+    pub fn build_related_query(
+        relation: &Relation,
+        keys: &[ConditionValue],
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        if relation.columns.is_empty() {
+            return Err(RepositoryError::InvalidInput(format!(
+                "relation '{}' declares no columns to load",
+                relation.name
+            )));
+        }
+        let (from, key) = Self::build_relation_source(relation)?;
+        let alias = Identifier::parse(&relation.name)?;
+        let columns = relation
+            .columns
+            .iter()
+            .map(|column| Ok(format!("{}.{}", alias, Self::output_name(column)?)))
+            .collect::<Result<Vec<_>, RepositoryError>>()?;
+        let mut args = PgArguments::default();
+        let condition = match Self::bind_array(keys, &mut args)? {
+            Some(array) => format!("{} = ANY({})", key, array),
+            None => "FALSE".to_string(),
+        };
+        let query = format!(
+            "SELECT {}, {} AS \"{}\" FROM {} WHERE {}",
+            columns.join(", "),
+            key,
+            PARENT_KEY_COLUMN,
+            from,
+            condition
+        );
+
+        Ok((query, args))
+    }
+
+    pub(crate) fn build_aggregate(
+        table: &Identifier,
+        relations: &[Relation],
+        query: &AggregateQuery,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        query.check()?;
+        let mut args = PgArguments::default();
+
+        let groups = query
+            .group_by
+            .iter()
+            .map(|field| Self::output_name(field))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut columns: Vec<String> = groups.iter().map(Identifier::to_string).collect();
+        for aggregate in &query.aggregates {
+            let argument = match (&aggregate.field, aggregate.function) {
+                (None, _) => "*".to_string(),
+                (Some(field), AggregateFunction::CountDistinct) => {
+                    format!("DISTINCT {}", Identifier::parse(field)?)
+                }
+                (Some(field), _) => Identifier::parse(field)?.to_string(),
+            };
+            columns.push(format!(
+                "{}({}) AS {}",
+                aggregate.function.sql_name(),
+                argument,
+                Self::output_name(&aggregate.alias)?
+            ));
+        }
+
+        let mut groups_query = format!("SELECT {} FROM {}", columns.join(", "), table);
+        let table = table.to_string();
+        let scope = Scope {
+            table: &table,
+            relations,
+        };
+        let where_clause = Self::build_where_clause(&query.criteria, scope, &mut args)?;
+        if !where_clause.is_empty() {
+            groups_query.push_str(" WHERE ");
+            groups_query.push_str(&where_clause);
+        }
+        if !groups.is_empty() {
+            groups_query.push_str(" GROUP BY ");
+            groups_query.push_str(&Self::column_list(&groups));
+        }
+
+        let outer = FilterCriteria {
+            filters: query.having.clone(),
+            sort: query.criteria.sort.clone(),
+            limit: query.criteria.limit,
+            offset: query.criteria.offset,
+            ..FilterCriteria::new()
+        };
+        let sql = Self::build_select_from(
+            "*",
+            &format!("({}) AS \"groups\"", groups_query),
+            Scope::PLAIN,
+            &outer,
+            None,
+            &mut args,
+        )?;
 
-        let where_clause = Self::build_where_clause(criteria);
+        Ok((sql, args))
+    }
+
+    pub(crate) fn build_count(
+        table: &Identifier,
+        relations: &[Relation],
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        let mut query = format!("SELECT COUNT(*) FROM {}", table);
+        let mut args = PgArguments::default();
+
+        let table = table.to_string();
+        let scope = Scope {
+            table: &table,
+            relations,
+        };
+        let where_clause = Self::build_where_clause(criteria, scope, &mut args)?;
         if !where_clause.is_empty() {
             query.push_str(" WHERE ");
             query.push_str(&where_clause);
         }
 
-        query
+        Ok((query, args))
     }
 
-    /// Builds a WHERE clause from FilterCriteria conditions
+    pub(crate) fn build_exists(
+        table: &Identifier,
+        relations: &[Relation],
+        criteria: &FilterCriteria,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        let mut query = format!("SELECT EXISTS(SELECT 1 FROM {}", table);
+        let mut args = PgArguments::default();
+
+        let table = table.to_string();
+        let scope = Scope {
+            table: &table,
+            relations,
+        };
+        let where_clause = Self::build_where_clause(criteria, scope, &mut args)?;
+        if !where_clause.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&where_clause);
+        }
+        query.push(')');
+
+        Ok((query, args))
+    }
+
+    /// Renders identifiers as a comma-separated column list
+    pub(crate) fn column_list(columns: &[Identifier]) -> String {
+        columns
+            .iter()
+            .map(Identifier::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Renders a projection as a select list
     ///
-    /// Converts conditions to SQL with properly escaped values
-    fn build_where_clause(criteria: &FilterCriteria) -> String {
-        if criteria.conditions.is_empty() {
-            return String::new();
+    /// Fields are rendered like condition fields and aliases must be single,
+    /// unqualified identifiers.
+    fn build_projection(
+        projection: &[Projection],
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        let mut columns = Vec::with_capacity(projection.len());
+        for item in projection {
+            let field = Self::build_field(&item.field, args)?;
+            match &item.alias {
+                Some(alias) => columns.push(format!("{} AS {}", field, Self::output_name(alias)?)),
+                None => columns.push(field),
+            }
+        }
+        Ok(columns.join(", "))
+    }
+
+    /// Parses the name of a result column, which must not be qualified
+    fn output_name(name: &str) -> Result<Identifier, RepositoryError> {
+        let identifier = Identifier::parse(name)?;
+        if identifier.parts().len() != 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "result column '{}' must not be qualified",
+                name
+            )));
         }
+        Ok(identifier)
+    }
+
+    /// Builds a WHERE clause from FilterCriteria conditions and filters
+    ///
+    /// Values are pushed onto `args` and referenced through placeholders.
+    /// Returns an empty string when the criteria has nothing to filter on.
+    fn build_where_clause(
+        criteria: &FilterCriteria,
+        scope: Scope,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        match criteria.expression() {
+            FilterExpr::And(exprs) if exprs.is_empty() => Ok(String::new()),
+            expr => Self::build_expression(&expr, scope, args),
+        }
+    }
+
+    /// Renders a filter expression, parenthesizing nested groups
+    fn build_expression(
+        expr: &FilterExpr,
+        scope: Scope,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        match expr {
+            FilterExpr::And(exprs) => Self::build_group(exprs, " AND ", "TRUE", scope, args),
+            FilterExpr::Or(exprs) => Self::build_group(exprs, " OR ", "FALSE", scope, args),
+            FilterExpr::Not(expr) => Ok(format!(
+                "NOT ({})",
+                Self::build_expression(expr, scope, args)?
+            )),
+            FilterExpr::Leaf(condition) => Self::build_condition(condition, scope, args),
+        }
+    }
+
+    /// Renders the members of an AND/OR group joined by `separator`
+    ///
+    /// Members that are themselves groups of several expressions are wrapped
+    /// in parentheses; an empty group renders as `empty`.
+    fn build_group(
+        exprs: &[FilterExpr],
+        separator: &str,
+        empty: &str,
+        scope: Scope,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        match exprs {
+            [] => Ok(empty.to_string()),
+            [expr] => Self::build_expression(expr, scope, args),
+            _ => {
+                let mut parts = Vec::with_capacity(exprs.len());
+                for expr in exprs {
+                    let sql = Self::build_expression(expr, scope, args)?;
+                    if Self::is_compound(expr) {
+                        parts.push(format!("({})", sql));
+                    } else {
+                        parts.push(sql);
+                    }
+                }
+                Ok(parts.join(separator))
+            }
+        }
+    }
+
+    /// Whether an expression renders as several members joined by AND/OR
+    fn is_compound(expr: &FilterExpr) -> bool {
+        match expr {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => match exprs.as_slice() {
+                [single] => Self::is_compound(single),
+                exprs => exprs.len() > 1,
+            },
+            _ => false,
+        }
+    }
+
+    /// Renders a keyset seek as row-value comparisons
+    ///
+    /// Consecutive fields sorted the same way are compared as one row, so
+    /// `a ASC, b ASC, c DESC` renders as
+    /// `(a, b) > ($1, $2) OR ((a, b) = ($1, $2) AND c < $3)`. Each value is
+    /// bound once.
+    fn build_seek(
+        seek: &Seek,
+        scope: Scope,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        let mut runs: Vec<(SortDirection, Vec<String>, Vec<String>)> = Vec::new();
+        for (sort, value) in seek.sort.iter().zip(&seek.values) {
+            let field = Self::build_sort_field(&sort.field, scope)?;
+            let placeholder = Self::bind_value(value, args)?;
+            match runs.last_mut() {
+                Some((direction, fields, placeholders)) if *direction == sort.direction => {
+                    fields.push(field);
+                    placeholders.push(placeholder);
+                }
+                _ => runs.push((sort.direction, vec![field], vec![placeholder])),
+            }
+        }
+
+        let row = |items: &[String]| match items {
+            [item] => item.clone(),
+            items => format!("({})", items.join(", ")),
+        };
+
+        let branches: Vec<String> = (0..runs.len())
+            .map(|i| {
+                let mut parts: Vec<String> = runs[..i]
+                    .iter()
+                    .map(|(_, fields, placeholders)| {
+                        format!("{} = {}", row(fields), row(placeholders))
+                    })
+                    .collect();
+                let (direction, fields, placeholders) = &runs[i];
+                let operator = match direction {
+                    SortDirection::Ascending => ">",
+                    SortDirection::Descending => "<",
+                };
+                parts.push(format!(
+                    "{} {} {}",
+                    row(fields),
+                    operator,
+                    row(placeholders)
+                ));
+                parts.join(" AND ")
+            })
+            .collect();
+
+        Ok(match branches.as_slice() {
+            [branch] => branch.clone(),
+            branches => branches
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    if i == 0 {
+                        b.clone()
+                    } else {
+                        format!("({})", b)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        })
+    }
+
+    /// Renders a sort field, selecting fields of many-to-one relations
+    /// through a scalar subquery
+    fn build_sort_field(field: &str, scope: Scope) -> Result<String, RepositoryError> {
+        match Relation::find(scope.relations, field) {
+            Some((relation, rest)) if relation.is_to_one() => {
+                Self::check_related_field(field, relation, rest)?;
+                let (from, link) = Self::build_relation(scope.table, relation)?;
+                Ok(format!(
+                    "(SELECT {} FROM {} WHERE {})",
+                    Identifier::parse(field)?,
+                    from,
+                    link
+                ))
+            }
+            Some((relation, _)) => Err(RepositoryError::InvalidInput(format!(
+                "cannot sort by '{}' through to-many relation '{}'",
+                field, relation.name
+            ))),
+            None => Ok(Identifier::parse(field)?.to_string()),
+        }
+    }
+
+    /// Checks that the part of `field` after the relation's name is a single
+    /// column, optionally followed by a JSON path
+    fn check_related_field(
+        field: &str,
+        relation: &Relation,
+        rest: &str,
+    ) -> Result<(), RepositoryError> {
+        let path = FieldPath::parse(rest)?;
+        if Identifier::parse(path.column)?.parts().len() != 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "'{}' must name a single column of relation '{}'",
+                field, relation.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Renders the FROM list of a relation's subquery, aliased by the
+    /// relation's name, and the condition linking it to the root table
+    fn build_relation(
+        root: &str,
+        relation: &Relation,
+    ) -> Result<(String, String), RepositoryError> {
+        let (from, key) = Self::build_relation_source(relation)?;
+        let root_key = Identifier::parse(relation.root_key())?;
+        Ok((from, format!("{} = {}.{}", key, root, root_key)))
+    }
+
+    /// Renders the FROM list of a relation's subquery, aliased by the
+    /// relation's name, and the related side of its link to the root table
+    fn build_relation_source(relation: &Relation) -> Result<(String, String), RepositoryError> {
+        let alias = Identifier::parse(&relation.name)?;
+        if alias.parts().len() != 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "relation name '{}' must not be qualified",
+                relation.name
+            )));
+        }
+        let table = Identifier::parse(&relation.table)?;
+        let target_key = Identifier::parse(&relation.target_key)?;
+
+        Ok(match &relation.kind {
+            RelationKind::ManyToOne { .. } => (
+                format!("{} AS {}", table, alias),
+                format!("{}.{}", alias, target_key),
+            ),
+            RelationKind::OneToMany { foreign_key } => (
+                format!("{} AS {}", table, alias),
+                format!("{}.{}", alias, Identifier::parse(foreign_key)?),
+            ),
+            RelationKind::ManyToMany {
+                join_table,
+                source_foreign_key,
+                target_foreign_key,
+            } => {
+                let join_table = Identifier::parse(join_table)?;
+                (
+                    format!(
+                        "{} JOIN {} AS {} ON {}.{} = {}.{}",
+                        join_table,
+                        table,
+                        alias,
+                        alias,
+                        target_key,
+                        join_table,
+                        Identifier::parse(target_foreign_key)?
+                    ),
+                    format!("{}.{}", join_table, Identifier::parse(source_foreign_key)?),
+                )
+            }
+        })
+    }
+
+    /// Renders a single condition
+    ///
+    /// A condition on the field of a relation renders as an `EXISTS`
+    /// subquery, which holds when any related row satisfies it. `IsNull` on a
+    /// many-to-one field holds when no related row has a value, so a root row
+    /// without a related row matches too, as it would through a left join.
+    fn build_condition(
+        condition: &Condition,
+        scope: Scope,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        if let Some((relation, rest)) = Relation::find(scope.relations, &condition.field) {
+            Self::check_related_field(&condition.field, relation, rest)?;
+            let (from, link) = Self::build_relation(scope.table, relation)?;
+            if relation.is_to_one() && condition.operator == Operator::IsNull {
+                let field = Self::build_field(&condition.field, args)?;
+                return Ok(format!(
+                    "NOT EXISTS (SELECT 1 FROM {} WHERE {} AND {} IS NOT NULL)",
+                    from, link, field
+                ));
+            }
+            let inner = Self::build_condition(condition, Scope::PLAIN, args)?;
+            return Ok(format!(
+                "EXISTS (SELECT 1 FROM {} WHERE {} AND {})",
+                from, link, inner
+            ));
+        }
+
+        let value = &condition.value;
+
+        // `IN ()` is a syntax error; nothing is in an empty list. The field is
+        // still checked, without binding its keys
+        if matches!(condition.operator, Operator::In | Operator::NotIn) {
+            Identifier::parse(FieldPath::parse(&condition.field)?.column)?;
+        }
+        match (condition.operator, value) {
+            (Operator::In, ConditionValue::List(values)) if values.is_empty() => {
+                return Ok("FALSE".to_string());
+            }
+            (Operator::NotIn, ConditionValue::List(values)) if values.is_empty() => {
+                return Ok("TRUE".to_string());
+            }
+            _ => {}
+        }
+        // A list would render as a row constructor, `field = ($1, $2)`
+        if let (
+            Operator::Equal
+            | Operator::NotEqual
+            | Operator::GreaterThan
+            | Operator::GreaterThanOrEqual
+            | Operator::LessThan
+            | Operator::LessThanOrEqual
+            | Operator::Like
+            | Operator::NotLike
+            | Operator::CaseSensitiveLike,
+            ConditionValue::List(_),
+        ) = (condition.operator, value)
+        {
+            return Err(RepositoryError::InvalidInput(format!(
+                "operator {:?} on '{}' does not take a list",
+                condition.operator, condition.field
+            )));
+        }
+
+        let field = Self::build_field(&condition.field, args)?;
+        let sql = match condition.operator {
+            Operator::Equal => format!("{} = {}", field, Self::bind_value(value, args)?),
+            Operator::NotEqual => format!("{} != {}", field, Self::bind_value(value, args)?),
+            Operator::GreaterThan => format!("{} > {}", field, Self::bind_value(value, args)?),
+            Operator::GreaterThanOrEqual => {
+                format!("{} >= {}", field, Self::bind_value(value, args)?)
+            }
+            Operator::LessThan => format!("{} < {}", field, Self::bind_value(value, args)?),
+            Operator::LessThanOrEqual => {
+                format!("{} <= {}", field, Self::bind_value(value, args)?)
+            }
+            Operator::Like => format!("{} ILIKE {}", field, Self::bind_value(value, args)?),
+            Operator::NotLike => {
+                format!("{} NOT ILIKE {}", field, Self::bind_value(value, args)?)
+            }
+            Operator::CaseSensitiveLike => {
+                format!("{} LIKE {}", field, Self::bind_value(value, args)?)
+            }
+            Operator::StartsWith | Operator::EndsWith | Operator::Contains => {
+                let text = escape_like(Self::string_value(condition)?);
+                let pattern = match condition.operator {
+                    Operator::StartsWith => format!("{}%", text),
+                    Operator::EndsWith => format!("%{}", text),
+                    _ => format!("%{}%", text),
+                };
+                format!(
+                    "{} ILIKE {}",
+                    field,
+                    Self::bind_value(&pattern.into(), args)?
+                )
+            }
+            Operator::Between => match value {
+                ConditionValue::List(bounds) if bounds.len() == 2 => format!(
+                    "{} BETWEEN {} AND {}",
+                    field,
+                    Self::bind_value(&bounds[0], args)?,
+                    Self::bind_value(&bounds[1], args)?
+                ),
+                _ => {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "operator Between on '{}' requires a list of two bounds",
+                        condition.field
+                    )));
+                }
+            },
+            Operator::IsNull => format!("{} IS NULL", field),
+            Operator::IsNotNull => format!("{} IS NOT NULL", field),
+            Operator::In => match value {
+                ConditionValue::List(_) => {
+                    format!("{} IN {}", field, Self::bind_value(value, args)?)
+                }
+                _ => format!("{} = {}", field, Self::bind_value(value, args)?),
+            },
+            Operator::NotIn => match value {
+                ConditionValue::List(_) => {
+                    format!("{} NOT IN {}", field, Self::bind_value(value, args)?)
+                }
+                _ => format!("{} != {}", field, Self::bind_value(value, args)?),
+            },
+            Operator::JsonContains => match value {
+                #[cfg(feature = "json")]
+                ConditionValue::Json(_) => {
+                    format!("{} @> {}", field, Self::bind_value(value, args)?)
+                }
+                ConditionValue::String(text) => {
+                    format!("{} @> {}::jsonb", field, Self::bind_text(text, args)?)
+                }
+                _ => {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "operator JsonContains on '{}' requires a JSON value",
+                        condition.field
+                    )));
+                }
+            },
+            Operator::JsonHasKey => {
+                Self::string_value(condition)?;
+                format!("{} ? {}", field, Self::bind_value(value, args)?)
+            }
+            Operator::JsonHasAnyKey | Operator::JsonHasAllKeys => {
+                let ConditionValue::List(keys) = value else {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "operator {:?} on '{}' requires a list of keys",
+                        condition.operator, condition.field
+                    )));
+                };
+                let placeholders = keys
+                    .iter()
+                    .map(|key| Self::bind_value(key, args))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let operator = match condition.operator {
+                    Operator::JsonHasAnyKey => "?|",
+                    _ => "?&",
+                };
+                format!(
+                    "{} {} ARRAY[{}]::text[]",
+                    field,
+                    operator,
+                    placeholders.join(", ")
+                )
+            }
+            Operator::ArrayOverlaps
+            | Operator::ArrayContains
+            | Operator::ArrayContainedBy
+            | Operator::EqualsAny => {
+                let ConditionValue::List(values) = value else {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "operator {:?} on '{}' requires a list",
+                        condition.operator, condition.field
+                    )));
+                };
+                match (condition.operator, Self::bind_array(values, args)?) {
+                    (Operator::ArrayOverlaps, Some(array)) => format!("{} && {}", field, array),
+                    (Operator::ArrayContains, Some(array)) => format!("{} @> {}", field, array),
+                    (Operator::ArrayContainedBy, Some(array)) => format!("{} <@ {}", field, array),
+                    (_, Some(array)) => format!("{} = ANY({})", field, array),
+                    (Operator::ArrayContains, None) => format!("{} IS NOT NULL", field),
+                    (Operator::ArrayContainedBy, None) => format!("cardinality({}) = 0", field),
+                    (_, None) => "FALSE".to_string(),
+                }
+            }
+            Operator::TextSearch => {
+                let ConditionValue::TextSearch(search) = value else {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "operator TextSearch on '{}' requires a text search value",
+                        condition.field
+                    )));
+                };
+                let (document, query) = Self::build_text_search(&field, search, args)?;
+                format!("{} @@ {}", document, query)
+            }
+            Operator::JsonPathExists => {
+                let path = Self::string_value(condition)?;
+                format!("{} @? {}::jsonpath", field, Self::bind_text(path, args)?)
+            }
+        };
+
+        Ok(sql)
+    }
+
+    /// Renders the `tsvector` of a field and the `tsquery` of a search
+    ///
+    /// The configuration and query text are bound as parameters, with the
+    /// configuration shared by both sides. A `tsvector` field is used as-is.
+    fn build_text_search(
+        field: &str,
+        search: &TextSearch,
+        args: &mut PgArguments,
+    ) -> Result<(String, String), RepositoryError> {
+        let bind_error = |e| RepositoryError::QueryError(format!("failed to bind value: {}", e));
+        let config = match &search.config {
+            Some(config) => {
+                args.add(config.clone()).map_err(bind_error)?;
+                format!("${}::regconfig, ", args.len())
+            }
+            None => String::new(),
+        };
+        args.add(search.query.clone()).map_err(bind_error)?;
+
+        let document = if search.tsvector {
+            field.to_string()
+        } else {
+            format!("to_tsvector({}{})", config, field)
+        };
+        Ok((
+            document,
+            format!("{}({}${})", search.mode.function(), config, args.len()),
+        ))
+    }
+
+    /// Renders a condition field, binding the keys of a JSON path
+    ///
+    /// `metadata->plan->>tier` renders as `"metadata"->$1->>$2`. Keys are
+    /// bound as text, and bracketed indices such as `[0]` as `int4` so they
+    /// index arrays.
+    fn build_field(field: &str, args: &mut PgArguments) -> Result<String, RepositoryError> {
+        let path = FieldPath::parse(field)?;
+        let mut sql = Identifier::parse(path.column)?.to_string();
+
+        for (i, key) in path.keys.iter().enumerate() {
+            let arrow = if path.as_text && i + 1 == path.keys.len() {
+                "->>"
+            } else {
+                "->"
+            };
+            let result = match FieldPath::index(key) {
+                Some(index) => args.add(index),
+                None => args.add(key.to_string()),
+            };
+            result
+                .map_err(|e| RepositoryError::QueryError(format!("failed to bind value: {}", e)))?;
+            sql.push_str(&format!("{}${}", arrow, args.len()));
+        }
+
+        Ok(sql)
+    }
+
+    /// Returns the string value of a condition whose operator requires one
+    fn string_value(condition: &Condition) -> Result<&str, RepositoryError> {
+        match &condition.value {
+            ConditionValue::String(text) => Ok(text),
+            _ => Err(RepositoryError::InvalidInput(format!(
+                "operator {:?} on '{}' requires a string value",
+                condition.operator, condition.field
+            ))),
+        }
+    }
+
+    /// Binds a ConditionValue to `args` and returns the placeholder(s) referencing it
+    ///
+    /// Lists bind each element separately and render as `($1, $2, ...)`.
+    /// Strings and NULLs are bound untyped so they take the type of the column
+    /// they meet.
+    pub(crate) fn bind_value(
+        value: &ConditionValue,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        let result = match value {
+            ConditionValue::String(s) => args.add(Untyped(s.clone())),
+            ConditionValue::Integer(i) => args.add(*i),
+            ConditionValue::Float(f) => args.add(*f),
+            ConditionValue::Boolean(b) => args.add(*b),
+            ConditionValue::Null => args.add(Untyped(None::<String>)),
+            ConditionValue::Bytes(b) => args.add(b.clone()),
+            #[cfg(feature = "uuid")]
+            ConditionValue::Uuid(u) => args.add(*u),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDate(d) => args.add(*d),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveTime(t) => args.add(*t),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDateTime(dt) => args.add(*dt),
+            #[cfg(feature = "chrono")]
+            ConditionValue::DateTime(dt) => args.add(*dt),
+            #[cfg(feature = "time")]
+            ConditionValue::Date(d) => args.add(*d),
+            #[cfg(feature = "time")]
+            ConditionValue::Time(t) => args.add(*t),
+            #[cfg(feature = "time")]
+            ConditionValue::PrimitiveDateTime(dt) => args.add(*dt),
+            #[cfg(feature = "time")]
+            ConditionValue::OffsetDateTime(dt) => args.add(*dt),
+            #[cfg(feature = "rust_decimal")]
+            ConditionValue::Decimal(d) => args.add(*d),
+            #[cfg(feature = "json")]
+            ConditionValue::Json(v) => args.add(v.clone()),
+            ConditionValue::TextSearch(_) => {
+                return Err(RepositoryError::InvalidInput(
+                    "text search values can only be used with the TextSearch operator".to_string(),
+                ));
+            }
+            ConditionValue::List(values) => {
+                let placeholders = values
+                    .iter()
+                    .map(|v| Self::bind_value(v, args))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(format!("({})", placeholders.join(", ")));
+            }
+        };
+
+        result.map_err(|e| RepositoryError::QueryError(format!("failed to bind value: {}", e)))?;
+        Ok(format!("${}", args.len()))
+    }
+
+    /// Binds a string as `text` and returns its placeholder
+    ///
+    /// Used where the SQL casts the parameter: an untyped one would be read
+    /// directly as the cast's target type instead of converted from text.
+    fn bind_text(text: &str, args: &mut PgArguments) -> Result<String, RepositoryError> {
+        args.add(text.to_string())
+            .map_err(|e| RepositoryError::QueryError(format!("failed to bind value: {}", e)))?;
+        Ok(format!("${}", args.len()))
+    }
+
+    /// Binds a list as a single typed Postgres array and returns its placeholder
+    ///
+    /// The array type follows the elements, which must all be of one kind
+    /// apart from NULLs; integers bind as `bigint[]`. Lists and JSON values
+    /// cannot be elements. An empty list has no element type to bind it with,
+    /// so nothing is bound and `None` is returned for the caller to render
+    /// the constant result instead.
+    pub(crate) fn bind_array(
+        values: &[ConditionValue],
+        args: &mut PgArguments,
+    ) -> Result<Option<String>, RepositoryError> {
+        if values.is_empty() {
+            return Ok(None);
+        }
+        let mixed = || {
+            RepositoryError::InvalidInput("array values must all be of the same kind".to_string())
+        };
+
+        // Collects the elements as `Vec<Option<T>>` and binds them
+        macro_rules! bind_elements {
+            ($variant:ident, $element:expr) => {{
+                let elements = values
+                    .iter()
+                    .map(|v| match v {
+                        ConditionValue::Null => Ok(None),
+                        ConditionValue::$variant(x) => Ok(Some($element(x))),
+                        _ => Err(mixed()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                args.add(elements)
+            }};
+        }
+
+        let first = values.iter().find(|v| !matches!(v, ConditionValue::Null));
+        let result = match first {
+            None => args.add(vec![None::<String>; values.len()]),
+            Some(ConditionValue::String(_)) => bind_elements!(String, |s: &String| s.clone()),
+            Some(ConditionValue::Integer(_)) => bind_elements!(Integer, |i: &i64| *i),
+            Some(ConditionValue::Float(_)) => bind_elements!(Float, |f: &f64| *f),
+            Some(ConditionValue::Boolean(_)) => bind_elements!(Boolean, |b: &bool| *b),
+            Some(ConditionValue::Bytes(_)) => bind_elements!(Bytes, |b: &Vec<u8>| b.clone()),
+            #[cfg(feature = "uuid")]
+            Some(ConditionValue::Uuid(_)) => bind_elements!(Uuid, |u: &uuid::Uuid| *u),
+            #[cfg(feature = "chrono")]
+            Some(ConditionValue::NaiveDate(_)) => {
+                bind_elements!(NaiveDate, |d: &chrono::NaiveDate| *d)
+            }
+            #[cfg(feature = "chrono")]
+            Some(ConditionValue::NaiveTime(_)) => {
+                bind_elements!(NaiveTime, |t: &chrono::NaiveTime| *t)
+            }
+            #[cfg(feature = "chrono")]
+            Some(ConditionValue::NaiveDateTime(_)) => {
+                bind_elements!(NaiveDateTime, |dt: &chrono::NaiveDateTime| *dt)
+            }
+            #[cfg(feature = "chrono")]
+            Some(ConditionValue::DateTime(_)) => {
+                bind_elements!(DateTime, |dt: &chrono::DateTime<chrono::Utc>| *dt)
+            }
+            #[cfg(feature = "time")]
+            Some(ConditionValue::Date(_)) => bind_elements!(Date, |d: &time::Date| *d),
+            #[cfg(feature = "time")]
+            Some(ConditionValue::Time(_)) => bind_elements!(Time, |t: &time::Time| *t),
+            #[cfg(feature = "time")]
+            Some(ConditionValue::PrimitiveDateTime(_)) => {
+                bind_elements!(PrimitiveDateTime, |dt: &time::PrimitiveDateTime| *dt)
+            }
+            #[cfg(feature = "time")]
+            Some(ConditionValue::OffsetDateTime(_)) => {
+                bind_elements!(OffsetDateTime, |dt: &time::OffsetDateTime| *dt)
+            }
+            #[cfg(feature = "rust_decimal")]
+            Some(ConditionValue::Decimal(_)) => {
+                bind_elements!(Decimal, |d: &rust_decimal::Decimal| *d)
+            }
+            Some(value) => {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "{:?} values cannot be array elements",
+                    value.kind()
+                )));
+            }
+        };
+
+        result.map_err(|e| RepositoryError::QueryError(format!("failed to bind value: {}", e)))?;
+        Ok(Some(format!("${}", args.len())))
+    }
+
+    /// Reads a row into a map from column names to values
+    ///
+    /// Columns are read by their Postgres type. `NUMERIC` values without
+    /// fractional digits, such as sums of integers, become integers; others
+    /// become decimals with the `rust_decimal` feature and floats without.
+    /// Enums are read as strings, domains as their base type and arrays of
+    /// supported element types as lists. Types without a matching
+    /// `ConditionValue` variant are reported as `RepositoryError::InvalidInput`.
+    pub fn decode_row(row: &PgRow) -> Result<AggregateRow, RepositoryError> {
+        let mut values = AggregateRow::with_capacity(row.len());
+        for column in row.columns() {
+            values.insert(
+                column.name().to_string(),
+                Self::decode_column(row, column.ordinal())?,
+            );
+        }
+        Ok(values)
+    }
+
+    /// Reads the `i`th column of a row by its Postgres type
+    pub(crate) fn decode_column(row: &PgRow, i: usize) -> Result<ConditionValue, RepositoryError> {
+        Self::decode_value(row, i)?.ok_or_else(|| {
+            let column = row.column(i);
+            RepositoryError::InvalidInput(format!(
+                "column '{}' has unsupported type {}",
+                column.name(),
+                column.type_info().name()
+            ))
+        })
+    }
+
+    /// Reads a related row into a map, leaving out the parent key and the
+    /// columns whose type has no matching `ConditionValue` variant
+    pub(crate) fn decode_related_row(row: &PgRow) -> Result<RelatedRow, RepositoryError> {
+        let mut values = RelatedRow::with_capacity(row.len());
+        for column in row
+            .columns()
+            .iter()
+            .filter(|column| column.name() != PARENT_KEY_COLUMN)
+        {
+            if let Some(value) = Self::decode_value(row, column.ordinal())? {
+                values.insert(column.name().to_string(), value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Reads the `i`th column of a row, or returns `None` if its type has no
+    /// matching `ConditionValue` variant
+    ///
+    /// Enums are read as strings, domains as their base type and arrays of
+    /// supported element types as lists.
+    fn decode_value(row: &PgRow, i: usize) -> Result<Option<ConditionValue>, RepositoryError> {
+        if row.try_get_raw(i)?.is_null() {
+            return Ok(Some(ConditionValue::Null));
+        }
+        let type_info = row.column(i).type_info();
+        match type_info.kind() {
+            PgTypeKind::Enum(_) => Ok(Some(row.try_get_unchecked::<String, _>(i)?.into())),
+            PgTypeKind::Domain(base) => Self::decode_scalar(row, i, base.name()),
+            PgTypeKind::Array(element) => Self::decode_array(row, i, element.name()),
+            _ => Self::decode_scalar(row, i, type_info.name()),
+        }
+    }
+
+    /// Reads a value of the named scalar type
+    fn decode_scalar(
+        row: &PgRow,
+        i: usize,
+        type_name: &str,
+    ) -> Result<Option<ConditionValue>, RepositoryError> {
+        Ok(Some(match type_name {
+            "BOOL" => row.try_get_unchecked::<bool, _>(i)?.into(),
+            "INT2" => i64::from(row.try_get_unchecked::<i16, _>(i)?).into(),
+            "INT4" => i64::from(row.try_get_unchecked::<i32, _>(i)?).into(),
+            "INT8" => row.try_get_unchecked::<i64, _>(i)?.into(),
+            "FLOAT4" => f64::from(row.try_get_unchecked::<f32, _>(i)?).into(),
+            "FLOAT8" => row.try_get_unchecked::<f64, _>(i)?.into(),
+            "NUMERIC" => Self::decode_numeric(row, i)?,
+            "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => row.try_get_unchecked::<String, _>(i)?.into(),
+            "BYTEA" => row.try_get_unchecked::<Vec<u8>, _>(i)?.into(),
+            #[cfg(feature = "uuid")]
+            "UUID" => row.try_get_unchecked::<uuid::Uuid, _>(i)?.into(),
+            #[cfg(feature = "chrono")]
+            "DATE" => row.try_get_unchecked::<chrono::NaiveDate, _>(i)?.into(),
+            #[cfg(feature = "chrono")]
+            "TIME" => row.try_get_unchecked::<chrono::NaiveTime, _>(i)?.into(),
+            #[cfg(feature = "chrono")]
+            "TIMESTAMP" => row.try_get_unchecked::<chrono::NaiveDateTime, _>(i)?.into(),
+            #[cfg(feature = "chrono")]
+            "TIMESTAMPTZ" => row
+                .try_get_unchecked::<chrono::DateTime<chrono::Utc>, _>(i)?
+                .into(),
+            #[cfg(all(feature = "time", not(feature = "chrono")))]
+            "DATE" => row.try_get_unchecked::<time::Date, _>(i)?.into(),
+            #[cfg(all(feature = "time", not(feature = "chrono")))]
+            "TIME" => row.try_get_unchecked::<time::Time, _>(i)?.into(),
+            #[cfg(all(feature = "time", not(feature = "chrono")))]
+            "TIMESTAMP" => row
+                .try_get_unchecked::<time::PrimitiveDateTime, _>(i)?
+                .into(),
+            #[cfg(all(feature = "time", not(feature = "chrono")))]
+            "TIMESTAMPTZ" => row.try_get_unchecked::<time::OffsetDateTime, _>(i)?.into(),
+            #[cfg(feature = "json")]
+            "JSON" | "JSONB" => row.try_get_unchecked::<serde_json::Value, _>(i)?.into(),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Reads an array whose elements are of the named type as a list
+    fn decode_array(
+        row: &PgRow,
+        i: usize,
+        element: &str,
+    ) -> Result<Option<ConditionValue>, RepositoryError> {
+        // Reads the elements as `Vec<Option<T>>`, NULL elements becoming `Null`
+        macro_rules! elements {
+            ($ty:ty, $convert:expr) => {
+                row.try_get_unchecked::<Vec<Option<$ty>>, _>(i)?
+                    .into_iter()
+                    .map(|value| value.map_or(ConditionValue::Null, $convert))
+                    .collect()
+            };
+        }
+
+        let values: Vec<ConditionValue> = match element {
+            "BOOL" => elements!(bool, ConditionValue::from),
+            "INT2" => elements!(i16, |v| i64::from(v).into()),
+            "INT4" => elements!(i32, |v| i64::from(v).into()),
+            "INT8" => elements!(i64, ConditionValue::from),
+            "FLOAT4" => elements!(f32, |v| f64::from(v).into()),
+            "FLOAT8" => elements!(f64, ConditionValue::from),
+            "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => elements!(String, ConditionValue::from),
+            #[cfg(feature = "uuid")]
+            "UUID" => elements!(uuid::Uuid, ConditionValue::from),
+            _ => return Ok(None),
+        };
+        Ok(Some(ConditionValue::List(values)))
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    fn decode_numeric(row: &PgRow, i: usize) -> Result<ConditionValue, RepositoryError> {
+        let value = row.try_get_unchecked::<rust_decimal::Decimal, _>(i)?;
+        Ok(match i64::try_from(value) {
+            Ok(integer) if value.scale() == 0 => ConditionValue::Integer(integer),
+            _ => ConditionValue::Decimal(value),
+        })
+    }
+
+    #[cfg(not(feature = "rust_decimal"))]
+    fn decode_numeric(row: &PgRow, i: usize) -> Result<ConditionValue, RepositoryError> {
+        let raw = row.try_get_raw(i)?;
+        let value = match raw.format() {
+            PgValueFormat::Binary => raw.as_bytes().ok().and_then(parse_numeric),
+            PgValueFormat::Text => raw.as_str().ok().and_then(|text| {
+                text.parse::<i64>()
+                    .map(ConditionValue::Integer)
+                    .or_else(|_| text.parse::<f64>().map(ConditionValue::Float))
+                    .ok()
+            }),
+        };
+        value.ok_or_else(|| RepositoryError::QueryError("invalid NUMERIC value".to_string()))
+    }
+}
 
-        let conditions: Vec<String> = criteria
-            .conditions
-            .iter()
-            .map(|condition| {
-                let field = &condition.field;
-                let value = &condition.value;
+/// Parses the binary form of a `NUMERIC`: a header of digit count, weight,
+/// sign and display scale, followed by base-10000 digits, the first of which
+/// is multiplied by `10000^weight`
+///
+/// The sign word also marks NaN and the infinities, which become floats.
+#[cfg(not(feature = "rust_decimal"))]
+fn parse_numeric(bytes: &[u8]) -> Option<ConditionValue> {
+    const POSITIVE: u16 = 0x0000;
+    const NEGATIVE: u16 = 0x4000;
+    const NAN: u16 = 0xC000;
+    const INFINITY: u16 = 0xD000;
+    const NEG_INFINITY: u16 = 0xF000;
 
-                match condition.operator {
-                    Operator::Equal => {
-                        format!("{} = {}", field, Self::format_value(value))
-                    }
-                    Operator::NotEqual => {
-                        format!("{} != {}", field, Self::format_value(value))
-                    }
-                    Operator::GreaterThan => {
-                        format!("{} > {}", field, Self::format_value(value))
-                    }
-                    Operator::GreaterThanOrEqual => {
-                        format!("{} >= {}", field, Self::format_value(value))
-                    }
-                    Operator::LessThan => {
-                        format!("{} < {}", field, Self::format_value(value))
-                    }
-                    Operator::LessThanOrEqual => {
-                        format!("{} <= {}", field, Self::format_value(value))
-                    }
-                    Operator::Like => {
-                        format!("{} ILIKE {}", field, Self::format_value(value))
-                    }
-                    Operator::IsNull => {
-                        format!("{} IS NULL", field)
-                    }
-                    Operator::IsNotNull => {
-                        format!("{} IS NOT NULL", field)
-                    }
-                    Operator::In => {
-                        if let ConditionValue::List(values) = value {
-                            let formatted_values: Vec<String> =
-                                values.iter().map(Self::format_value).collect();
-                            format!("{} IN ({})", field, formatted_values.join(", "))
-                        } else {
-                            format!("{} = {}", field, Self::format_value(value))
-                        }
-                    }
-                }
-            })
-            .collect();
+    let words: Vec<i16> = bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_be_bytes([b[0], b[1]]))
+        .collect();
+    let (&[count, weight, sign, scale], digits) = words.split_first_chunk::<4>()?;
+    if digits.len() != usize::try_from(count).ok()? {
+        return None;
+    }
+    let sign = match sign as u16 {
+        NAN => return Some(ConditionValue::Float(f64::NAN)),
+        INFINITY => return Some(ConditionValue::Float(f64::INFINITY)),
+        NEG_INFINITY => return Some(ConditionValue::Float(f64::NEG_INFINITY)),
+        sign @ (POSITIVE | NEGATIVE) => sign,
+        _ => return None,
+    };
+    let negate = |v: f64| if sign == NEGATIVE { -v } else { v };
 
-        conditions.join(" AND ")
+    if scale == 0 && weight >= 0 {
+        let integer = (0..=weight as usize).try_fold(0i64, |value, i| {
+            value
+                .checked_mul(10_000)?
+                .checked_add(i64::from(*digits.get(i).unwrap_or(&0)))
+        });
+        if let Some(integer) = integer {
+            return Some(ConditionValue::Integer(if sign == NEGATIVE {
+                -integer
+            } else {
+                integer
+            }));
+        }
     }
 
-    /// Formats a ConditionValue for SQL (with proper escaping)
-    ///
-    /// Note: This uses PostgreSQL's dollar-quoted strings for safety
-    fn format_value(value: &ConditionValue) -> String {
-        match value {
-            ConditionValue::String(s) => {
-                // Use PostgreSQL dollar quoting to avoid SQL injection
-                // Escape single quotes by doubling them
-                let escaped = s.replace("'", "''");
-                format!("'{}'", escaped)
-            }
-            ConditionValue::Integer(i) => i.to_string(),
-            ConditionValue::Float(f) => f.to_string(),
-            ConditionValue::Boolean(b) => {
-                if *b {
-                    "TRUE".to_string()
-                } else {
-                    "FALSE".to_string()
-                }
-            }
-            ConditionValue::Null => "NULL".to_string(),
-            ConditionValue::List(values) => {
-                let formatted: Vec<String> = values.iter().map(Self::format_value).collect();
-                format!("({})", formatted.join(", "))
-            }
+    let value: f64 = digits
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| f64::from(*digit) * 10_000f64.powi(i32::from(weight) - i as i32))
+        .sum();
+    Some(ConditionValue::Float(negate(value)))
+}
+
+/// Escapes LIKE wildcards so the value matches literally
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
         }
+        escaped.push(c);
     }
+    escaped
 }
 
 /// Extension trait for executing wyvern queries with SQLx
 #[async_trait::async_trait]
 pub trait WyvernSqlxExt {
     /// Execute a filter query and return all matching entities
+    ///
+    /// Selects every column unless the criteria has a projection, so `T` can
+    /// be a smaller type read from just the projected columns.
     async fn filter_entities<T>(
         &self,
         table_name: &str,
         criteria: &FilterCriteria,
-    ) -> Result<Vec<T>, sqlx::Error>
+    ) -> Result<Vec<T>, RepositoryError>
     where
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Unpin + Send;
 
@@ -197,7 +1511,23 @@ pub trait WyvernSqlxExt {
         &self,
         table_name: &str,
         criteria: &FilterCriteria,
-    ) -> Result<i64, sqlx::Error>;
+    ) -> Result<i64, RepositoryError>;
+
+    /// Execute an aggregation query and read each group into `T`
+    async fn aggregate_entities<T>(
+        &self,
+        table_name: &str,
+        query: &AggregateQuery,
+    ) -> Result<Vec<T>, RepositoryError>
+    where
+        T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Unpin + Send;
+
+    /// Execute an aggregation query and return each group as a map of values
+    async fn aggregate_rows(
+        &self,
+        table_name: &str,
+        query: &AggregateQuery,
+    ) -> Result<Vec<AggregateRow>, RepositoryError>;
 }
 
 #[async_trait::async_trait]
@@ -206,38 +1536,71 @@ impl WyvernSqlxExt for PgPool {
         &self,
         table_name: &str,
         criteria: &FilterCriteria,
-    ) -> Result<Vec<T>, sqlx::Error>
+    ) -> Result<Vec<T>, RepositoryError>
     where
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Unpin + Send,
     {
-        let query = SqlxAdapter::build_select_query(table_name, criteria);
-        sqlx::query_as::<_, T>(&query).fetch_all(self).await
+        let (query, args) = SqlxAdapter::build_select_query(table_name, criteria)?;
+        let rows = sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_all(self)
+            .await?;
+        Ok(rows)
     }
 
     async fn count_entities(
         &self,
         table_name: &str,
         criteria: &FilterCriteria,
-    ) -> Result<i64, sqlx::Error> {
-        let query = SqlxAdapter::build_count_query(table_name, criteria);
-        sqlx::query_scalar::<_, i64>(&query).fetch_one(self).await
+    ) -> Result<i64, RepositoryError> {
+        let (query, args) = SqlxAdapter::build_count_query(table_name, criteria)?;
+        let count = sqlx::query_scalar_with::<_, i64, _>(&query, args)
+            .fetch_one(self)
+            .await?;
+        Ok(count)
+    }
+
+    async fn aggregate_entities<T>(
+        &self,
+        table_name: &str,
+        query: &AggregateQuery,
+    ) -> Result<Vec<T>, RepositoryError>
+    where
+        T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Unpin + Send,
+    {
+        let (query, args) = SqlxAdapter::build_aggregate_query(table_name, query)?;
+        let rows = sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_all(self)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn aggregate_rows(
+        &self,
+        table_name: &str,
+        query: &AggregateQuery,
+    ) -> Result<Vec<AggregateRow>, RepositoryError> {
+        let (query, args) = SqlxAdapter::build_aggregate_query(table_name, query)?;
+        let rows = sqlx::query_with(&query, args).fetch_all(self).await?;
+        rows.iter().map(SqlxAdapter::decode_row).collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Condition, SortOrder};
+    use crate::{Aggregate, SortOrder};
 
     #[test]
     fn test_build_simple_query() {
         let criteria =
             FilterCriteria::new().with_condition(Condition::eq("provider", "openai".into()));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, args) =
+            SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
 
-        assert!(query.contains("SELECT * FROM llm_model_pricing"));
-        assert!(query.contains("WHERE provider = 'openai'"));
+        assert!(query.contains("SELECT * FROM \"llm_model_pricing\""));
+        assert!(query.contains("WHERE \"provider\" = $1"));
+        assert_eq!(args.len(), 1);
     }
 
     #[test]
@@ -246,11 +1609,13 @@ mod tests {
             .with_condition(Condition::eq("provider", "openai".into()))
             .with_condition(Condition::gt("price", 10.into()));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, args) =
+            SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
 
-        assert!(query.contains("provider = 'openai'"));
-        assert!(query.contains("price > 10"));
+        assert!(query.contains("\"provider\" = $1"));
+        assert!(query.contains("\"price\" > $2"));
         assert!(query.contains("AND"));
+        assert_eq!(args.len(), 2);
     }
 
     #[test]
@@ -259,21 +1624,34 @@ mod tests {
             .with_sort(SortOrder::asc("model_name"))
             .with_sort(SortOrder::desc("created_at"));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, _) = SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
 
-        assert!(query.contains("ORDER BY model_name ASC, created_at DESC"));
+        assert!(query.contains("ORDER BY \"model_name\" ASC, \"created_at\" DESC"));
     }
 
     #[test]
     fn test_build_query_with_limit_offset() {
         let criteria = FilterCriteria::new().with_limit(10).with_offset(20);
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, _) = SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
 
         assert!(query.contains("LIMIT 10"));
         assert!(query.contains("OFFSET 20"));
     }
 
+    #[test]
+    fn test_negative_limit_and_offset_are_rejected() {
+        for criteria in [
+            FilterCriteria::new().with_limit(-5),
+            FilterCriteria::new().with_limit(10).with_offset(-1),
+        ] {
+            assert!(matches!(
+                SqlxAdapter::build_select_query("users", &criteria),
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_build_query_with_null_check() {
         let criteria = FilterCriteria::new().with_condition(Condition::new(
@@ -282,28 +1660,75 @@ mod tests {
             ConditionValue::Null,
         ));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, args) =
+            SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
 
-        assert!(query.contains("valid_to IS NULL"));
+        assert!(query.contains("\"valid_to\" IS NULL"));
+        assert_eq!(args.len(), 0);
     }
 
     #[test]
-    fn test_format_string_with_quotes() {
-        let value = ConditionValue::String("O'Reilly".to_string());
-        let formatted = SqlxAdapter::format_value(&value);
+    fn test_string_values_are_never_inlined() {
+        let criteria = FilterCriteria::new().with_condition(Condition::eq(
+            "author",
+            "O'Reilly\\'; DROP TABLE books; --".into(),
+        ));
+
+        let (query, args) = SqlxAdapter::build_select_query("books", &criteria).unwrap();
+
+        assert_eq!(query, "SELECT * FROM \"books\" WHERE \"author\" = $1");
+        assert_eq!(args.len(), 1);
+    }
+
+    #[test]
+    fn test_strings_and_nulls_are_bound_untyped() {
+        // An unspecified type lets Postgres read them as the column's type
+        assert_eq!(
+            <Untyped<String> as Type<Postgres>>::type_info().oid(),
+            Some(Oid(0))
+        );
+
+        let mut buf = PgArgumentBuffer::default();
+        let null = Untyped(None::<String>).encode_by_ref(&mut buf).unwrap();
+        assert!(matches!(null, IsNull::Yes));
+
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::json_contains("meta", r#"{"a":1}"#.into()))
+            .with_condition(Condition::json_path_exists("meta", "$.a ? (@ > 0)"));
+        let (query, args) = SqlxAdapter::build_select_query("docs", &criteria).unwrap();
 
-        // Should escape the single quote
-        assert_eq!(formatted, "'O''Reilly'");
+        // Cast parameters stay text so they are converted rather than decoded
+        assert_eq!(
+            query,
+            "SELECT * FROM \"docs\" WHERE \"meta\" @> $1::jsonb AND \"meta\" @? $2::jsonpath"
+        );
+        assert_eq!(args.len(), 2);
     }
 
     #[test]
     fn test_build_count_query() {
         let criteria = FilterCriteria::new().with_condition(Condition::eq("active", true.into()));
 
-        let query = SqlxAdapter::build_count_query("users", &criteria);
+        let (query, args) = SqlxAdapter::build_count_query("users", &criteria).unwrap();
+
+        assert!(query.contains("SELECT COUNT(*) FROM \"users\""));
+        assert!(query.contains("WHERE \"active\" = $1"));
+        assert_eq!(args.len(), 1);
+    }
+
+    #[test]
+    fn test_build_exists_query() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("email", "a@b.c".into()))
+            .with_limit(5);
 
-        assert!(query.contains("SELECT COUNT(*) FROM users"));
-        assert!(query.contains("WHERE active = TRUE"));
+        let (query, args) = SqlxAdapter::build_exists_query("users", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT EXISTS(SELECT 1 FROM \"users\" WHERE \"email\" = $1)"
+        );
+        assert_eq!(args.len(), 1);
     }
 
     #[test]
@@ -314,9 +1739,73 @@ mod tests {
             ConditionValue::String("%gpt%".to_string()),
         ));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, _) = SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
+
+        assert!(query.contains("\"model_name\" ILIKE $1"));
+    }
+
+    #[test]
+    fn test_pattern_operators() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::not_like("name", "%bot%"))
+            .with_condition(Condition::case_sensitive_like("code", "AB%"))
+            .with_condition(Condition::starts_with("name", "50%_"))
+            .with_condition(Condition::ends_with("email", ".org"))
+            .with_condition(Condition::contains("path", "C:\\"));
+
+        let (query, args) = SqlxAdapter::build_select_query("items", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"items\" WHERE \"name\" NOT ILIKE $1 AND \"code\" LIKE $2 \
+             AND \"name\" ILIKE $3 AND \"email\" ILIKE $4 AND \"path\" ILIKE $5"
+        );
+        assert_eq!(args.len(), 5);
+        assert_eq!(escape_like("50%_"), "50\\%\\_");
+        assert_eq!(escape_like("C:\\"), "C:\\\\");
+
+        let not_text = FilterCriteria::new().with_condition(Condition::new(
+            "name",
+            Operator::StartsWith,
+            1.into(),
+        ));
+        assert!(SqlxAdapter::build_select_query("items", &not_text).is_err());
+    }
+
+    #[test]
+    fn test_between_and_not_in_operators() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::between("price", 1.into(), 9.5.into()))
+            .with_condition(Condition::not_in("status", vec!["a".into(), "b".into()]))
+            .with_condition(Condition::not_in("tag", vec![]));
+
+        let (query, args) = SqlxAdapter::build_select_query("items", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"items\" WHERE \"price\" BETWEEN $1 AND $2 \
+             AND \"status\" NOT IN ($3, $4) AND TRUE"
+        );
+        assert_eq!(args.len(), 4);
+
+        let one_bound = FilterCriteria::new().with_condition(Condition::new(
+            "price",
+            Operator::Between,
+            ConditionValue::List(vec![1.into()]),
+        ));
+        assert!(SqlxAdapter::build_select_query("items", &one_bound).is_err());
 
-        assert!(query.contains("model_name ILIKE '%gpt%'"));
+        for operator in [Operator::Equal, Operator::GreaterThan, Operator::Like] {
+            let list = FilterCriteria::new().with_condition(Condition::new(
+                "status",
+                operator,
+                ConditionValue::List(vec!["a".into(), "b".into()]),
+            ));
+            assert!(matches!(
+                SqlxAdapter::build_select_query("items", &list),
+                Err(RepositoryError::InvalidInput(msg)) if msg.contains("does not take a list")
+            ));
+        }
     }
 
     #[test]
@@ -330,8 +1819,734 @@ mod tests {
             ]),
         ));
 
-        let query = SqlxAdapter::build_select_query("llm_model_pricing", &criteria);
+        let (query, args) =
+            SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
+
+        assert!(query.contains("\"provider\" IN ($1, $2)"));
+        assert_eq!(args.len(), 2);
+    }
+
+    #[test]
+    fn test_in_operator_with_empty_list() {
+        let criteria = FilterCriteria::new().with_condition(Condition::in_list("provider", vec![]));
+
+        let (query, args) =
+            SqlxAdapter::build_select_query("llm_model_pricing", &criteria).unwrap();
+
+        assert!(query.contains("WHERE FALSE"));
+        assert_eq!(args.len(), 0);
+    }
+
+    #[test]
+    fn test_placeholders_continue_after_list() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::in_list("provider", vec!["a".into(), "b".into()]))
+            .with_condition(Condition::lt("price", 2.5.into()));
+
+        let (query, args) = SqlxAdapter::build_count_query("llm_model_pricing", &criteria).unwrap();
+
+        assert!(query.contains("\"provider\" IN ($1, $2) AND \"price\" < $3"));
+        assert_eq!(args.len(), 3);
+    }
+
+    #[test]
+    fn test_identifier_quotes_qualified_names() {
+        let ident = Identifier::parse("billing.invoices").unwrap();
+
+        assert_eq!(ident.to_string(), "\"billing\".\"invoices\"");
+        assert_eq!(ident.parts(), ["billing", "invoices"]);
+    }
+
+    #[test]
+    fn test_identifier_rejects_invalid_names() {
+        for name in [
+            "",
+            "1abc",
+            "name; DROP TABLE users",
+            "a\"b",
+            "a..b",
+            "a.b.c.d",
+            "col -- comment",
+            &"x".repeat(64),
+        ] {
+            assert!(
+                matches!(
+                    Identifier::parse(name),
+                    Err(RepositoryError::InvalidInput(_))
+                ),
+                "expected '{}' to be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_field_is_rejected() {
+        let criteria = FilterCriteria::new().with_condition(Condition::eq("id = 1 OR 1", 1.into()));
+
+        let result = SqlxAdapter::build_select_query("users", &criteria);
+
+        assert!(matches!(result, Err(RepositoryError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_invalid_sort_field_is_rejected() {
+        let criteria = FilterCriteria::new().with_sort(SortOrder::asc("name; DROP TABLE users"));
+
+        let result = SqlxAdapter::build_select_query("users", &criteria);
+
+        assert!(matches!(result, Err(RepositoryError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_invalid_table_name_is_rejected() {
+        let result = SqlxAdapter::build_count_query("users u, secrets", &FilterCriteria::new());
+
+        assert!(matches!(result, Err(RepositoryError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_or_with_nested_and_not() {
+        let criteria = FilterCriteria::new().with_filter(FilterExpr::or([
+            FilterExpr::from(Condition::eq("status", "active".into())),
+            FilterExpr::and([
+                FilterExpr::from(Condition::eq("role", "admin".into())),
+                FilterExpr::not(Condition::eq("banned", true.into())),
+            ]),
+        ]));
+
+        let (query, args) = SqlxAdapter::build_select_query("users", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"users\" WHERE \"status\" = $1 OR (\"role\" = $2 AND NOT (\"banned\" = $3))"
+        );
+        assert_eq!(args.len(), 3);
+    }
+
+    #[test]
+    fn test_conditions_and_filters_are_combined_with_and() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("tenant_id", 7.into()))
+            .with_any([
+                Condition::eq("status", "active".into()),
+                Condition::eq("status", "trial".into()),
+            ])
+            .with_not(Condition::new(
+                "deleted_at",
+                Operator::IsNotNull,
+                ConditionValue::Null,
+            ));
+
+        let (query, _) = SqlxAdapter::build_count_query("accounts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT COUNT(*) FROM \"accounts\" WHERE \"tenant_id\" = $1 AND (\"status\" = $2 OR \"status\" = $3) AND NOT (\"deleted_at\" IS NOT NULL)"
+        );
+    }
+
+    #[test]
+    fn test_single_member_group_keeps_precedence() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("a", 1.into()))
+            .with_filter(FilterExpr::and([FilterExpr::or([
+                Condition::eq("b", 2.into()),
+                Condition::eq("c", 3.into()),
+            ])]));
+
+        let (query, _) = SqlxAdapter::build_count_query("t", &criteria).unwrap();
+
+        assert!(query.ends_with("WHERE \"a\" = $1 AND (\"b\" = $2 OR \"c\" = $3)"));
+    }
+
+    #[test]
+    fn test_empty_groups() {
+        let criteria = FilterCriteria::new()
+            .with_filter(FilterExpr::Or(vec![]))
+            .with_filter(FilterExpr::not(FilterExpr::And(vec![])));
+
+        let (query, _) = SqlxAdapter::build_count_query("users", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT COUNT(*) FROM \"users\" WHERE FALSE AND NOT (TRUE)"
+        );
+    }
+
+    #[test]
+    fn test_seek_renders_row_values_per_direction() {
+        let table = Identifier::parse("events").unwrap();
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("kind", "click".into()))
+            .with_sort(SortOrder::asc("day"))
+            .with_sort(SortOrder::asc("hour"))
+            .with_sort(SortOrder::desc("score"))
+            .with_sort(SortOrder::asc("id"));
+        let seek = Seek {
+            sort: criteria.sort.clone(),
+            values: vec![3.into(), 12.into(), 0.5.into(), 42.into()],
+        };
+
+        let (query, args) =
+            SqlxAdapter::build_select_seek(&table, &[], &[], &criteria, Some(&seek)).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"events\" WHERE (\"kind\" = $1) AND \
+             ((\"day\", \"hour\") > ($2, $3) \
+             OR ((\"day\", \"hour\") = ($2, $3) AND \"score\" < $4) \
+             OR ((\"day\", \"hour\") = ($2, $3) AND \"score\" = $4 AND \"id\" > $5)) \
+             ORDER BY \"day\" ASC, \"hour\" ASC, \"score\" DESC, \"id\" ASC"
+        );
+        assert_eq!(args.len(), 5);
+    }
+
+    #[test]
+    fn test_seek_with_single_direction_is_one_row_comparison() {
+        let table = Identifier::parse("events").unwrap();
+        let criteria = FilterCriteria::new()
+            .with_sort(SortOrder::desc("created_at"))
+            .with_sort(SortOrder::desc("id"));
+        let seek = Seek {
+            sort: criteria.sort.clone(),
+            values: vec!["2024-01-01".into(), 7.into()],
+        };
+
+        let (query, _) =
+            SqlxAdapter::build_select_seek(&table, &[], &[], &criteria, Some(&seek)).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"events\" WHERE (\"created_at\", \"id\") < ($1, $2) \
+             ORDER BY \"created_at\" DESC, \"id\" DESC"
+        );
+    }
+
+    #[test]
+    fn test_array_operators_bind_one_array() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::array_overlaps(
+                "tags",
+                vec!["rust".into(), "sql".into()],
+            ))
+            .with_condition(Condition::array_contains("tags", vec!["rust".into()]))
+            .with_condition(Condition::array_contained_by(
+                "scores",
+                vec![1.into(), ConditionValue::Null, 3.into()],
+            ))
+            .with_condition(Condition::equals_any(
+                "id",
+                vec![1.into(), 2.into(), 3.into()],
+            ));
+
+        let (query, args) = SqlxAdapter::build_select_query("posts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"posts\" WHERE \"tags\" && $1 AND \"tags\" @> $2 \
+             AND \"scores\" <@ $3 AND \"id\" = ANY($4)"
+        );
+        assert_eq!(args.len(), 4);
+    }
+
+    #[test]
+    fn test_array_operators_with_empty_or_invalid_lists() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::array_overlaps("tags", vec![]))
+            .with_condition(Condition::array_contains("tags", vec![]))
+            .with_condition(Condition::array_contained_by("tags", vec![]))
+            .with_condition(Condition::equals_any("id", vec![]));
+
+        let (query, args) = SqlxAdapter::build_select_query("posts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"posts\" WHERE FALSE AND \"tags\" IS NOT NULL \
+             AND cardinality(\"tags\") = 0 AND FALSE"
+        );
+        assert_eq!(args.len(), 0);
+
+        for condition in [
+            Condition::array_overlaps("tags", vec!["a".into(), 1.into()]),
+            Condition::array_contains("tags", vec![ConditionValue::List(vec![])]),
+            Condition::new("tags", Operator::ArrayContains, "a".into()),
+        ] {
+            let criteria = FilterCriteria::new().with_condition(condition);
+            assert!(matches!(
+                SqlxAdapter::build_select_query("posts", &criteria),
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_text_search_and_rank_sort() {
+        let search = TextSearch::websearch("rust -go").with_config("english");
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::text_search("body", search.clone()))
+            .with_condition(Condition::text_search(
+                "title",
+                TextSearch::phrase("async rust"),
+            ))
+            .with_sort(SortOrder::by_rank("body", search))
+            .with_sort(SortOrder::asc("id"))
+            .with_limit(10);
+
+        let (query, args) = SqlxAdapter::build_select_query("posts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"posts\" \
+             WHERE to_tsvector($1::regconfig, \"body\") @@ websearch_to_tsquery($1::regconfig, $2) \
+             AND to_tsvector(\"title\") @@ phraseto_tsquery($3) \
+             ORDER BY ts_rank(to_tsvector($4::regconfig, \"body\"), \
+             websearch_to_tsquery($4::regconfig, $5)) DESC, \"id\" ASC LIMIT 10"
+        );
+        assert_eq!(args.len(), 5);
+
+        let misused = FilterCriteria::new().with_condition(Condition::new(
+            "body",
+            Operator::TextSearch,
+            "rust".into(),
+        ));
+        assert!(SqlxAdapter::build_select_query("posts", &misused).is_err());
+    }
+
+    #[test]
+    fn test_text_search_on_tsvector_column() {
+        let search = TextSearch::plain("rust")
+            .with_config("english")
+            .on_tsvector();
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::text_search("search_vector", search.clone()))
+            .with_sort(SortOrder::by_rank("search_vector", search));
+
+        let (query, args) = SqlxAdapter::build_select_query("posts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"posts\" \
+             WHERE \"search_vector\" @@ plainto_tsquery($1::regconfig, $2) \
+             ORDER BY ts_rank(\"search_vector\", plainto_tsquery($3::regconfig, $4)) DESC"
+        );
+        assert_eq!(args.len(), 4);
+    }
+
+    #[test]
+    fn test_projection_replaces_select_star() {
+        let criteria = FilterCriteria::new()
+            .with_columns(["id", "users.name"])
+            .with_projection(Projection::new("metadata->>plan").with_alias("plan"))
+            .with_condition(Condition::eq("status", "active".into()));
+
+        let (query, args) = SqlxAdapter::build_select_query("users", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT \"id\", \"users\".\"name\", \"metadata\"->>$1 AS \"plan\" \
+             FROM \"users\" WHERE \"status\" = $2"
+        );
+        assert_eq!(args.len(), 2);
+
+        // The projection takes precedence over the repository's columns
+        let table = Identifier::parse("users").unwrap();
+        let columns = [Identifier::parse("id").unwrap()];
+        let (query, _) = SqlxAdapter::build_select_columns(
+            &table,
+            &[],
+            &columns,
+            &FilterCriteria::new().with_columns(["email"]),
+        )
+        .unwrap();
+        assert_eq!(query, "SELECT \"email\" FROM \"users\"");
+
+        for projection in [
+            Projection::new("email; --"),
+            Projection::new("email").with_alias("users.contact"),
+            Projection::new("email").with_alias("x\" FROM secrets --"),
+        ] {
+            let criteria = FilterCriteria::new().with_projection(projection.clone());
+            assert!(
+                SqlxAdapter::build_select_query("users", &criteria).is_err(),
+                "{:?}",
+                projection
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_query() {
+        let query = AggregateQuery::new(
+            FilterCriteria::new()
+                .with_condition(Condition::eq("status", "paid".into()))
+                .with_sort(SortOrder::desc("total"))
+                .with_limit(10),
+        )
+        .with_group_by("customer_id")
+        .with_aggregate(Aggregate::count_all("orders"))
+        .with_aggregate(Aggregate::count_distinct("coupon", "coupons"))
+        .with_aggregate(Aggregate::sum("amount", "total"))
+        .with_having(Condition::gt("orders", 5.into()));
+
+        let (sql, args) = SqlxAdapter::build_aggregate_query("orders", &query).unwrap();
+
+        assert_eq!(
+            sql,
+            "SELECT * FROM (SELECT \"customer_id\", COUNT(*) AS \"orders\", \
+             COUNT(DISTINCT \"coupon\") AS \"coupons\", SUM(\"amount\") AS \"total\" \
+             FROM \"orders\" WHERE \"status\" = $1 GROUP BY \"customer_id\") AS \"groups\" \
+             WHERE \"orders\" > $2 ORDER BY \"total\" DESC LIMIT 10"
+        );
+        assert_eq!(args.len(), 2);
+
+        let (sql, _) = SqlxAdapter::build_aggregate_query(
+            "orders",
+            &AggregateQuery::new(FilterCriteria::new())
+                .with_aggregate(Aggregate::avg("amount", "average")),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM (SELECT AVG(\"amount\") AS \"average\" FROM \"orders\") AS \"groups\""
+        );
+
+        for query in [
+            AggregateQuery::new(FilterCriteria::new()).with_group_by("orders.customer_id"),
+            AggregateQuery::new(FilterCriteria::new())
+                .with_aggregate(Aggregate::sum("amount; --", "total")),
+            AggregateQuery::new(FilterCriteria::new()).with_aggregate(Aggregate::count_all("a b")),
+        ] {
+            assert!(
+                SqlxAdapter::build_aggregate_query("orders", &query).is_err(),
+                "{:?}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_relation_conditions_render_exists_subqueries() {
+        let relations = [
+            Relation::many_to_one("customer", "customers", "customer_id"),
+            Relation::one_to_many("lines", "order_lines", "order_id"),
+            Relation::many_to_many("tags", "tags", "order_tags", "order_id", "tag_id")
+                .with_target_key("tag_id"),
+        ];
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("customer.country", "FR".into()))
+            .with_filter(FilterExpr::not(Condition::gt("lines.quantity", 10.into())))
+            .with_filter(FilterExpr::or([
+                Condition::eq("tags.name", "gift".into()),
+                Condition::eq("status", "open".into()),
+            ]));
+
+        let (query, args) =
+            SqlxAdapter::build_select_query_with_relations("orders", &relations, &criteria)
+                .unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"orders\" WHERE \
+             EXISTS (SELECT 1 FROM \"customers\" AS \"customer\" \
+             WHERE \"customer\".\"id\" = \"orders\".\"customer_id\" \
+             AND \"customer\".\"country\" = $1) \
+             AND NOT (EXISTS (SELECT 1 FROM \"order_lines\" AS \"lines\" \
+             WHERE \"lines\".\"order_id\" = \"orders\".\"id\" AND \"lines\".\"quantity\" > $2)) \
+             AND (EXISTS (SELECT 1 FROM \"order_tags\" \
+             JOIN \"tags\" AS \"tags\" ON \"tags\".\"tag_id\" = \"order_tags\".\"tag_id\" \
+             WHERE \"order_tags\".\"order_id\" = \"orders\".\"id\" AND \"tags\".\"name\" = $3) \
+             OR \"status\" = $4)"
+        );
+        assert_eq!(args.len(), 4);
+
+        // Without relations the field is a qualified column
+        let (query, _) = SqlxAdapter::build_count_query("orders", &criteria).unwrap();
+        assert!(query.contains("WHERE \"customer\".\"country\" = $1"));
+
+        let (query, _) =
+            SqlxAdapter::build_count_query_with_relations("orders", &relations, &criteria).unwrap();
+        assert!(query.starts_with("SELECT COUNT(*) FROM \"orders\" WHERE EXISTS ("));
+    }
+
+    #[test]
+    fn test_relation_null_checks_and_nested_fields() {
+        let relations = [
+            Relation::many_to_one("customer", "customers", "customer_id"),
+            Relation::one_to_many("lines", "order_lines", "order_id"),
+        ];
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::new(
+                "customer.country",
+                Operator::IsNull,
+                ConditionValue::Null,
+            ))
+            .with_condition(Condition::new(
+                "lines.note",
+                Operator::IsNull,
+                ConditionValue::Null,
+            ));
+
+        let (query, _) =
+            SqlxAdapter::build_select_query_with_relations("orders", &relations, &criteria)
+                .unwrap();
+
+        // Orders without a customer have no country either
+        assert_eq!(
+            query,
+            "SELECT * FROM \"orders\" WHERE \
+             NOT EXISTS (SELECT 1 FROM \"customers\" AS \"customer\" \
+             WHERE \"customer\".\"id\" = \"orders\".\"customer_id\" \
+             AND \"customer\".\"country\" IS NOT NULL) \
+             AND EXISTS (SELECT 1 FROM \"order_lines\" AS \"lines\" \
+             WHERE \"lines\".\"order_id\" = \"orders\".\"id\" AND \"lines\".\"note\" IS NULL)"
+        );
+
+        for criteria in [
+            FilterCriteria::new()
+                .with_condition(Condition::eq("customer.address.city", "x".into())),
+            FilterCriteria::new().with_sort(SortOrder::asc("customer.address.city")),
+        ] {
+            assert!(
+                SqlxAdapter::build_select_query_with_relations("orders", &relations, &criteria)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_related_query() {
+        let keys = [3.into(), 5.into()];
+
+        let customer = Relation::many_to_one("customer", "customers", "customer_id")
+            .with_columns(["id", "name"]);
+        let (query, args) = SqlxAdapter::build_related_query(&customer, &keys).unwrap();
+        assert_eq!(
+            query,
+            "SELECT \"customer\".\"id\", \"customer\".\"name\", \
+             \"customer\".\"id\" AS \"wyvern_parent_key\" \
+             FROM \"customers\" AS \"customer\" WHERE \"customer\".\"id\" = ANY($1)"
+        );
+        assert_eq!(args.len(), 1);
+
+        let lines = Relation::one_to_many("lines", "order_lines", "order_id").with_columns(["sku"]);
+        let (query, _) = SqlxAdapter::build_related_query(&lines, &keys).unwrap();
+        assert_eq!(
+            query,
+            "SELECT \"lines\".\"sku\", \"lines\".\"order_id\" AS \"wyvern_parent_key\" \
+             FROM \"order_lines\" AS \"lines\" WHERE \"lines\".\"order_id\" = ANY($1)"
+        );
+
+        let tags = Relation::many_to_many("tags", "tags", "order_tags", "order_id", "tag_id")
+            .with_columns(["name"]);
+        let (query, _) = SqlxAdapter::build_related_query(&tags, &keys).unwrap();
+        assert_eq!(
+            query,
+            "SELECT \"tags\".\"name\", \"order_tags\".\"order_id\" AS \"wyvern_parent_key\" \
+             FROM \"order_tags\" JOIN \"tags\" AS \"tags\" ON \"tags\".\"id\" = \"order_tags\".\"tag_id\" \
+             WHERE \"order_tags\".\"order_id\" = ANY($1)"
+        );
+
+        let (query, args) = SqlxAdapter::build_related_query(&lines, &[]).unwrap();
+        assert!(query.ends_with(" WHERE FALSE"));
+        assert_eq!(args.len(), 0);
+
+        for invalid in [
+            Relation::one_to_many("lines", "order_lines", "order_id; --").with_columns(["sku"]),
+            Relation::one_to_many("lines", "order_lines", "order_id").with_columns(["a.b"]),
+            // Without declared columns nothing may be read
+            Relation::one_to_many("lines", "order_lines", "order_id"),
+        ] {
+            assert!(matches!(
+                SqlxAdapter::build_related_query(&invalid, &keys),
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_sort_by_many_to_one_relation() {
+        let relations = [Relation::many_to_one(
+            "customer",
+            "customers",
+            "customer_id",
+        )];
+        let criteria = FilterCriteria::new()
+            .with_sort(SortOrder::asc("customer.name"))
+            .with_sort(SortOrder::asc("id"));
+
+        let (query, _) =
+            SqlxAdapter::build_select_query_with_relations("orders", &relations, &criteria)
+                .unwrap();
+        assert_eq!(
+            query,
+            "SELECT * FROM \"orders\" ORDER BY \
+             (SELECT \"customer\".\"name\" FROM \"customers\" AS \"customer\" \
+             WHERE \"customer\".\"id\" = \"orders\".\"customer_id\") ASC, \"id\" ASC"
+        );
+
+        let seek = Seek {
+            sort: criteria.sort.clone(),
+            values: vec!["Ada".into(), 7.into()],
+        };
+        let (query, _) = SqlxAdapter::build_select_seek(
+            &Identifier::parse("orders").unwrap(),
+            &relations,
+            &[],
+            &criteria,
+            Some(&seek),
+        )
+        .unwrap();
+        assert!(query.contains(
+            "WHERE ((SELECT \"customer\".\"name\" FROM \"customers\" AS \"customer\" \
+             WHERE \"customer\".\"id\" = \"orders\".\"customer_id\"), \"id\") > ($1, $2)"
+        ));
+
+        let relations = [Relation::one_to_many("lines", "order_lines", "order_id")];
+        let err = SqlxAdapter::build_select_query_with_relations(
+            "orders",
+            &relations,
+            &FilterCriteria::new().with_sort(SortOrder::desc("lines.quantity")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, RepositoryError::InvalidInput(_)));
+    }
+
+    #[cfg(not(feature = "rust_decimal"))]
+    #[test]
+    fn test_parse_binary_numeric() {
+        let numeric =
+            |words: &[i16]| -> Vec<u8> { words.iter().flat_map(|w| w.to_be_bytes()).collect() };
+
+        // 123456789 = 1_2345_6789, weight 2
+        assert_eq!(
+            parse_numeric(&numeric(&[3, 2, 0, 0, 1, 2345, 6789])),
+            Some(ConditionValue::Integer(123_456_789))
+        );
+        // -20000 = -2_0000 with a trailing zero digit dropped
+        assert_eq!(
+            parse_numeric(&numeric(&[1, 1, 0x4000, 0, 2])),
+            Some(ConditionValue::Integer(-20_000))
+        );
+        assert_eq!(
+            parse_numeric(&numeric(&[0, 0, 0, 0])),
+            Some(ConditionValue::Integer(0))
+        );
+        // 12.5 = 12.5000 with a display scale of 1
+        assert_eq!(
+            parse_numeric(&numeric(&[2, 0, 0, 1, 12, 5000])),
+            Some(ConditionValue::Float(12.5))
+        );
+        assert_eq!(parse_numeric(&numeric(&[2, 0, 0, 0, 1])), None);
+
+        // Special values are flagged by the sign word alone
+        let special = |sign: u16| parse_numeric(&numeric(&[0, 0, sign as i16, 0]));
+        assert!(matches!(special(0xC000), Some(ConditionValue::Float(f)) if f.is_nan()));
+        assert_eq!(special(0xD000), Some(ConditionValue::Float(f64::INFINITY)));
+        assert_eq!(
+            special(0xF000),
+            Some(ConditionValue::Float(f64::NEG_INFINITY))
+        );
+        assert_eq!(special(0x1234), None);
+    }
+
+    #[test]
+    fn test_json_path_keys_are_bound() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("metadata->plan->>tier", "pro".into()))
+            .with_condition(Condition::new(
+                "metadata->seats->[0]",
+                Operator::IsNotNull,
+                ConditionValue::Null,
+            ))
+            .with_condition(Condition::in_list("metadata->>'x'", vec![]));
+
+        let (query, args) = SqlxAdapter::build_select_query("accounts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"accounts\" WHERE \"metadata\"->$1->>$2 = $3 \
+             AND \"metadata\"->$4->$5 IS NOT NULL AND FALSE"
+        );
+        assert_eq!(args.len(), 5);
+
+        for field in ["meta data->x", "metadata->>x->y", "metadata->"] {
+            let criteria = FilterCriteria::new().with_condition(Condition::eq(field, 1.into()));
+            assert!(
+                SqlxAdapter::build_select_query("accounts", &criteria).is_err(),
+                "{}",
+                field
+            );
+        }
+
+        // Empty lists render as a constant, but their field is still checked
+        for condition in [
+            Condition::in_list("meta data", vec![]),
+            Condition::not_in("metadata->", vec![]),
+        ] {
+            let criteria = FilterCriteria::new().with_condition(condition);
+            assert!(SqlxAdapter::build_select_query("accounts", &criteria).is_err());
+        }
+    }
+
+    #[test]
+    fn test_json_operators() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::json_contains(
+                "metadata",
+                r#"{"plan":"pro"}"#.into(),
+            ))
+            .with_condition(Condition::json_has_key("metadata->flags", "beta"))
+            .with_condition(Condition::json_has_any_key("tags", ["a", "b"]))
+            .with_condition(Condition::json_has_all_keys("tags", Vec::<String>::new()))
+            .with_condition(Condition::json_path_exists(
+                "metadata",
+                "$.seats[*] ? (@ > 10)",
+            ));
+
+        let (query, args) = SqlxAdapter::build_select_query("accounts", &criteria).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT * FROM \"accounts\" WHERE \"metadata\" @> $1::jsonb \
+             AND \"metadata\"->$2 ? $3 AND \"tags\" ?| ARRAY[$4, $5]::text[] \
+             AND \"tags\" ?& ARRAY[]::text[] AND \"metadata\" @? $6::jsonpath"
+        );
+        assert_eq!(args.len(), 6);
+
+        let not_json =
+            FilterCriteria::new().with_condition(Condition::json_contains("metadata", 1.into()));
+        assert!(SqlxAdapter::build_select_query("accounts", &not_json).is_err());
+    }
+
+    #[cfg(all(
+        feature = "uuid",
+        feature = "chrono",
+        feature = "rust_decimal",
+        feature = "json"
+    ))]
+    #[test]
+    fn test_typed_values_are_bound() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("id", uuid::Uuid::nil().into()))
+            .with_condition(Condition::gt(
+                "created_at",
+                chrono::DateTime::from_timestamp(0, 0).unwrap().into(),
+            ))
+            .with_condition(Condition::lt(
+                "price",
+                rust_decimal::Decimal::ONE_HUNDRED.into(),
+            ))
+            .with_condition(Condition::eq("meta", serde_json::json!({ "a": 1 }).into()))
+            .with_condition(Condition::eq("digest", vec![1u8, 2, 3].into()));
+
+        let (query, args) = SqlxAdapter::build_select_query("orders", &criteria).unwrap();
 
-        assert!(query.contains("provider IN ('openai', 'anthropic')"));
+        assert_eq!(
+            query,
+            "SELECT * FROM \"orders\" WHERE \"id\" = $1 AND \"created_at\" > $2 \
+             AND \"price\" < $3 AND \"meta\" = $4 AND \"digest\" = $5"
+        );
+        assert_eq!(args.len(), 5);
     }
 }
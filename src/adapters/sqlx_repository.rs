@@ -0,0 +1,794 @@
+//! Generic PostgreSQL repository built on the SQLx adapter
+//!
+//! [`SqlxRepository`] implements `Repository`, `Queryable` and `Transactional`
+//! for any entity that can be read with `sqlx::FromRow` and exposes its column
+//! values through [`FieldValues`].
+//!
+//! The trait operations each run on a connection from the pool. Every one of
+//! them also has an `_in` variant, such as [`SqlxRepository::create_in`],
+//! that runs on a given connection instead, which is how operations join a
+//! transaction from `begin_transaction`.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use wyvern::{Repository, SqlxRepository};
+//!
+//! let users: SqlxRepository<User> =
+//!     SqlxRepository::new(pool, "users", "id", &["id", "email", "status"])?
+//!         .with_generated_id();
+//!
+//! let user = users.find_by_id(42).await?;
+//! ```
+//!
+//! # Eager loading
+//!
+//! Instead of looking up the related rows of each result one by one,
+//! [`SqlxRepository::filter_loaded`] loads every relation named in the
+//! criteria's `include` list with one query for all results:
+//!
+//! ```rust,ignore
+//! let orders: SqlxRepository<Order> = SqlxRepository::for_entity(pool)?;
+//!
+//! let loaded = orders
+//!     .filter_loaded(FilterCriteria::new().with_include("customer").with_include("lines"))
+//!     .await?;
+//! for order in &loaded {
+//!     let customer = order.related_one("customer");
+//!     let lines = order.related("lines");
+//! }
+//!
+//! // Or read one relation into a type
+//! let lines: Vec<Vec<OrderLine>> = orders.load_related(&page, "lines").await?;
+//! ```
+//!
+//! # Transactions
+//!
+//! ```rust,ignore
+//! use wyvern::Transactional;
+//!
+//! let mut tx = users.begin_transaction().await?;
+//! let user = users.create_in(&mut tx, user).await?;
+//! audit.create_in(&mut tx, AuditLog::created(&user)).await?;
+//! users.commit_transaction(tx).await?;
+//! ```
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use sqlx::postgres::{PgArguments, PgConnection, PgPool, PgRow};
+use sqlx::{Arguments, Encode, FromRow, Postgres, Row, Type};
+
+use super::sqlx::{Identifier, SqlxAdapter};
+use crate::aggregate::{AggregateQuery, AggregateRow};
+use crate::cursor::{CursorPage, CursorPagination, CursorQuery, Seek};
+use crate::entity::{Entity, FieldValues};
+use crate::error::RepositoryError;
+use crate::eval;
+use crate::query::{ConditionValue, FilterCriteria, Page, Pagination};
+use crate::relation::{self, Loaded, Relation};
+use crate::repository::{Queryable, Repository};
+use crate::transaction::Transactional;
+
+/// A PostgreSQL repository for entities of type `T`, identified by `Id`.
+pub struct SqlxRepository<T, Id = i64> {
+    pool: PgPool,
+    table: Identifier,
+    id_column: Identifier,
+    columns: Vec<Identifier>,
+    relations: Vec<Relation>,
+    generated_id: bool,
+    _marker: PhantomData<fn() -> (T, Id)>,
+}
+
+impl<T, Id> SqlxRepository<T, Id> {
+    /// Creates a repository for `table`.
+    ///
+    /// `columns` lists the columns read into and written from `T`. The id
+    /// column is added if it is not part of the list. Returns
+    /// `RepositoryError::InvalidInput` if any name is not a valid identifier.
+    pub fn new(
+        pool: PgPool,
+        table: &str,
+        id_column: &str,
+        columns: &[&str],
+    ) -> Result<Self, RepositoryError> {
+        let id_column = Identifier::parse(id_column)?;
+        let mut columns = columns
+            .iter()
+            .map(|c| Identifier::parse(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !columns.contains(&id_column) {
+            columns.insert(0, id_column.clone());
+        }
+
+        Ok(Self {
+            pool,
+            table: Identifier::parse(table)?,
+            id_column,
+            columns,
+            relations: Vec::new(),
+            generated_id: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Lets the database assign ids, leaving the id column out of inserts.
+    pub fn with_generated_id(mut self) -> Self {
+        self.generated_id = true;
+        self
+    }
+
+    /// Declares a relation conditions and sorts may traverse.
+    pub fn with_relation(mut self, relation: Relation) -> Self {
+        self.relations.push(relation);
+        self
+    }
+
+    /// Returns the underlying connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Returns the rows matching the criteria read as `D` instead of `T`.
+    ///
+    /// The criteria's projection selects the columns `D` is read from; without
+    /// one the repository's columns are selected.
+    pub async fn filter_as<D>(&self, criteria: FilterCriteria) -> Result<Vec<D>, RepositoryError>
+    where
+        D: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    {
+        let (query, args) = SqlxAdapter::build_select_columns(
+            &self.table,
+            &self.relations,
+            &self.columns,
+            &criteria,
+        )?;
+        let rows = sqlx::query_as_with::<_, D, _>(&query, args)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Runs an aggregation query, reading each group into `D`.
+    pub async fn aggregate_as<D>(&self, query: AggregateQuery) -> Result<Vec<D>, RepositoryError>
+    where
+        D: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    {
+        let (sql, args) = SqlxAdapter::build_aggregate(&self.table, &self.relations, &query)?;
+        let rows = sqlx::query_as_with::<_, D, _>(&sql, args)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Returns the entities matching the criteria with the rows of the
+    /// relations named in its `include` list.
+    ///
+    /// Each included relation is loaded with a single query for all
+    /// entities. Returns `RepositoryError::InvalidInput` if an included name
+    /// is not one of the repository's relations.
+    ///
+    /// Related columns are read by their Postgres type like
+    /// [`SqlxAdapter::decode_row`] does, with enums read as strings, domains
+    /// as their base type and arrays as lists. Columns of other types (such
+    /// as `inet`, `interval`, or `uuid` without the `uuid` feature) are left
+    /// out of the rows; read them with [`load_related`](Self::load_related)
+    /// into a type of your own.
+    pub async fn filter_loaded(
+        &self,
+        criteria: FilterCriteria,
+    ) -> Result<Vec<Loaded<T>>, RepositoryError>
+    where
+        T: for<'r> FromRow<'r, PgRow> + FieldValues + Send + Unpin,
+    {
+        let relations = criteria
+            .include
+            .iter()
+            .map(|name| self.relation(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (query, args) = self.select_entities(&criteria, None)?;
+        let entities = sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut related = vec![HashMap::new(); entities.len()];
+        for relation in relations {
+            let (rows, groups) = self.load_rows(&entities, relation).await?;
+            for (entity_related, group) in related.iter_mut().zip(groups) {
+                let mut entity_rows = Vec::with_capacity(group.len());
+                for i in group {
+                    entity_rows.push(SqlxAdapter::decode_related_row(&rows[i])?);
+                }
+                entity_related.insert(relation.name.clone(), entity_rows);
+            }
+        }
+
+        Ok(entities
+            .into_iter()
+            .zip(related)
+            .map(|(entity, related)| Loaded { entity, related })
+            .collect())
+    }
+
+    /// Loads the rows of `relation` for each of `entities`, read as `C`.
+    ///
+    /// Returns one list per entity, in the same order, using a single query.
+    /// Rows hold the relation's declared columns in order, followed by a
+    /// `wyvern_parent_key` column, so a tuple `C` reads the declared columns
+    /// and a `FromRow` struct ignores the extra column.
+    pub async fn load_related<C>(
+        &self,
+        entities: &[T],
+        relation: &str,
+    ) -> Result<Vec<Vec<C>>, RepositoryError>
+    where
+        T: FieldValues,
+        C: for<'r> FromRow<'r, PgRow>,
+    {
+        let (rows, groups) = self.load_rows(entities, self.relation(relation)?).await?;
+        groups
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .map(|i| C::from_row(&rows[i]).map_err(RepositoryError::from))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn relation(&self, name: &str) -> Result<&Relation, RepositoryError> {
+        self.relations
+            .iter()
+            .find(|relation| relation.name == name)
+            .ok_or_else(|| RepositoryError::InvalidInput(format!("unknown relation '{}'", name)))
+    }
+
+    /// Fetches the related rows of all entities at once, returning them with
+    /// the indices of the rows belonging to each entity
+    async fn load_rows<E: FieldValues>(
+        &self,
+        entities: &[E],
+        relation: &Relation,
+    ) -> Result<(Vec<PgRow>, Vec<Vec<usize>>), RepositoryError> {
+        let parent_keys: Vec<ConditionValue> = entities
+            .iter()
+            .map(|entity| eval::field_value(entity, relation.root_key()))
+            .collect();
+        let keys = relation::distinct_keys(&parent_keys);
+        if keys.is_empty() {
+            return Ok((Vec::new(), vec![Vec::new(); entities.len()]));
+        }
+
+        let (query, args) = SqlxAdapter::build_related_query(relation, &keys)?;
+        let rows = sqlx::query_with(&query, args).fetch_all(&self.pool).await?;
+        // The parent key is the last column
+        let row_keys = rows
+            .iter()
+            .map(|row| SqlxAdapter::decode_column(row, row.len() - 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((rows, relation::group_by_parent(&parent_keys, &row_keys)))
+    }
+
+    /// Builds a SELECT of whole entities, which always reads the repository's
+    /// columns: `T` cannot be read from a projection of them
+    fn select_entities(
+        &self,
+        criteria: &FilterCriteria,
+        seek: Option<&Seek>,
+    ) -> Result<(String, PgArguments), RepositoryError> {
+        let criteria = FilterCriteria {
+            projection: Vec::new(),
+            ..criteria.clone()
+        };
+        SqlxAdapter::build_select_seek(&self.table, &self.relations, &self.columns, &criteria, seek)
+    }
+
+    fn insert_columns(&self) -> Vec<&Identifier> {
+        self.columns
+            .iter()
+            .filter(|c| !self.generated_id || **c != self.id_column)
+            .collect()
+    }
+
+    fn update_columns(&self) -> Vec<&Identifier> {
+        self.columns
+            .iter()
+            .filter(|c| **c != self.id_column)
+            .collect()
+    }
+
+    fn bind_column<E: FieldValues>(
+        entity: &E,
+        column: &Identifier,
+        args: &mut PgArguments,
+    ) -> Result<String, RepositoryError> {
+        let name = column.parts().join(".");
+        let value = entity.field_value(&name).ok_or_else(|| {
+            RepositoryError::InvalidInput(format!("entity has no value for column '{}'", name))
+        })?;
+        SqlxAdapter::bind_value(&value, args)
+    }
+
+    fn insert_query(&self, entity: &T) -> Result<(String, PgArguments), RepositoryError>
+    where
+        T: FieldValues,
+    {
+        let columns = self.insert_columns();
+        let mut args = PgArguments::default();
+        let placeholders = columns
+            .iter()
+            .map(|c| Self::bind_column(entity, c, &mut args))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let query = format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+            self.table,
+            columns
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            placeholders.join(", "),
+            SqlxAdapter::column_list(&self.columns)
+        );
+        Ok((query, args))
+    }
+
+    fn update_query(&self, entity: &T) -> Result<(String, PgArguments), RepositoryError>
+    where
+        T: FieldValues,
+    {
+        let mut args = PgArguments::default();
+        let assignments = self
+            .update_columns()
+            .into_iter()
+            .map(|c| {
+                Ok(format!(
+                    "{} = {}",
+                    c,
+                    Self::bind_column(entity, c, &mut args)?
+                ))
+            })
+            .collect::<Result<Vec<_>, RepositoryError>>()?;
+        let id = Self::bind_column(entity, &self.id_column, &mut args)?;
+
+        let query = format!(
+            "UPDATE {} SET {} WHERE {} = {} RETURNING {}",
+            self.table,
+            assignments.join(", "),
+            self.id_column,
+            id,
+            SqlxAdapter::column_list(&self.columns)
+        );
+        Ok((query, args))
+    }
+
+    fn find_by_id_query(&self) -> String {
+        format!(
+            "SELECT {} FROM {} WHERE {} = $1",
+            SqlxAdapter::column_list(&self.columns),
+            self.table,
+            self.id_column
+        )
+    }
+
+    fn delete_query(&self) -> String {
+        format!("DELETE FROM {} WHERE {} = $1", self.table, self.id_column)
+    }
+
+    fn id_arguments(id: Id) -> Result<PgArguments, RepositoryError>
+    where
+        Id: for<'q> Encode<'q, Postgres> + Type<Postgres>,
+    {
+        let mut args = PgArguments::default();
+        args.add(id)
+            .map_err(|e| RepositoryError::QueryError(format!("failed to bind id: {}", e)))?;
+        Ok(args)
+    }
+}
+
+impl<T: Entity> SqlxRepository<T, T::Id> {
+    /// Creates a repository from the entity's table, id column, columns and
+    /// relations.
+    pub fn for_entity(pool: PgPool) -> Result<Self, RepositoryError> {
+        let mut repository = Self::new(pool, T::TABLE, T::ID_COLUMN, T::COLUMNS)?;
+        repository.relations = T::relations();
+        Ok(repository)
+    }
+}
+
+impl<T, Id> Clone for SqlxRepository<T, Id> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            table: self.table.clone(),
+            id_column: self.id_column.clone(),
+            columns: self.columns.clone(),
+            relations: self.relations.clone(),
+            generated_id: self.generated_id,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Id> SqlxRepository<T, Id>
+where
+    T: for<'r> FromRow<'r, PgRow> + FieldValues + Send + Sync + Unpin + 'static,
+    Id: for<'q> Encode<'q, Postgres> + Type<Postgres> + Send + Sync + 'static,
+{
+    /// Inserts an entity on `conn`, like [`Repository::create`].
+    pub async fn create_in(
+        &self,
+        conn: &mut PgConnection,
+        entity: T,
+    ) -> Result<T, RepositoryError> {
+        let (query, args) = self.insert_query(&entity)?;
+        let created = sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_one(conn)
+            .await?;
+        Ok(created)
+    }
+
+    /// Finds an entity by id on `conn`, like [`Repository::find_by_id`].
+    pub async fn find_by_id_in(
+        &self,
+        conn: &mut PgConnection,
+        id: Id,
+    ) -> Result<Option<T>, RepositoryError> {
+        let query = self.find_by_id_query();
+        let entity = sqlx::query_as_with::<_, T, _>(&query, Self::id_arguments(id)?)
+            .fetch_optional(conn)
+            .await?;
+        Ok(entity)
+    }
+
+    /// Updates an entity on `conn`, like [`Repository::update`].
+    pub async fn update_in(
+        &self,
+        conn: &mut PgConnection,
+        entity: T,
+    ) -> Result<T, RepositoryError> {
+        let (query, args) = self.update_query(&entity)?;
+        sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_optional(conn)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound(format!("no row to update in {}", self.table)))
+    }
+
+    /// Deletes an entity by id on `conn`, like [`Repository::delete`].
+    pub async fn delete_in(
+        &self,
+        conn: &mut PgConnection,
+        id: Id,
+    ) -> Result<bool, RepositoryError> {
+        let query = self.delete_query();
+        let result = sqlx::query_with(&query, Self::id_arguments(id)?)
+            .execute(conn)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns the entities matching the criteria on `conn`, like
+    /// [`Queryable::filter`].
+    pub async fn filter_in(
+        &self,
+        conn: &mut PgConnection,
+        criteria: FilterCriteria,
+    ) -> Result<Vec<T>, RepositoryError> {
+        let (query, args) = self.select_entities(&criteria, None)?;
+        let entities = sqlx::query_as_with::<_, T, _>(&query, args)
+            .fetch_all(conn)
+            .await?;
+        Ok(entities)
+    }
+
+    /// Counts the entities matching the criteria on `conn`, like
+    /// [`Queryable::count`].
+    pub async fn count_in(
+        &self,
+        conn: &mut PgConnection,
+        criteria: FilterCriteria,
+    ) -> Result<i64, RepositoryError> {
+        let (query, args) = SqlxAdapter::build_count(&self.table, &self.relations, &criteria)?;
+        let count = sqlx::query_scalar_with::<_, i64, _>(&query, args)
+            .fetch_one(conn)
+            .await?;
+        Ok(count)
+    }
+
+    /// Returns a page of entities on `conn`, like [`Queryable::paginate`].
+    pub async fn paginate_in(
+        &self,
+        conn: &mut PgConnection,
+        criteria: FilterCriteria,
+        pagination: Pagination,
+    ) -> Result<Page<T>, RepositoryError> {
+        pagination.validate()?;
+        let total = self.count_in(conn, criteria.clone()).await?;
+        let items = self
+            .filter_in(conn, criteria.with_pagination(&pagination))
+            .await?;
+        Ok(Page::from_pagination(items, &pagination, total))
+    }
+
+    /// Checks whether any entity matches the criteria on `conn`, like
+    /// [`Queryable::exists`].
+    pub async fn exists_in(
+        &self,
+        conn: &mut PgConnection,
+        criteria: FilterCriteria,
+    ) -> Result<bool, RepositoryError> {
+        let (query, args) = SqlxAdapter::build_exists(&self.table, &self.relations, &criteria)?;
+        let exists = sqlx::query_scalar_with::<_, bool, _>(&query, args)
+            .fetch_one(conn)
+            .await?;
+        Ok(exists)
+    }
+
+    /// Runs an aggregation query on `conn`, like [`Queryable::aggregate`].
+    pub async fn aggregate_in(
+        &self,
+        conn: &mut PgConnection,
+        query: AggregateQuery,
+    ) -> Result<Vec<AggregateRow>, RepositoryError> {
+        let (sql, args) = SqlxAdapter::build_aggregate(&self.table, &self.relations, &query)?;
+        let rows = sqlx::query_with(&sql, args).fetch_all(conn).await?;
+        rows.iter().map(SqlxAdapter::decode_row).collect()
+    }
+
+    /// Returns a cursor page of entities on `conn`, like
+    /// [`Queryable::paginate_by_cursor`].
+    pub async fn paginate_by_cursor_in(
+        &self,
+        conn: &mut PgConnection,
+        criteria: FilterCriteria,
+        pagination: CursorPagination,
+    ) -> Result<CursorPage<T>, RepositoryError> {
+        let query = CursorQuery::new(criteria, pagination, &self.relations)?;
+        let (sql, args) = self.select_entities(&query.criteria, query.seek.as_ref())?;
+        let items = sqlx::query_as_with::<_, T, _>(&sql, args)
+            .fetch_all(conn)
+            .await?;
+        query.finish(items)
+    }
+}
+
+#[async_trait]
+impl<T, Id> Repository<T> for SqlxRepository<T, Id>
+where
+    T: for<'r> FromRow<'r, PgRow> + FieldValues + Send + Sync + Unpin + 'static,
+    Id: for<'q> Encode<'q, Postgres> + Type<Postgres> + Send + Sync + 'static,
+{
+    type Id = Id;
+    type Error = RepositoryError;
+
+    async fn create(&self, entity: T) -> Result<T, Self::Error> {
+        self.create_in(&mut *self.pool.acquire().await?, entity)
+            .await
+    }
+
+    async fn find_by_id(&self, id: Self::Id) -> Result<Option<T>, Self::Error> {
+        self.find_by_id_in(&mut *self.pool.acquire().await?, id)
+            .await
+    }
+
+    async fn update(&self, entity: T) -> Result<T, Self::Error> {
+        self.update_in(&mut *self.pool.acquire().await?, entity)
+            .await
+    }
+
+    async fn delete(&self, id: Self::Id) -> Result<bool, Self::Error> {
+        self.delete_in(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<T>, Self::Error> {
+        self.filter(FilterCriteria::new()).await
+    }
+}
+
+#[async_trait]
+impl<T, Id> Queryable<T> for SqlxRepository<T, Id>
+where
+    T: for<'r> FromRow<'r, PgRow> + FieldValues + Send + Sync + Unpin + 'static,
+    Id: for<'q> Encode<'q, Postgres> + Type<Postgres> + Send + Sync + 'static,
+{
+    async fn filter(&self, criteria: FilterCriteria) -> Result<Vec<T>, RepositoryError> {
+        self.filter_in(&mut *self.pool.acquire().await?, criteria)
+            .await
+    }
+
+    async fn count(&self, criteria: FilterCriteria) -> Result<i64, RepositoryError> {
+        self.count_in(&mut *self.pool.acquire().await?, criteria)
+            .await
+    }
+
+    async fn paginate(
+        &self,
+        criteria: FilterCriteria,
+        pagination: Pagination,
+    ) -> Result<Page<T>, RepositoryError> {
+        self.paginate_in(&mut *self.pool.acquire().await?, criteria, pagination)
+            .await
+    }
+
+    async fn exists(&self, criteria: FilterCriteria) -> Result<bool, RepositoryError> {
+        self.exists_in(&mut *self.pool.acquire().await?, criteria)
+            .await
+    }
+
+    async fn aggregate(&self, query: AggregateQuery) -> Result<Vec<AggregateRow>, RepositoryError> {
+        self.aggregate_in(&mut *self.pool.acquire().await?, query)
+            .await
+    }
+
+    async fn paginate_by_cursor(
+        &self,
+        criteria: FilterCriteria,
+        pagination: CursorPagination,
+    ) -> Result<CursorPage<T>, RepositoryError> {
+        self.paginate_by_cursor_in(&mut *self.pool.acquire().await?, criteria, pagination)
+            .await
+    }
+}
+
+#[async_trait]
+impl<T, Id> Transactional for SqlxRepository<T, Id> {
+    type Transaction = sqlx::Transaction<'static, Postgres>;
+    type Error = RepositoryError;
+
+    async fn begin_transaction(&self) -> Result<Self::Transaction, Self::Error> {
+        Ok(self.pool.begin().await?)
+    }
+
+    async fn commit_transaction(&self, transaction: Self::Transaction) -> Result<(), Self::Error> {
+        Ok(transaction.commit().await?)
+    }
+
+    async fn rollback_transaction(
+        &self,
+        transaction: Self::Transaction,
+    ) -> Result<(), Self::Error> {
+        Ok(transaction.rollback().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Condition, ConditionValue};
+
+    struct User {
+        id: i64,
+        email: String,
+        active: bool,
+    }
+
+    impl FieldValues for User {
+        fn field_value(&self, field: &str) -> Option<ConditionValue> {
+            match field {
+                "id" => Some(self.id.into()),
+                "email" => Some(self.email.clone().into()),
+                "active" => Some(self.active.into()),
+                _ => None,
+            }
+        }
+    }
+
+    fn repository() -> SqlxRepository<User> {
+        let pool = PgPool::connect_lazy("postgres://localhost/wyvern").unwrap();
+        SqlxRepository::new(pool, "users", "id", &["email", "active"]).unwrap()
+    }
+
+    fn user() -> User {
+        User {
+            id: 7,
+            email: "a@b.c".to_string(),
+            active: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_query() {
+        let (query, args) = repository().insert_query(&user()).unwrap();
+
+        assert_eq!(
+            query,
+            "INSERT INTO \"users\" (\"id\", \"email\", \"active\") VALUES ($1, $2, $3) RETURNING \"id\", \"email\", \"active\""
+        );
+        assert_eq!(args.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_insert_query_with_generated_id() {
+        let (query, args) = repository()
+            .with_generated_id()
+            .insert_query(&user())
+            .unwrap();
+
+        assert_eq!(
+            query,
+            "INSERT INTO \"users\" (\"email\", \"active\") VALUES ($1, $2) RETURNING \"id\", \"email\", \"active\""
+        );
+        assert_eq!(args.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_query() {
+        let (query, args) = repository().update_query(&user()).unwrap();
+
+        assert_eq!(
+            query,
+            "UPDATE \"users\" SET \"email\" = $1, \"active\" = $2 WHERE \"id\" = $3 RETURNING \"id\", \"email\", \"active\""
+        );
+        assert_eq!(args.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_find_and_delete_queries() {
+        let repository = repository();
+
+        assert_eq!(
+            repository.find_by_id_query(),
+            "SELECT \"id\", \"email\", \"active\" FROM \"users\" WHERE \"id\" = $1"
+        );
+        assert_eq!(
+            repository.delete_query(),
+            "DELETE FROM \"users\" WHERE \"id\" = $1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entity_reads_ignore_projection() {
+        let criteria = FilterCriteria::new()
+            .with_columns(["id"])
+            .with_condition(Condition::eq("active", true.into()));
+
+        let (query, args) = repository().select_entities(&criteria, None).unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT \"id\", \"email\", \"active\" FROM \"users\" WHERE \"active\" = $1"
+        );
+        assert_eq!(args.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_field_value_is_rejected() {
+        let pool = PgPool::connect_lazy("postgres://localhost/wyvern").unwrap();
+        let repository: SqlxRepository<User> =
+            SqlxRepository::new(pool, "users", "id", &["nickname"]).unwrap();
+
+        assert!(matches!(
+            repository.insert_query(&user()),
+            Err(RepositoryError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_names_are_rejected() {
+        let pool = PgPool::connect_lazy("postgres://localhost/wyvern").unwrap();
+
+        assert!(SqlxRepository::<User>::new(pool.clone(), "users;", "id", &[]).is_err());
+        assert!(SqlxRepository::<User>::new(pool, "users", "id", &["a b"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_related_checks_relation_and_keys() {
+        let repository =
+            repository().with_relation(Relation::one_to_many("posts", "posts", "author_id"));
+
+        let err = repository
+            .load_related::<(i64,)>(&[user()], "comments")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RepositoryError::InvalidInput(_)));
+
+        // The lazy pool never connects: there are no keys to look up
+        let posts: Vec<Vec<(i64,)>> = repository.load_related(&[], "posts").await.unwrap();
+        assert!(posts.is_empty());
+    }
+}
@@ -0,0 +1,584 @@
+//! Aggregation queries
+//!
+//! An [`AggregateQuery`] groups the entities matching a `FilterCriteria` by
+//! some fields and computes [`Aggregate`]s over each group. Every result row
+//! holds the group fields and the aggregates under their aliases.
+//!
+//! `having` conditions filter the result rows, and the criteria's sort, limit
+//! and offset order and page them; all of them refer to group fields and
+//! aggregate aliases rather than to entity fields.
+//!
+//! ```rust
+//! use wyvern::{Aggregate, AggregateQuery, Condition, FilterCriteria, SortOrder};
+//!
+//! // Customers with more than 5 paid orders, biggest spenders first
+//! let query = AggregateQuery::new(
+//!     FilterCriteria::new()
+//!         .with_condition(Condition::eq("status", "paid".into()))
+//!         .with_sort(SortOrder::desc("total"))
+//!         .with_limit(10),
+//! )
+//! .with_group_by("customer_id")
+//! .with_aggregate(Aggregate::count_all("orders"))
+//! .with_aggregate(Aggregate::sum("amount", "total"))
+//! .with_having(Condition::gt("orders", 5.into()));
+//! ```
+//!
+//! In process, sums of integers are integers. Averages of integers and
+//! decimals are decimals with the `rust_decimal` feature, as PostgreSQL's
+//! `numeric` averages decode, and floats otherwise, as they decode without
+//! it; averages of floats are floats. NULLs are skipped by every aggregate but
+//! `count_all`, and groups with no values have a NULL sum, average, minimum
+//! and maximum.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::entity::FieldValues;
+use crate::error::RepositoryError;
+use crate::eval;
+use crate::query::{ConditionValue, FilterCriteria, FilterExpr};
+
+/// A result row of an aggregation query, keyed by group field and alias.
+pub type AggregateRow = HashMap<String, ConditionValue>;
+
+/// An aggregate function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AggregateFunction {
+    /// `COUNT(*)` without a field, `COUNT(field)` (non-null values) with one
+    Count,
+    /// `COUNT(DISTINCT field)`
+    CountDistinct,
+    /// `SUM(field)`
+    Sum,
+    /// `AVG(field)`
+    Avg,
+    /// `MIN(field)`
+    Min,
+    /// `MAX(field)`
+    Max,
+}
+
+impl AggregateFunction {
+    /// Returns the SQL name of the function.
+    #[cfg_attr(not(feature = "sqlx"), allow(dead_code))]
+    pub(crate) fn sql_name(&self) -> &'static str {
+        match self {
+            AggregateFunction::Count | AggregateFunction::CountDistinct => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
+}
+
+/// An aggregate computed over each group, named by its alias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    /// The aggregated field; only a `Count` of every row has none
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub field: Option<String>,
+    pub alias: String,
+}
+
+impl Aggregate {
+    pub fn new(
+        function: AggregateFunction,
+        field: impl Into<String>,
+        alias: impl Into<String>,
+    ) -> Self {
+        Self {
+            function,
+            field: Some(field.into()),
+            alias: alias.into(),
+        }
+    }
+
+    /// Counts the rows of each group (`COUNT(*)`).
+    pub fn count_all(alias: impl Into<String>) -> Self {
+        Self {
+            function: AggregateFunction::Count,
+            field: None,
+            alias: alias.into(),
+        }
+    }
+
+    /// Counts the non-null values of a field.
+    pub fn count(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::Count, field, alias)
+    }
+
+    /// Counts the distinct non-null values of a field.
+    pub fn count_distinct(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::CountDistinct, field, alias)
+    }
+
+    pub fn sum(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::Sum, field, alias)
+    }
+
+    pub fn avg(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::Avg, field, alias)
+    }
+
+    pub fn min(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::Min, field, alias)
+    }
+
+    pub fn max(field: impl Into<String>, alias: impl Into<String>) -> Self {
+        Self::new(AggregateFunction::Max, field, alias)
+    }
+}
+
+/// A grouped query over the entities matching a criteria.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct AggregateQuery {
+    /// Selects the aggregated entities; its sort, limit and offset apply to
+    /// the result rows, and its projection and includes are not used
+    pub criteria: FilterCriteria,
+    /// Fields to group by; without any, the whole selection is one group
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub group_by: Vec<String>,
+    /// Aggregates computed for each group
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub aggregates: Vec<Aggregate>,
+    /// Filters on the result rows, combined with AND
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub having: Vec<FilterExpr>,
+}
+
+impl AggregateQuery {
+    /// Creates a query aggregating the entities matching `criteria`.
+    pub fn new(criteria: FilterCriteria) -> Self {
+        Self {
+            criteria,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a field to group by.
+    pub fn with_group_by(mut self, field: impl Into<String>) -> Self {
+        self.group_by.push(field.into());
+        self
+    }
+
+    /// Adds an aggregate.
+    pub fn with_aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregates.push(aggregate);
+        self
+    }
+
+    /// Adds a filter on the result rows.
+    pub fn with_having(mut self, filter: impl Into<FilterExpr>) -> Self {
+        self.having.push(filter.into());
+        self
+    }
+
+    /// Checks that the query selects something, that its result columns have
+    /// distinct names, and that `having` and the sort only use those names.
+    pub(crate) fn check(&self) -> Result<(), RepositoryError> {
+        let invalid = |message: String| Err(RepositoryError::InvalidInput(message));
+
+        if self.group_by.is_empty() && self.aggregates.is_empty() {
+            return invalid("an aggregate query needs a group field or an aggregate".to_string());
+        }
+        if !self.criteria.projection.is_empty() {
+            return invalid("an aggregate query selects its own columns".to_string());
+        }
+
+        let mut columns: Vec<&str> = Vec::new();
+        for aggregate in &self.aggregates {
+            if aggregate.field.is_none() && aggregate.function != AggregateFunction::Count {
+                return invalid(format!("aggregate '{}' needs a field", aggregate.alias));
+            }
+        }
+        let names = self
+            .group_by
+            .iter()
+            .chain(self.aggregates.iter().map(|a| &a.alias));
+        for name in names {
+            if columns.contains(&name.as_str()) {
+                return invalid(format!("duplicate aggregate column '{}'", name));
+            }
+            columns.push(name);
+        }
+
+        let having = FilterExpr::And(self.having.clone());
+        for condition in having.conditions() {
+            if !columns.contains(&condition.field.as_str()) {
+                return invalid(format!(
+                    "'{}' is neither a group field nor an aggregate",
+                    condition.field
+                ));
+            }
+        }
+        for sort in &self.criteria.sort {
+            if !columns.contains(&sort.field.as_str()) {
+                return invalid(format!(
+                    "sort field '{}' is neither a group field nor an aggregate",
+                    sort.field
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs an aggregation query over entities already matching its criteria.
+pub(crate) fn evaluate<T: FieldValues>(
+    query: &AggregateQuery,
+    entities: &[T],
+) -> Result<Vec<AggregateRow>, RepositoryError> {
+    query.check()?;
+    let (limit, offset) = query.criteria.bounds()?;
+    let (limit, offset) = (limit.unwrap_or(usize::MAX), offset.unwrap_or(0));
+
+    // Like GROUP BY, NULLs fall into one group; without group fields there is
+    // always exactly one group, even with no entities
+    let mut groups: Vec<(Vec<ConditionValue>, Vec<&T>)> = Vec::new();
+    if query.group_by.is_empty() {
+        groups.push((Vec::new(), entities.iter().collect()));
+    } else {
+        for entity in entities {
+            let key: Vec<ConditionValue> = query
+                .group_by
+                .iter()
+                .map(|field| eval::field_value(entity, field))
+                .collect();
+            match groups.iter_mut().find(|(k, _)| same_key(k, &key)) {
+                Some((_, members)) => members.push(entity),
+                None => groups.push((key, vec![entity])),
+            }
+        }
+    }
+
+    let having = FilterExpr::And(query.having.clone());
+    let mut rows = Vec::with_capacity(groups.len());
+    for (key, members) in groups {
+        let mut row: AggregateRow = query.group_by.iter().cloned().zip(key).collect();
+        for aggregate in &query.aggregates {
+            row.insert(aggregate.alias.clone(), compute(aggregate, &members)?);
+        }
+        if eval::evaluate(&having, &row) == Some(true) {
+            rows.push(row);
+        }
+    }
+    rows.sort_by(|a, b| eval::sort_ordering(&query.criteria, a, b));
+
+    Ok(rows.into_iter().skip(offset).take(limit).collect())
+}
+
+fn same_key(a: &[ConditionValue], b: &[ConditionValue]) -> bool {
+    a.iter().zip(b).all(|(a, b)| match (a, b) {
+        (ConditionValue::Null, ConditionValue::Null) => true,
+        (a, b) => eval::equals(a, b) == Some(true),
+    })
+}
+
+fn compute<T: FieldValues>(
+    aggregate: &Aggregate,
+    members: &[&T],
+) -> Result<ConditionValue, RepositoryError> {
+    let Some(field) = &aggregate.field else {
+        return Ok(ConditionValue::Integer(members.len() as i64));
+    };
+    let values: Vec<ConditionValue> = members
+        .iter()
+        .map(|entity| eval::field_value(*entity, field))
+        .filter(|value| *value != ConditionValue::Null)
+        .collect();
+
+    Ok(match aggregate.function {
+        AggregateFunction::Count => ConditionValue::Integer(values.len() as i64),
+        AggregateFunction::CountDistinct => {
+            let mut distinct: Vec<&ConditionValue> = Vec::new();
+            for value in &values {
+                if !distinct
+                    .iter()
+                    .any(|d| eval::equals(d, value) == Some(true))
+                {
+                    distinct.push(value);
+                }
+            }
+            ConditionValue::Integer(distinct.len() as i64)
+        }
+        AggregateFunction::Sum => sum(field, &values)?,
+        AggregateFunction::Avg => average(field, &values)?,
+        AggregateFunction::Min => extreme(&values, Ordering::Less),
+        AggregateFunction::Max => extreme(&values, Ordering::Greater),
+    })
+}
+
+/// Adds up numeric values, staying with integers while every value is one
+fn sum(field: &str, values: &[ConditionValue]) -> Result<ConditionValue, RepositoryError> {
+    let not_numeric = || {
+        RepositoryError::InvalidInput(format!("cannot add up non-numeric values of '{}'", field))
+    };
+
+    if values.is_empty() {
+        return Ok(ConditionValue::Null);
+    }
+
+    if values
+        .iter()
+        .all(|v| matches!(v, ConditionValue::Integer(_)))
+    {
+        let total: i128 = values
+            .iter()
+            .map(|v| match v {
+                ConditionValue::Integer(i) => i128::from(*i),
+                _ => 0,
+            })
+            .sum();
+        // Like PostgreSQL's numeric sum, do not overflow
+        return Ok(match i64::try_from(total) {
+            Ok(total) => ConditionValue::Integer(total),
+            Err(_) => ConditionValue::Float(total as f64),
+        });
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    if values
+        .iter()
+        .any(|v| matches!(v, ConditionValue::Decimal(_)))
+    {
+        let mut total = rust_decimal::Decimal::ZERO;
+        for value in values {
+            let value = match value {
+                ConditionValue::Decimal(d) => *d,
+                ConditionValue::Integer(i) => rust_decimal::Decimal::from(*i),
+                _ => return Err(not_numeric()),
+            };
+            total = total.checked_add(value).ok_or_else(|| {
+                RepositoryError::InvalidInput(format!("sum of '{}' overflows", field))
+            })?;
+        }
+        return Ok(ConditionValue::Decimal(total));
+    }
+
+    let mut total = 0.0;
+    for value in values {
+        total += match value {
+            ConditionValue::Integer(i) => *i as f64,
+            ConditionValue::Float(f) => *f,
+            _ => return Err(not_numeric()),
+        };
+    }
+    Ok(ConditionValue::Float(total))
+}
+
+/// Averages numeric values like `AVG`, whose result is `numeric` for
+/// integers and decimals and a float for floats
+fn average(field: &str, values: &[ConditionValue]) -> Result<ConditionValue, RepositoryError> {
+    let count = values.len();
+    Ok(match sum(field, values)? {
+        #[cfg(feature = "rust_decimal")]
+        ConditionValue::Integer(total) => ConditionValue::Decimal(
+            rust_decimal::Decimal::from(total) / rust_decimal::Decimal::from(count),
+        ),
+        #[cfg(not(feature = "rust_decimal"))]
+        ConditionValue::Integer(total) => ConditionValue::Float(total as f64 / count as f64),
+        ConditionValue::Float(total) => ConditionValue::Float(total / count as f64),
+        #[cfg(feature = "rust_decimal")]
+        ConditionValue::Decimal(total) => {
+            ConditionValue::Decimal(total / rust_decimal::Decimal::from(count))
+        }
+        _ => ConditionValue::Null,
+    })
+}
+
+/// Returns the least (`Less`) or greatest (`Greater`) value
+fn extreme(values: &[ConditionValue], wanted: Ordering) -> ConditionValue {
+    values
+        .iter()
+        .fold(None, |best: Option<&ConditionValue>, value| match best {
+            Some(best) if eval::compare(value, best) != Some(wanted) => Some(best),
+            _ => Some(value),
+        })
+        .cloned()
+        .unwrap_or(ConditionValue::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Condition, SortOrder};
+
+    fn order(customer: &str, amount: i64, coupon: Option<&str>) -> HashMap<String, ConditionValue> {
+        HashMap::from([
+            ("customer".to_string(), customer.into()),
+            ("amount".to_string(), amount.into()),
+            (
+                "coupon".to_string(),
+                coupon.map_or(ConditionValue::Null, ConditionValue::from),
+            ),
+        ])
+    }
+
+    fn orders() -> Vec<HashMap<String, ConditionValue>> {
+        vec![
+            order("ada", 10, Some("SPRING")),
+            order("bob", 5, None),
+            order("ada", 30, Some("SPRING")),
+            order("cy", 7, Some("VIP")),
+            order("ada", 20, None),
+            order("bob", 15, Some("VIP")),
+        ]
+    }
+
+    #[test]
+    fn test_group_aggregates() {
+        let query = AggregateQuery::new(FilterCriteria::new().with_sort(SortOrder::desc("total")))
+            .with_group_by("customer")
+            .with_aggregate(Aggregate::count_all("orders"))
+            .with_aggregate(Aggregate::count("coupon", "with_coupon"))
+            .with_aggregate(Aggregate::count_distinct("coupon", "coupons"))
+            .with_aggregate(Aggregate::sum("amount", "total"))
+            .with_aggregate(Aggregate::avg("amount", "average"))
+            .with_aggregate(Aggregate::min("amount", "smallest"))
+            .with_aggregate(Aggregate::max("amount", "largest"));
+
+        let rows = evaluate(&query, &orders()).unwrap();
+        // AVG of integers is numeric in PostgreSQL
+        #[cfg(feature = "rust_decimal")]
+        let average = ConditionValue::from(rust_decimal::Decimal::from(20));
+        #[cfg(not(feature = "rust_decimal"))]
+        let average = ConditionValue::from(20.0);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0],
+            HashMap::from([
+                ("customer".to_string(), "ada".into()),
+                ("orders".to_string(), 3.into()),
+                ("with_coupon".to_string(), 2.into()),
+                ("coupons".to_string(), 1.into()),
+                ("total".to_string(), 60.into()),
+                ("average".to_string(), average),
+                ("smallest".to_string(), 10.into()),
+                ("largest".to_string(), 30.into()),
+            ])
+        );
+        assert_eq!(rows[1]["customer"], "bob".into());
+        assert_eq!(rows[2]["customer"], "cy".into());
+    }
+
+    #[test]
+    fn test_having_filters_groups() {
+        let query = AggregateQuery::new(
+            FilterCriteria::new()
+                .with_sort(SortOrder::asc("coupon"))
+                .with_limit(2),
+        )
+        .with_group_by("coupon")
+        .with_aggregate(Aggregate::sum("amount", "total"))
+        .with_having(Condition::gt("total", 20.into()));
+
+        let rows = evaluate(&query, &orders()).unwrap();
+
+        // NULL coupons form one group, sorted last
+        let coupons: Vec<&ConditionValue> = rows.iter().map(|r| &r["coupon"]).collect();
+        assert_eq!(coupons, [&"SPRING".into(), &"VIP".into()]);
+        assert_eq!(rows[1]["total"], 22.into());
+    }
+
+    #[test]
+    fn test_without_groups_is_one_row() {
+        let query = AggregateQuery::new(FilterCriteria::new())
+            .with_aggregate(Aggregate::count_all("orders"))
+            .with_aggregate(Aggregate::max("amount", "largest"));
+
+        let empty: Vec<HashMap<String, ConditionValue>> = Vec::new();
+        let rows = evaluate(&query, &empty).unwrap();
+
+        assert_eq!(
+            rows,
+            [HashMap::from([
+                ("orders".to_string(), 0.into()),
+                ("largest".to_string(), ConditionValue::Null),
+            ])]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let query = AggregateQuery::new(FilterCriteria::new())
+            .with_group_by("customer")
+            .with_aggregate(Aggregate::count_all("orders"))
+            .with_aggregate(Aggregate::count_distinct("coupon", "coupons"));
+
+        let value = serde_json::to_value(&query).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "criteria": { "version": 1 },
+                "group_by": ["customer"],
+                "aggregates": [
+                    { "function": "count", "alias": "orders" },
+                    { "function": "count_distinct", "field": "coupon", "alias": "coupons" }
+                ]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<AggregateQuery>(value).unwrap(),
+            query
+        );
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        let base = || AggregateQuery::new(FilterCriteria::new()).with_group_by("customer");
+
+        for query in [
+            AggregateQuery::new(FilterCriteria::new()),
+            base().with_aggregate(Aggregate::count_all("customer")),
+            base().with_having(Condition::gt("amount", 1.into())),
+            AggregateQuery::new(FilterCriteria::new().with_sort(SortOrder::asc("amount")))
+                .with_group_by("customer"),
+            AggregateQuery::new(FilterCriteria::new().with_columns(["amount"]))
+                .with_group_by("customer"),
+            base().with_aggregate(Aggregate::sum("customer", "total")),
+        ] {
+            assert!(
+                matches!(
+                    evaluate(&query, &orders()),
+                    Err(RepositoryError::InvalidInput(_))
+                ),
+                "{:?}",
+                query
+            );
+        }
+    }
+}
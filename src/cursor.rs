@@ -0,0 +1,752 @@
+//! Keyset (cursor) pagination
+//!
+//! Offset pagination re-scans every skipped row and shifts when rows are
+//! inserted. Keyset pagination instead seeks past the sort values of the last
+//! row it returned, encoded in an opaque [`Cursor`].
+//!
+//! The sort order must be total: end it with a unique column such as the id,
+//! and only sort on columns that are never NULL.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use wyvern::{CursorPagination, FilterCriteria, Queryable, SortOrder};
+//!
+//! let criteria = FilterCriteria::new()
+//!     .with_sort(SortOrder::desc("created_at"))
+//!     .with_sort(SortOrder::asc("id"));
+//!
+//! let first = users.paginate_by_cursor(criteria.clone(), CursorPagination::new(50)).await?;
+//! if let Some(next) = &first.next_cursor {
+//!     let pagination = CursorPagination::new(50).with_cursor(next.parse()?);
+//!     let second = users.paginate_by_cursor(criteria, pagination).await?;
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+use crate::entity::FieldValues;
+use crate::error::RepositoryError;
+use crate::query::{
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, SortDirection, SortOrder,
+    ValueKind,
+};
+use crate::relation::Relation;
+
+/// Version tag of the cursor encoding
+const CURSOR_VERSION: char = '1';
+
+/// Which side of the cursor row a page lies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorDirection {
+    /// Rows following the cursor in sort order.
+    After,
+    /// Rows preceding the cursor in sort order.
+    Before,
+}
+
+/// An opaque position in a sorted result set.
+///
+/// Holds the sort fields and values of a row. Cursors are created by
+/// `Queryable::paginate_by_cursor` and round-trip through their string form
+/// (URL-safe base64), which is what clients should see.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    direction: CursorDirection,
+    keys: Vec<(String, ConditionValue)>,
+}
+
+impl Cursor {
+    /// Returns which side of the row the cursor points to.
+    pub fn direction(&self) -> CursorDirection {
+        self.direction
+    }
+
+    /// Encodes the cursor as an opaque URL-safe string.
+    pub fn encode(&self) -> String {
+        let mut payload = String::new();
+        payload.push(CURSOR_VERSION);
+        payload.push(match self.direction {
+            CursorDirection::After => '>',
+            CursorDirection::Before => '<',
+        });
+        for (field, value) in &self.keys {
+            push_text(&mut payload, field);
+            match value {
+                ConditionValue::String(s) => {
+                    payload.push('s');
+                    push_text(&mut payload, s);
+                }
+                ConditionValue::Integer(i) => payload.push_str(&format!("i{};", i)),
+                ConditionValue::Float(f) => payload.push_str(&format!("f{:?};", f)),
+                ConditionValue::Boolean(b) => payload.push_str(if *b { "b1" } else { "b0" }),
+                value => match typed_text(value) {
+                    Some((tag, text)) => {
+                        payload.push(tag);
+                        push_text(&mut payload, &text);
+                    }
+                    // Cursors are only built from non-null scalar sort values
+                    None => payload.push('n'),
+                },
+            }
+        }
+        URL_SAFE_NO_PAD.encode(payload)
+    }
+
+    /// Decodes a cursor produced by [`Cursor::encode`].
+    ///
+    /// Malformed cursors are reported as `RepositoryError::InvalidInput`.
+    pub fn decode(encoded: &str) -> Result<Self, RepositoryError> {
+        let invalid = || RepositoryError::InvalidInput("invalid cursor".to_string());
+
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| invalid())?;
+        let payload = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut rest = payload.strip_prefix(CURSOR_VERSION).ok_or_else(invalid)?;
+
+        let direction = match rest.chars().next() {
+            Some('>') => CursorDirection::After,
+            Some('<') => CursorDirection::Before,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[1..];
+
+        let mut keys = Vec::new();
+        while !rest.is_empty() {
+            let field;
+            (field, rest) = take_text(rest).ok_or_else(invalid)?;
+            let value;
+            (value, rest) = take_value(rest).ok_or_else(invalid)?;
+            keys.push((field.to_string(), value));
+        }
+        if keys.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self { direction, keys })
+    }
+
+    /// Builds a cursor from the sort values of an entity.
+    pub(crate) fn from_entity<T: FieldValues + ?Sized>(
+        direction: CursorDirection,
+        sort: &[SortOrder],
+        entity: &T,
+    ) -> Result<Self, RepositoryError> {
+        let keys = sort
+            .iter()
+            .map(|s| match entity.field_value(&s.field) {
+                Some(value)
+                    if !matches!(
+                        value.kind(),
+                        ValueKind::List | ValueKind::Null | ValueKind::Json
+                    ) =>
+                {
+                    Ok((s.field.clone(), value))
+                }
+                _ => Err(RepositoryError::InvalidInput(format!(
+                    "sort field '{}' must be a non-null scalar for cursor pagination",
+                    s.field
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { direction, keys })
+    }
+
+    fn with_direction(&self, direction: CursorDirection) -> Self {
+        Self {
+            direction,
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = RepositoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode(s)
+    }
+}
+
+fn push_text(payload: &mut String, text: &str) {
+    payload.push_str(&text.len().to_string());
+    payload.push(':');
+    payload.push_str(text);
+}
+
+fn take_text(rest: &str) -> Option<(&str, &str)> {
+    let (len, rest) = rest.split_once(':')?;
+    let len: usize = len.parse().ok()?;
+    Some((rest.get(..len)?, rest.get(len..)?))
+}
+
+fn take_value(rest: &str) -> Option<(ConditionValue, &str)> {
+    let tag = rest.chars().next()?;
+    let rest = &rest[tag.len_utf8()..];
+    match tag {
+        's' => {
+            let (text, rest) = take_text(rest)?;
+            Some((ConditionValue::String(text.to_string()), rest))
+        }
+        'i' => {
+            let (number, rest) = rest.split_once(';')?;
+            Some((ConditionValue::Integer(number.parse().ok()?), rest))
+        }
+        'f' => {
+            let (number, rest) = rest.split_once(';')?;
+            Some((ConditionValue::Float(number.parse().ok()?), rest))
+        }
+        'b' => match rest.chars().next()? {
+            '1' => Some((ConditionValue::Boolean(true), &rest[1..])),
+            '0' => Some((ConditionValue::Boolean(false), &rest[1..])),
+            _ => None,
+        },
+        tag => {
+            let (text, rest) = take_text(rest)?;
+            Some((parse_typed(tag, text)?, rest))
+        }
+    }
+}
+
+/// Tags and renders the values beyond strings, numbers and booleans
+///
+/// `time` values use their numeric parts, which need no parsing support.
+fn typed_text(value: &ConditionValue) -> Option<(char, String)> {
+    match value {
+        ConditionValue::Bytes(b) => Some(('x', b.iter().map(|b| format!("{:02x}", b)).collect())),
+        #[cfg(feature = "uuid")]
+        ConditionValue::Uuid(u) => Some(('u', u.to_string())),
+        #[cfg(feature = "chrono")]
+        ConditionValue::NaiveDate(d) => Some(('d', d.to_string())),
+        #[cfg(feature = "chrono")]
+        ConditionValue::NaiveTime(t) => Some(('h', t.to_string())),
+        #[cfg(feature = "chrono")]
+        ConditionValue::NaiveDateTime(dt) => {
+            Some(('p', dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))
+        }
+        #[cfg(feature = "chrono")]
+        ConditionValue::DateTime(dt) => Some(('z', dt.to_rfc3339())),
+        #[cfg(feature = "time")]
+        ConditionValue::Date(d) => Some(('D', d.to_julian_day().to_string())),
+        #[cfg(feature = "time")]
+        ConditionValue::Time(t) => Some(('H', time_text(*t))),
+        #[cfg(feature = "time")]
+        ConditionValue::PrimitiveDateTime(dt) => Some((
+            'P',
+            format!("{},{}", dt.date().to_julian_day(), time_text(dt.time())),
+        )),
+        #[cfg(feature = "time")]
+        ConditionValue::OffsetDateTime(dt) => Some((
+            'Z',
+            format!(
+                "{},{}",
+                dt.unix_timestamp_nanos(),
+                dt.offset().whole_seconds()
+            ),
+        )),
+        #[cfg(feature = "rust_decimal")]
+        ConditionValue::Decimal(d) => Some(('m', d.to_string())),
+        _ => None,
+    }
+}
+
+fn parse_typed(tag: char, text: &str) -> Option<ConditionValue> {
+    match tag {
+        'x' => {
+            if !text.len().is_multiple_of(2) {
+                return None;
+            }
+            let bytes = (0..text.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            Some(ConditionValue::Bytes(bytes))
+        }
+        #[cfg(feature = "uuid")]
+        'u' => text.parse().ok().map(ConditionValue::Uuid),
+        #[cfg(feature = "chrono")]
+        'd' => text.parse().ok().map(ConditionValue::NaiveDate),
+        #[cfg(feature = "chrono")]
+        'h' => text.parse().ok().map(ConditionValue::NaiveTime),
+        #[cfg(feature = "chrono")]
+        'p' => text.parse().ok().map(ConditionValue::NaiveDateTime),
+        #[cfg(feature = "chrono")]
+        'z' => text.parse().ok().map(ConditionValue::DateTime),
+        #[cfg(feature = "time")]
+        'D' => parse_julian_day(text).map(ConditionValue::Date),
+        #[cfg(feature = "time")]
+        'H' => parse_time(text).map(ConditionValue::Time),
+        #[cfg(feature = "time")]
+        'P' => {
+            let (date, time) = text.split_once(',')?;
+            Some(ConditionValue::PrimitiveDateTime(
+                time::PrimitiveDateTime::new(parse_julian_day(date)?, parse_time(time)?),
+            ))
+        }
+        #[cfg(feature = "time")]
+        'Z' => {
+            let (nanos, offset) = text.split_once(',')?;
+            let offset = time::UtcOffset::from_whole_seconds(offset.parse().ok()?).ok()?;
+            let dt = time::OffsetDateTime::from_unix_timestamp_nanos(nanos.parse().ok()?).ok()?;
+            Some(ConditionValue::OffsetDateTime(dt.to_offset(offset)))
+        }
+        #[cfg(feature = "rust_decimal")]
+        'm' => text.parse().ok().map(ConditionValue::Decimal),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "time")]
+fn time_text(t: time::Time) -> String {
+    let (hour, minute, second, nanosecond) = t.as_hms_nano();
+    format!("{}:{}:{}:{}", hour, minute, second, nanosecond)
+}
+
+#[cfg(feature = "time")]
+fn parse_time(text: &str) -> Option<time::Time> {
+    let mut parts = text.split(':').map(|part| part.parse::<u32>().ok());
+    let mut next = || parts.next().flatten();
+    let (hour, minute, second, nanosecond) = (next()?, next()?, next()?, next()?);
+    time::Time::from_hms_nano(
+        u8::try_from(hour).ok()?,
+        u8::try_from(minute).ok()?,
+        u8::try_from(second).ok()?,
+        nanosecond,
+    )
+    .ok()
+}
+
+#[cfg(feature = "time")]
+fn parse_julian_day(text: &str) -> Option<time::Date> {
+    time::Date::from_julian_day(text.parse().ok()?).ok()
+}
+
+/// Cursor pagination parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorPagination {
+    /// Where the page starts; `None` fetches the first page.
+    pub cursor: Option<Cursor>,
+    /// The maximum number of items per page.
+    pub limit: i64,
+}
+
+impl CursorPagination {
+    /// Requests the first page of `limit` items.
+    pub fn new(limit: i64) -> Self {
+        Self {
+            cursor: None,
+            limit,
+        }
+    }
+
+    /// Continues from a cursor of a previous page.
+    pub fn with_cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+}
+
+/// A page of results fetched by cursor.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    /// Encoded cursor of the following page, if there is one.
+    pub next_cursor: Option<String>,
+    /// Encoded cursor of the preceding page, if there is one.
+    pub prev_cursor: Option<String>,
+}
+
+impl<T> CursorPage<T> {
+    pub fn has_next(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.prev_cursor.is_some()
+    }
+}
+
+/// The rows following a set of sort values.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Seek {
+    pub(crate) sort: Vec<SortOrder>,
+    pub(crate) values: Vec<ConditionValue>,
+}
+
+impl Seek {
+    /// Expands the seek into plain conditions:
+    /// `a > x OR (a = x AND b > y) OR ...`, flipping `>` to `<` for
+    /// descending fields.
+    pub(crate) fn expression(&self) -> FilterExpr {
+        FilterExpr::or((0..self.sort.len()).map(|i| {
+            let equal = self.sort[..i]
+                .iter()
+                .zip(&self.values)
+                .map(|(s, v)| FilterExpr::from(Condition::eq(s.field.clone(), v.clone())));
+            let operator = match self.sort[i].direction {
+                SortDirection::Ascending => Operator::GreaterThan,
+                SortDirection::Descending => Operator::LessThan,
+            };
+            let beyond =
+                Condition::new(self.sort[i].field.clone(), operator, self.values[i].clone());
+            FilterExpr::and(equal.chain([FilterExpr::from(beyond)]))
+        }))
+    }
+}
+
+/// A cursor page request resolved against the criteria.
+///
+/// Pages before a cursor are fetched by reversing the sort and seeking past
+/// the cursor, then reversing the rows back.
+pub(crate) struct CursorQuery {
+    /// Criteria to run, with the seek order, one extra row to detect more
+    /// pages, and no offset
+    pub(crate) criteria: FilterCriteria,
+    pub(crate) seek: Option<Seek>,
+    sort: Vec<SortOrder>,
+    cursor: Option<Cursor>,
+    limit: i64,
+}
+
+impl CursorQuery {
+    /// Resolves the page request, rejecting sorts whose values cannot be read
+    /// back from the returned entities: rank sorts and the fields of
+    /// `relations`.
+    pub(crate) fn new(
+        criteria: FilterCriteria,
+        pagination: CursorPagination,
+        relations: &[Relation],
+    ) -> Result<Self, RepositoryError> {
+        if criteria.sort.is_empty() {
+            return Err(RepositoryError::InvalidInput(
+                "cursor pagination requires a sort order".to_string(),
+            ));
+        }
+        if let Some(sort) = criteria.sort.iter().find(|s| s.rank.is_some()) {
+            return Err(RepositoryError::InvalidInput(format!(
+                "rank sort on '{}' cannot be used for cursor pagination",
+                sort.field
+            )));
+        }
+        if let Some(sort) = criteria
+            .sort
+            .iter()
+            .find(|s| Relation::find(relations, &s.field).is_some())
+        {
+            return Err(RepositoryError::InvalidInput(format!(
+                "sort on relation field '{}' cannot be used for cursor pagination",
+                sort.field
+            )));
+        }
+        if pagination.limit < 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "cursor page limit must be at least 1, got {}",
+                pagination.limit
+            )));
+        }
+        // One extra row tells whether there is a next page
+        let fetch = pagination.limit.checked_add(1).ok_or_else(|| {
+            RepositoryError::InvalidInput(format!(
+                "cursor page limit is too large, got {}",
+                pagination.limit
+            ))
+        })?;
+
+        let sort = criteria.sort.clone();
+        let backward = pagination
+            .cursor
+            .as_ref()
+            .is_some_and(|c| c.direction == CursorDirection::Before);
+        let seek_sort: Vec<SortOrder> = if backward {
+            sort.iter()
+                .map(|s| {
+                    let direction = match s.direction {
+                        SortDirection::Ascending => SortDirection::Descending,
+                        SortDirection::Descending => SortDirection::Ascending,
+                    };
+                    SortOrder::new(s.field.clone(), direction)
+                })
+                .collect()
+        } else {
+            sort.clone()
+        };
+
+        let seek = match &pagination.cursor {
+            None => None,
+            Some(cursor) => {
+                let matches_sort = cursor.keys.len() == sort.len()
+                    && cursor
+                        .keys
+                        .iter()
+                        .zip(&sort)
+                        .all(|((f, _), s)| *f == s.field);
+                if !matches_sort {
+                    return Err(RepositoryError::InvalidInput(
+                        "cursor does not match the sort order".to_string(),
+                    ));
+                }
+                Some(Seek {
+                    sort: seek_sort.clone(),
+                    values: cursor.keys.iter().map(|(_, v)| v.clone()).collect(),
+                })
+            }
+        };
+
+        let mut criteria = criteria;
+        criteria.sort = seek_sort;
+        criteria.limit = Some(fetch);
+        criteria.offset = None;
+
+        Ok(Self {
+            criteria,
+            seek,
+            sort,
+            cursor: pagination.cursor,
+            limit: pagination.limit,
+        })
+    }
+
+    /// Trims the extra row, restores the requested order and derives the
+    /// neighbouring cursors.
+    pub(crate) fn finish<T: FieldValues>(
+        self,
+        mut items: Vec<T>,
+    ) -> Result<CursorPage<T>, RepositoryError> {
+        let has_more = items.len() as i64 > self.limit;
+        items.truncate(self.limit as usize);
+
+        let backward = self
+            .cursor
+            .as_ref()
+            .is_some_and(|c| c.direction == CursorDirection::Before);
+        if backward {
+            items.reverse();
+        }
+
+        let edge = |direction: CursorDirection, item: Option<&T>| match (item, &self.cursor) {
+            (Some(item), _) => Cursor::from_entity(direction, &self.sort, item).map(Some),
+            // An empty page still links back to where it came from
+            (None, Some(cursor)) => Ok(Some(cursor.with_direction(direction))),
+            (None, None) => Ok(None),
+        };
+
+        let (next, prev) = match (&self.cursor, backward) {
+            (None, _) => (
+                if has_more {
+                    edge(CursorDirection::After, items.last())?
+                } else {
+                    None
+                },
+                None,
+            ),
+            (Some(_), false) => (
+                if has_more {
+                    edge(CursorDirection::After, items.last())?
+                } else {
+                    None
+                },
+                edge(CursorDirection::Before, items.first())?,
+            ),
+            (Some(_), true) => (
+                edge(CursorDirection::After, items.last())?,
+                if has_more {
+                    edge(CursorDirection::Before, items.first())?
+                } else {
+                    None
+                },
+            ),
+        };
+
+        Ok(CursorPage {
+            items,
+            next_cursor: next.map(|c| c.encode()),
+            prev_cursor: prev.map(|c| c.encode()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextSearch;
+    use std::collections::HashMap;
+
+    fn row(id: i64, score: f64) -> HashMap<String, ConditionValue> {
+        HashMap::from([
+            ("id".to_string(), id.into()),
+            ("score".to_string(), score.into()),
+            ("name".to_string(), format!("n;{}:", id).into()),
+        ])
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let sort = [
+            SortOrder::asc("name"),
+            SortOrder::desc("score"),
+            SortOrder::asc("id"),
+        ];
+        let cursor = Cursor::from_entity(CursorDirection::Before, &sort, &row(7, 2.0)).unwrap();
+
+        let encoded = cursor.encode();
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(encoded.parse::<Cursor>().unwrap(), cursor);
+        assert_eq!(cursor.direction(), CursorDirection::Before);
+    }
+
+    #[test]
+    fn test_invalid_cursors_are_rejected() {
+        for encoded in ["", "not base64!", &URL_SAFE_NO_PAD.encode("1>2:idx")] {
+            assert!(matches!(
+                Cursor::decode(encoded),
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+
+        let missing = HashMap::from([("id".to_string(), ConditionValue::Null)]);
+        assert!(
+            Cursor::from_entity(CursorDirection::After, &[SortOrder::asc("id")], &missing).is_err()
+        );
+    }
+
+    #[test]
+    fn test_seek_expression_respects_directions() {
+        let seek = Seek {
+            sort: vec![SortOrder::desc("score"), SortOrder::asc("id")],
+            values: vec![2.0.into(), 7.into()],
+        };
+
+        assert_eq!(
+            seek.expression(),
+            FilterExpr::or([
+                FilterExpr::and([Condition::new("score", Operator::LessThan, 2.0.into())]),
+                FilterExpr::and([
+                    Condition::eq("score", 2.0.into()),
+                    Condition::gt("id", 7.into())
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_query_requires_matching_sort() {
+        let criteria = FilterCriteria::new().with_sort(SortOrder::asc("id"));
+        assert!(CursorQuery::new(FilterCriteria::new(), CursorPagination::new(10), &[]).is_err());
+        assert!(CursorQuery::new(criteria.clone(), CursorPagination::new(0), &[]).is_err());
+        assert!(matches!(
+            CursorQuery::new(criteria.clone(), CursorPagination::new(i64::MAX), &[]),
+            Err(RepositoryError::InvalidInput(_))
+        ));
+        assert!(
+            CursorQuery::new(
+                FilterCriteria::new().with_sort(SortOrder::by_rank("body", TextSearch::plain("a"))),
+                CursorPagination::new(10),
+                &[]
+            )
+            .is_err()
+        );
+
+        // Related values are not part of the entity the cursor is read from
+        let customer = Relation::many_to_one("customer", "customers", "customer_id");
+        let by_customer = FilterCriteria::new()
+            .with_sort(SortOrder::asc("customer.name"))
+            .with_sort(SortOrder::asc("id"));
+        assert!(
+            CursorQuery::new(
+                by_customer,
+                CursorPagination::new(10),
+                std::slice::from_ref(&customer)
+            )
+            .is_err()
+        );
+
+        let other = Cursor::from_entity(
+            CursorDirection::After,
+            &[SortOrder::asc("score")],
+            &row(1, 1.0),
+        )
+        .unwrap();
+        assert!(
+            CursorQuery::new(criteria, CursorPagination::new(10).with_cursor(other), &[]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cursor_round_trips_bytes() {
+        let entity = HashMap::from([("key".to_string(), ConditionValue::from(vec![0u8, 255, 16]))]);
+        let cursor =
+            Cursor::from_entity(CursorDirection::After, &[SortOrder::asc("key")], &entity).unwrap();
+
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[cfg(all(
+        feature = "uuid",
+        feature = "chrono",
+        feature = "time",
+        feature = "rust_decimal"
+    ))]
+    #[test]
+    fn test_cursor_round_trips_typed_values() {
+        let entity = HashMap::from([
+            (
+                "id".to_string(),
+                ConditionValue::from(uuid::Uuid::from_u128(42)),
+            ),
+            (
+                "created_at".to_string(),
+                chrono::DateTime::from_timestamp(1_700_000_000, 123_456_000)
+                    .unwrap()
+                    .into(),
+            ),
+            (
+                "due".to_string(),
+                chrono::NaiveDate::from_ymd_opt(2024, 2, 29)
+                    .unwrap()
+                    .and_hms_micro_opt(23, 59, 59, 999_999)
+                    .unwrap()
+                    .into(),
+            ),
+            (
+                "logged_at".to_string(),
+                time::OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789)
+                    .unwrap()
+                    .to_offset(time::UtcOffset::from_hms(5, 30, 0).unwrap())
+                    .into(),
+            ),
+            (
+                "starts".to_string(),
+                time::PrimitiveDateTime::new(
+                    time::Date::from_calendar_date(1999, time::Month::December, 31).unwrap(),
+                    time::Time::from_hms_nano(23, 0, 1, 5).unwrap(),
+                )
+                .into(),
+            ),
+            (
+                "price".to_string(),
+                rust_decimal::Decimal::new(-12345, 3).into(),
+            ),
+        ]);
+        let sort: Vec<SortOrder> = ["id", "created_at", "due", "logged_at", "starts", "price"]
+            .into_iter()
+            .map(SortOrder::asc)
+            .collect();
+
+        let cursor = Cursor::from_entity(CursorDirection::After, &sort, &entity).unwrap();
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+}
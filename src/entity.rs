@@ -0,0 +1,285 @@
+//! Entity metadata and field access
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::query::{Condition, ConditionValue, Operator, SortOrder};
+use crate::relation::Relation;
+
+/// Access to an entity's field values by name.
+///
+/// Generic repositories use this to read the values they write to storage, and
+/// [`FilterCriteria::matches`](crate::FilterCriteria::matches) uses it to
+/// evaluate filters in process. Maps from field names to values implement it,
+/// so loosely typed payloads such as change events can be checked too.
+///
+/// # Example
+///
+/// ```rust
+/// use wyvern::{ConditionValue, FieldValues};
+///
+/// struct User {
+///     id: i64,
+///     email: String,
+/// }
+///
+/// impl FieldValues for User {
+///     fn field_value(&self, field: &str) -> Option<ConditionValue> {
+///         match field {
+///             "id" => Some(self.id.into()),
+///             "email" => Some(self.email.clone().into()),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait FieldValues {
+    /// Returns the value of the named field, or `None` if there is no such field.
+    fn field_value(&self, field: &str) -> Option<ConditionValue>;
+}
+
+impl<T: FieldValues + ?Sized> FieldValues for &T {
+    fn field_value(&self, field: &str) -> Option<ConditionValue> {
+        (**self).field_value(field)
+    }
+}
+
+impl<S: std::hash::BuildHasher> FieldValues for HashMap<String, ConditionValue, S> {
+    fn field_value(&self, field: &str) -> Option<ConditionValue> {
+        self.get(field).cloned()
+    }
+}
+
+impl FieldValues for BTreeMap<String, ConditionValue> {
+    fn field_value(&self, field: &str) -> Option<ConditionValue> {
+        self.get(field).cloned()
+    }
+}
+
+/// Describes how an entity maps to a database table.
+///
+/// With the `derive` feature this can be derived together with [`FieldValues`]:
+///
+/// ```rust,ignore
+/// use wyvern::Entity;
+///
+/// #[derive(Clone, Entity)]
+/// #[wyvern(table = "users")]
+/// struct User {
+///     #[wyvern(id)]
+///     user_id: i64,
+///     email: String,
+///     #[wyvern(column = "created")]
+///     created_at: String,
+/// }
+///
+/// assert_eq!(User::TABLE, "users");
+/// assert_eq!(User::COLUMNS, &["user_id", "email", "created"]);
+/// let criteria = FilterCriteria::new().with_sort(SortOrder::desc(User::CREATED_AT));
+/// ```
+pub trait Entity: FieldValues {
+    /// The type of the primary key
+    type Id: Send + Sync;
+
+    /// The table the entity is stored in
+    const TABLE: &'static str;
+
+    /// The primary key column
+    const ID_COLUMN: &'static str;
+
+    /// Every column of the entity, including the primary key
+    const COLUMNS: &'static [&'static str];
+
+    /// Returns the primary key of this entity.
+    fn id(&self) -> Self::Id;
+
+    /// The relations conditions on this entity may traverse, none by default
+    fn relations() -> Vec<Relation> {
+        Vec::new()
+    }
+}
+
+/// A reference to a column of entity `E` holding values of type `V`.
+///
+/// Columns build conditions and sort orders whose values are checked against
+/// `V` at compile time. For nullable columns `V` is the inner type, not `Option<V>`.
+/// Columns also convert into field names, so they can be used wherever
+/// `Condition` or `SortOrder` expect one.
+///
+/// ```rust
+/// use wyvern::Column;
+///
+/// struct User;
+/// const AGE: Column<User, i32> = Column::new("age");
+/// const STATUS: Column<User, String> = Column::new("status");
+///
+/// let adults = AGE.gt(18);
+/// let active = STATUS.eq("active");
+/// let oldest_first = AGE.desc();
+/// ```
+///
+/// Values of the wrong type are rejected:
+///
+/// ```rust,compile_fail
+/// use wyvern::Column;
+///
+/// struct User;
+/// const AGE: Column<User, i32> = Column::new("age");
+///
+/// let condition = AGE.gt("eighteen");
+/// ```
+pub struct Column<E, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (E, V)>,
+}
+
+impl<E, V> Column<E, V> {
+    /// Creates a reference to the named column.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the column name.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<E, V: Into<ConditionValue>> Column<E, V> {
+    fn condition(self, operator: Operator, value: impl Into<V>) -> Condition {
+        Condition::new(self.name, operator, value.into().into())
+    }
+
+    /// Creates an equality condition.
+    pub fn eq(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::Equal, value)
+    }
+
+    /// Creates a not-equal condition.
+    pub fn ne(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::NotEqual, value)
+    }
+
+    /// Creates a greater-than condition.
+    pub fn gt(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::GreaterThan, value)
+    }
+
+    /// Creates a greater-than-or-equal condition.
+    pub fn gte(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::GreaterThanOrEqual, value)
+    }
+
+    /// Creates a less-than condition.
+    pub fn lt(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::LessThan, value)
+    }
+
+    /// Creates a less-than-or-equal condition.
+    pub fn lte(self, value: impl Into<V>) -> Condition {
+        self.condition(Operator::LessThanOrEqual, value)
+    }
+
+    /// Creates an IN condition.
+    pub fn in_list<I>(self, values: I) -> Condition
+    where
+        I: IntoIterator,
+        I::Item: Into<V>,
+    {
+        let values = values.into_iter().map(|v| v.into().into()).collect();
+        Condition::in_list(self.name, values)
+    }
+
+    /// Creates a NOT IN condition.
+    pub fn not_in<I>(self, values: I) -> Condition
+    where
+        I: IntoIterator,
+        I::Item: Into<V>,
+    {
+        let values = values.into_iter().map(|v| v.into().into()).collect();
+        Condition::not_in(self.name, values)
+    }
+
+    /// Creates an inclusive BETWEEN condition.
+    pub fn between(self, low: impl Into<V>, high: impl Into<V>) -> Condition {
+        Condition::between(self.name, low.into().into(), high.into().into())
+    }
+}
+
+impl<E> Column<E, String> {
+    /// Creates a LIKE condition.
+    pub fn like(self, pattern: impl Into<String>) -> Condition {
+        Condition::like(self.name, pattern)
+    }
+
+    /// Creates a NOT LIKE condition.
+    pub fn not_like(self, pattern: impl Into<String>) -> Condition {
+        Condition::not_like(self.name, pattern)
+    }
+
+    /// Creates a case-sensitive LIKE condition.
+    pub fn case_sensitive_like(self, pattern: impl Into<String>) -> Condition {
+        Condition::case_sensitive_like(self.name, pattern)
+    }
+
+    /// Creates a prefix condition.
+    pub fn starts_with(self, prefix: impl Into<String>) -> Condition {
+        Condition::starts_with(self.name, prefix)
+    }
+
+    /// Creates a suffix condition.
+    pub fn ends_with(self, suffix: impl Into<String>) -> Condition {
+        Condition::ends_with(self.name, suffix)
+    }
+
+    /// Creates a substring condition.
+    pub fn contains(self, text: impl Into<String>) -> Condition {
+        Condition::contains(self.name, text)
+    }
+}
+
+impl<E, V> Column<E, V> {
+    /// Creates an IS NULL condition.
+    pub fn is_null(self) -> Condition {
+        Condition::new(self.name, Operator::IsNull, ConditionValue::Null)
+    }
+
+    /// Creates an IS NOT NULL condition.
+    pub fn is_not_null(self) -> Condition {
+        Condition::new(self.name, Operator::IsNotNull, ConditionValue::Null)
+    }
+
+    /// Creates an ascending sort order.
+    pub fn asc(self) -> SortOrder {
+        SortOrder::asc(self.name)
+    }
+
+    /// Creates a descending sort order.
+    pub fn desc(self) -> SortOrder {
+        SortOrder::desc(self.name)
+    }
+}
+
+impl<E, V> Clone for Column<E, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E, V> Copy for Column<E, V> {}
+
+impl<E, V> fmt::Debug for Column<E, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Column").field(&self.name).finish()
+    }
+}
+
+impl<E, V> From<Column<E, V>> for String {
+    fn from(column: Column<E, V>) -> Self {
+        column.name.to_string()
+    }
+}
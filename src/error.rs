@@ -30,3 +30,94 @@ impl fmt::Display for RepositoryError {
 }
 
 impl Error for RepositoryError {}
+
+/// SQLSTATE `invalid_binary_representation`
+#[cfg(feature = "sqlx")]
+const INVALID_BINARY_REPRESENTATION: &str = "22P03";
+
+#[cfg(feature = "sqlx")]
+impl From<sqlx::Error> for RepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => Self::NotFound(error.to_string()),
+            // Strings are sent untyped but in binary format, which Postgres
+            // only reads for text-like columns
+            sqlx::Error::Database(ref db_error)
+                if db_error.code().as_deref() == Some(INVALID_BINARY_REPRESENTATION) =>
+            {
+                Self::InvalidInput(format!(
+                    "a value does not match its column type, pass a typed value \
+                     instead of a string (for example through FilterSchema::coerce): {}",
+                    db_error.message()
+                ))
+            }
+            sqlx::Error::Database(ref db_error) => match db_error.kind() {
+                sqlx::error::ErrorKind::Other => Self::QueryError(error.to_string()),
+                _ => Self::ConstraintViolation(error.to_string()),
+            },
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => Self::ConnectionError(error.to_string()),
+            sqlx::Error::BeginFailed | sqlx::Error::InvalidSavePointStatement => {
+                Self::TransactionError(error.to_string())
+            }
+            _ => Self::QueryError(error.to_string()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlx"))]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[derive(Debug)]
+    struct DatabaseError(&'static str);
+
+    impl fmt::Display for DatabaseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "database error {}", self.0)
+        }
+    }
+
+    impl Error for DatabaseError {}
+
+    impl sqlx::error::DatabaseError for DatabaseError {
+        fn message(&self) -> &str {
+            "incorrect binary data format in bind parameter 1"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn test_binary_format_errors_are_invalid_input() {
+        let error = RepositoryError::from(sqlx::Error::Database(Box::new(DatabaseError("22P03"))));
+        assert!(
+            matches!(error, RepositoryError::InvalidInput(msg) if msg.contains("bind parameter 1"))
+        );
+
+        let error = RepositoryError::from(sqlx::Error::Database(Box::new(DatabaseError("42883"))));
+        assert!(matches!(error, RepositoryError::QueryError(_)));
+    }
+}
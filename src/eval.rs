@@ -0,0 +1,735 @@
+//! In-process evaluation of filter criteria
+//!
+//! Conditions are evaluated with SQL's three-valued logic so results match
+//! what the SQLx adapter returns: comparisons involving NULL (or values of
+//! incompatible kinds) are unknown, and only rows whose filter is known to be
+//! true are kept.
+
+use std::cmp::Ordering;
+
+use crate::entity::FieldValues;
+use crate::query::{
+    Condition, ConditionValue, FieldPath, FilterCriteria, FilterExpr, Operator, SortDirection,
+    SortOrder,
+};
+use crate::search;
+
+/// Returns whether the entity matches every condition and filter of the criteria.
+pub(crate) fn matches<T: FieldValues + ?Sized>(criteria: &FilterCriteria, entity: &T) -> bool {
+    let conditions = criteria
+        .conditions
+        .iter()
+        .map(|c| evaluate_condition(c, entity));
+    let filters = criteria.filters.iter().map(|f| evaluate(f, entity));
+    and(conditions.chain(filters)) == Some(true)
+}
+
+/// Evaluates an expression, returning `None` when the result is unknown.
+pub(crate) fn evaluate<T: FieldValues + ?Sized>(expr: &FilterExpr, entity: &T) -> Option<bool> {
+    match expr {
+        FilterExpr::And(exprs) => and(exprs.iter().map(|e| evaluate(e, entity))),
+        FilterExpr::Or(exprs) => or(exprs.iter().map(|e| evaluate(e, entity))),
+        FilterExpr::Not(expr) => evaluate(expr, entity).map(|b| !b),
+        FilterExpr::Leaf(condition) => evaluate_condition(condition, entity),
+    }
+}
+
+/// Evaluates a single condition, returning `None` when the result is unknown.
+pub(crate) fn evaluate_condition<T: FieldValues + ?Sized>(
+    condition: &Condition,
+    entity: &T,
+) -> Option<bool> {
+    let actual = field_value(entity, &condition.field);
+    let expected = &condition.value;
+
+    match condition.operator {
+        Operator::IsNull => Some(matches!(actual, ConditionValue::Null)),
+        Operator::IsNotNull => Some(!matches!(actual, ConditionValue::Null)),
+        Operator::Equal => equals(&actual, expected),
+        Operator::NotEqual => equals(&actual, expected).map(|e| !e),
+        Operator::GreaterThan => compare(&actual, expected).map(|o| o == Ordering::Greater),
+        Operator::GreaterThanOrEqual => compare(&actual, expected).map(|o| o != Ordering::Less),
+        Operator::LessThan => compare(&actual, expected).map(|o| o == Ordering::Less),
+        Operator::LessThanOrEqual => compare(&actual, expected).map(|o| o != Ordering::Greater),
+        Operator::Like => like_text(&actual, expected, false),
+        Operator::NotLike => like_text(&actual, expected, false).map(|m| !m),
+        Operator::CaseSensitiveLike => like_text(&actual, expected, true),
+        Operator::In => in_list(&actual, expected),
+        Operator::NotIn => in_list(&actual, expected).map(|m| !m),
+        Operator::Between => match expected {
+            ConditionValue::List(bounds) => match bounds.as_slice() {
+                [low, high] => and([
+                    compare(&actual, low).map(|o| o != Ordering::Less),
+                    compare(&actual, high).map(|o| o != Ordering::Greater),
+                ]
+                .into_iter()),
+                _ => None,
+            },
+            _ => None,
+        },
+        Operator::StartsWith => affix(&actual, expected, |v, s| v.starts_with(s)),
+        Operator::EndsWith => affix(&actual, expected, |v, s| v.ends_with(s)),
+        Operator::Contains => affix(&actual, expected, |v, s| v.contains(s)),
+        Operator::JsonContains => json::contains(&actual, expected),
+        Operator::JsonHasKey => json::has_keys(&actual, std::slice::from_ref(expected), true),
+        Operator::JsonHasAnyKey | Operator::JsonHasAllKeys => match expected {
+            ConditionValue::List(keys) => json::has_keys(
+                &actual,
+                keys,
+                condition.operator == Operator::JsonHasAllKeys,
+            ),
+            _ => None,
+        },
+        // jsonpath is not implemented in process
+        Operator::JsonPathExists => None,
+        Operator::ArrayOverlaps => arrays(&actual, expected, |a, b| {
+            b.iter().any(|v| has_element(a, v))
+        }),
+        Operator::ArrayContains => arrays(&actual, expected, |a, b| {
+            b.iter().all(|v| has_element(a, v))
+        }),
+        Operator::ArrayContainedBy => arrays(&actual, expected, |a, b| {
+            a.iter().all(|v| has_element(b, v))
+        }),
+        Operator::EqualsAny => in_list(&actual, expected),
+        Operator::TextSearch => match (&actual, expected) {
+            (ConditionValue::String(document), ConditionValue::TextSearch(search)) => {
+                Some(search::matches(document, search))
+            }
+            _ => None,
+        },
+    }
+}
+
+/// Looks up a field, following its JSON path into the column if it has one
+pub(crate) fn field_value<T: FieldValues + ?Sized>(entity: &T, field: &str) -> ConditionValue {
+    match FieldPath::parse(field) {
+        Ok(path) if !path.keys.is_empty() => match entity.field_value(path.column) {
+            Some(value) => json::select(&value, &path),
+            None => ConditionValue::Null,
+        },
+        _ => entity.field_value(field).unwrap_or(ConditionValue::Null),
+    }
+}
+
+fn like_text(
+    actual: &ConditionValue,
+    pattern: &ConditionValue,
+    case_sensitive: bool,
+) -> Option<bool> {
+    match (actual, pattern) {
+        (ConditionValue::String(value), ConditionValue::String(pattern)) if case_sensitive => {
+            Some(like(value, pattern))
+        }
+        (ConditionValue::String(value), ConditionValue::String(pattern)) => {
+            Some(like(&value.to_lowercase(), &pattern.to_lowercase()))
+        }
+        _ => None,
+    }
+}
+
+fn in_list(actual: &ConditionValue, expected: &ConditionValue) -> Option<bool> {
+    match expected {
+        ConditionValue::List(values) => or(values.iter().map(|v| equals(actual, v))),
+        value => equals(actual, value),
+    }
+}
+
+/// Applies `test` to an array value and a list, as for PostgreSQL's array operators
+fn arrays(
+    actual: &ConditionValue,
+    expected: &ConditionValue,
+    test: fn(&[ConditionValue], &[ConditionValue]) -> bool,
+) -> Option<bool> {
+    match (actual, expected) {
+        (ConditionValue::List(actual), ConditionValue::List(expected)) => {
+            Some(test(actual, expected))
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether the array holds an element equal to `value`; NULLs equal nothing
+fn has_element(array: &[ConditionValue], value: &ConditionValue) -> bool {
+    array
+        .iter()
+        .any(|element| equals(element, value) == Some(true))
+}
+
+/// Matches the lowercased text of a string value with `test`
+fn affix(
+    actual: &ConditionValue,
+    expected: &ConditionValue,
+    test: fn(&str, &str) -> bool,
+) -> Option<bool> {
+    match (actual, expected) {
+        (ConditionValue::String(value), ConditionValue::String(text)) => {
+            Some(test(&value.to_lowercase(), &text.to_lowercase()))
+        }
+        _ => None,
+    }
+}
+
+/// Compares two scalar values, or returns `None` if either is NULL or they are
+/// not comparable.
+///
+/// Integers, floats and decimals compare numerically with each other; other
+/// kinds only compare with themselves.
+pub(crate) fn compare(a: &ConditionValue, b: &ConditionValue) -> Option<Ordering> {
+    match (a, b) {
+        (ConditionValue::String(a), ConditionValue::String(b)) => Some(a.cmp(b)),
+        (ConditionValue::Integer(a), ConditionValue::Integer(b)) => Some(a.cmp(b)),
+        (ConditionValue::Integer(a), ConditionValue::Float(b)) => (*a as f64).partial_cmp(b),
+        (ConditionValue::Float(a), ConditionValue::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (ConditionValue::Float(a), ConditionValue::Float(b)) => a.partial_cmp(b),
+        (ConditionValue::Boolean(a), ConditionValue::Boolean(b)) => Some(a.cmp(b)),
+        (ConditionValue::Bytes(a), ConditionValue::Bytes(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "uuid")]
+        (ConditionValue::Uuid(a), ConditionValue::Uuid(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "chrono")]
+        (ConditionValue::NaiveDate(a), ConditionValue::NaiveDate(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "chrono")]
+        (ConditionValue::NaiveTime(a), ConditionValue::NaiveTime(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "chrono")]
+        (ConditionValue::NaiveDateTime(a), ConditionValue::NaiveDateTime(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "chrono")]
+        (ConditionValue::DateTime(a), ConditionValue::DateTime(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "time")]
+        (ConditionValue::Date(a), ConditionValue::Date(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "time")]
+        (ConditionValue::Time(a), ConditionValue::Time(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "time")]
+        (ConditionValue::PrimitiveDateTime(a), ConditionValue::PrimitiveDateTime(b)) => {
+            Some(a.cmp(b))
+        }
+        #[cfg(feature = "time")]
+        (ConditionValue::OffsetDateTime(a), ConditionValue::OffsetDateTime(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "rust_decimal")]
+        (ConditionValue::Decimal(a), ConditionValue::Decimal(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "rust_decimal")]
+        (ConditionValue::Decimal(a), ConditionValue::Integer(b)) => {
+            Some(a.cmp(&rust_decimal::Decimal::from(*b)))
+        }
+        #[cfg(feature = "rust_decimal")]
+        (ConditionValue::Integer(a), ConditionValue::Decimal(b)) => {
+            Some(rust_decimal::Decimal::from(*a).cmp(b))
+        }
+        #[cfg(feature = "rust_decimal")]
+        (ConditionValue::Decimal(a), ConditionValue::Float(b)) => {
+            f64::try_from(*a).ok()?.partial_cmp(b)
+        }
+        #[cfg(feature = "rust_decimal")]
+        (ConditionValue::Float(a), ConditionValue::Decimal(b)) => {
+            a.partial_cmp(&f64::try_from(*b).ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Tests two values for equality, or returns `None` if either is NULL or they
+/// are not comparable.
+///
+/// Unlike [`compare`], this also covers JSON values, which have no order.
+pub(crate) fn equals(a: &ConditionValue, b: &ConditionValue) -> Option<bool> {
+    match (a, b) {
+        #[cfg(feature = "json")]
+        (ConditionValue::Json(a), ConditionValue::Json(b)) => Some(a == b),
+        (a, b) => compare(a, b).map(|o| o == Ordering::Equal),
+    }
+}
+
+/// Orders two entities by the criteria's sort orders.
+///
+/// Like PostgreSQL, NULLs sort last in ascending order and first in descending order.
+pub(crate) fn sort_ordering<T: FieldValues + ?Sized>(
+    criteria: &FilterCriteria,
+    a: &T,
+    b: &T,
+) -> Ordering {
+    for sort in &criteria.sort {
+        let a = sort_value(a, sort);
+        let b = sort_value(b, sort);
+        let ordering = match (&a, &b) {
+            (ConditionValue::Null, ConditionValue::Null) => Ordering::Equal,
+            (ConditionValue::Null, _) => Ordering::Greater,
+            (_, ConditionValue::Null) => Ordering::Less,
+            (a, b) => compare(a, b).unwrap_or(Ordering::Equal),
+        };
+        let ordering = match sort.direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Returns the value an entity is sorted by: its field, or the field's rank
+fn sort_value<T: FieldValues + ?Sized>(entity: &T, sort: &SortOrder) -> ConditionValue {
+    let value = field_value(entity, &sort.field);
+    match (&sort.rank, value) {
+        (None, value) => value,
+        (Some(search), ConditionValue::String(document)) => {
+            ConditionValue::Float(search::rank(&document, search))
+        }
+        (Some(_), _) => ConditionValue::Null,
+    }
+}
+
+fn and(results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    let mut result = Some(true);
+    for r in results {
+        match r {
+            Some(false) => return Some(false),
+            None => result = None,
+            Some(true) => {}
+        }
+    }
+    result
+}
+
+fn or(results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    let mut result = Some(false);
+    for r in results {
+        match r {
+            Some(true) => return Some(true),
+            None => result = None,
+            Some(false) => {}
+        }
+    }
+    result
+}
+
+/// Matches `value` against a SQL LIKE pattern
+///
+/// `%` matches any sequence, `_` any single character, and `\` escapes the
+/// next pattern character, as with PostgreSQL's default escape.
+pub(crate) fn like(value: &str, pattern: &str) -> bool {
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '%' => Token::Any,
+            '_' => Token::One,
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            c => Token::Char(c),
+        });
+    }
+
+    let value: Vec<char> = value.chars().collect();
+    // matched[j] is true when the pattern consumed so far matches value[..j]
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+    for token in &tokens {
+        let mut next = vec![false; value.len() + 1];
+        match token {
+            Token::Any => {
+                let mut reachable = false;
+                for j in 0..=value.len() {
+                    reachable |= matched[j];
+                    next[j] = reachable;
+                }
+            }
+            Token::One => next[1..].copy_from_slice(&matched[..value.len()]),
+            Token::Char(c) => {
+                for j in 1..=value.len() {
+                    next[j] = matched[j - 1] && value[j - 1] == *c;
+                }
+            }
+        }
+        matched = next;
+    }
+    matched[value.len()]
+}
+
+/// JSON path selection and operators, following PostgreSQL's `jsonb` semantics
+#[cfg(feature = "json")]
+mod json {
+    use serde_json::Value;
+
+    use crate::query::{ConditionValue, FieldPath};
+
+    /// Selects the value at `path`, as text when it ends with `->>`
+    pub(super) fn select(value: &ConditionValue, path: &FieldPath) -> ConditionValue {
+        let ConditionValue::Json(root) = value else {
+            return ConditionValue::Null;
+        };
+        let mut current = root;
+        for key in &path.keys {
+            let next = match (current, FieldPath::index(key)) {
+                (Value::Array(items), Some(index)) => {
+                    // Negative indices count from the end
+                    let index = if index < 0 {
+                        items.len().checked_sub(index.unsigned_abs() as usize)
+                    } else {
+                        Some(index as usize)
+                    };
+                    index.and_then(|i| items.get(i))
+                }
+                (Value::Object(map), _) => map.get(*key),
+                _ => None,
+            };
+            match next {
+                Some(next) => current = next,
+                None => return ConditionValue::Null,
+            }
+        }
+
+        match current {
+            Value::Null if path.as_text => ConditionValue::Null,
+            Value::String(text) if path.as_text => ConditionValue::String(text.clone()),
+            other if path.as_text => ConditionValue::String(other.to_string()),
+            other => ConditionValue::Json(other.clone()),
+        }
+    }
+
+    pub(super) fn contains(actual: &ConditionValue, expected: &ConditionValue) -> Option<bool> {
+        let ConditionValue::Json(actual) = actual else {
+            return None;
+        };
+        match expected {
+            ConditionValue::Json(expected) => Some(json_contains(actual, expected, true)),
+            ConditionValue::String(text) => {
+                let expected = serde_json::from_str(text).ok()?;
+                Some(json_contains(actual, &expected, true))
+            }
+            _ => None,
+        }
+    }
+
+    fn json_contains(a: &Value, b: &Value, top_level: bool) -> bool {
+        match (a, b) {
+            (Value::Object(a), Value::Object(b)) => b
+                .iter()
+                .all(|(key, b)| a.get(key).is_some_and(|a| json_contains(a, b, false))),
+            (Value::Array(a), Value::Array(b)) => b
+                .iter()
+                .all(|b| a.iter().any(|a| json_contains(a, b, false))),
+            // Only a top-level array contains a bare primitive
+            (Value::Array(a), b) if top_level && !b.is_object() => a.contains(b),
+            (a, b) => a == b,
+        }
+    }
+
+    pub(super) fn has_keys(
+        actual: &ConditionValue,
+        keys: &[ConditionValue],
+        all: bool,
+    ) -> Option<bool> {
+        let ConditionValue::Json(value) = actual else {
+            return None;
+        };
+        let mut found = Vec::with_capacity(keys.len());
+        for key in keys {
+            let ConditionValue::String(key) = key else {
+                return None;
+            };
+            found.push(match value {
+                Value::Object(map) => map.contains_key(key),
+                Value::Array(items) => items.iter().any(|item| item.as_str() == Some(key)),
+                Value::String(text) => text == key,
+                _ => false,
+            });
+        }
+        Some(if all {
+            found.into_iter().all(|f| f)
+        } else {
+            found.into_iter().any(|f| f)
+        })
+    }
+}
+
+/// Without the `json` feature there are no JSON values to look into
+#[cfg(not(feature = "json"))]
+mod json {
+    use crate::query::{ConditionValue, FieldPath};
+
+    pub(super) fn select(_value: &ConditionValue, _path: &FieldPath) -> ConditionValue {
+        ConditionValue::Null
+    }
+
+    pub(super) fn contains(_actual: &ConditionValue, _expected: &ConditionValue) -> Option<bool> {
+        None
+    }
+
+    pub(super) fn has_keys(
+        _actual: &ConditionValue,
+        _keys: &[ConditionValue],
+        _all: bool,
+    ) -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use std::cmp::Ordering;
+
+    use crate::{
+        Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, SortOrder, TextSearch,
+    };
+
+    fn event() -> HashMap<String, ConditionValue> {
+        HashMap::from([
+            ("status".to_string(), "active".into()),
+            ("role".to_string(), "admin".into()),
+            ("banned".to_string(), false.into()),
+            ("age".to_string(), 30.into()),
+            ("nickname".to_string(), ConditionValue::Null),
+        ])
+    }
+
+    #[test]
+    fn test_criteria_matches() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("status", "active".into()))
+            .with_condition(Condition::new(
+                "age",
+                Operator::GreaterThanOrEqual,
+                30.0.into(),
+            ));
+
+        assert!(criteria.matches(&event()));
+        assert!(
+            !criteria
+                .with_condition(Condition::eq("role", "owner".into()))
+                .matches(&event())
+        );
+        assert!(FilterCriteria::new().matches(&event()));
+    }
+
+    #[test]
+    fn test_expression_matches() {
+        let expr = FilterExpr::or([
+            FilterExpr::from(Condition::eq("status", "suspended".into())),
+            FilterExpr::and([
+                FilterExpr::from(Condition::eq("role", "admin".into())),
+                FilterExpr::not(Condition::eq("banned", true.into())),
+            ]),
+        ]);
+
+        assert!(expr.matches(&event()));
+        assert!(!FilterExpr::not(expr).matches(&event()));
+    }
+
+    #[test]
+    fn test_null_and_missing_fields() {
+        let is_null = Condition::new("nickname", Operator::IsNull, ConditionValue::Null);
+        let missing_is_null = Condition::new("email", Operator::IsNull, ConditionValue::Null);
+        let ne_null = Condition::ne("nickname", "bob".into());
+
+        assert!(is_null.matches(&event()));
+        assert!(missing_is_null.matches(&event()));
+        assert!(!ne_null.matches(&event()));
+        assert!(!FilterExpr::not(ne_null).matches(&event()));
+    }
+
+    #[test]
+    fn test_incomparable_values_never_match() {
+        let condition = Condition::gt("status", 3.into());
+
+        assert!(!condition.matches(&event()));
+        assert!(!FilterExpr::not(condition).matches(&event()));
+    }
+
+    #[test]
+    fn test_pattern_range_and_list_operators() {
+        let matching = [
+            Condition::between("age", 30.into(), 40.into()),
+            Condition::not_in("role", vec!["owner".into(), "guest".into()]),
+            Condition::not_like("status", "sus%"),
+            Condition::case_sensitive_like("status", "act%"),
+            Condition::starts_with("status", "ACT"),
+            Condition::ends_with("status", "ive"),
+            Condition::contains("role", "dm"),
+        ];
+        for condition in matching {
+            assert!(condition.matches(&event()), "{:?}", condition);
+        }
+
+        let failing = [
+            Condition::between("age", 31.into(), 40.into()),
+            Condition::not_in("role", vec!["admin".into()]),
+            Condition::case_sensitive_like("status", "ACT%"),
+            Condition::contains("status", "%"),
+            Condition::starts_with("age", "3"),
+            // NULL is neither in nor out of a list
+            Condition::not_in("nickname", vec!["bob".into()]),
+        ];
+        for condition in failing {
+            assert!(!condition.matches(&event()), "{:?}", condition);
+        }
+
+        // Nothing is in an empty list, not even NULL
+        assert!(Condition::not_in("nickname", vec![]).matches(&event()));
+    }
+
+    #[test]
+    fn test_array_operators() {
+        let post = HashMap::from([
+            (
+                "tags".to_string(),
+                ConditionValue::List(vec!["rust".into(), "sql".into(), ConditionValue::Null]),
+            ),
+            ("author".to_string(), "ada".into()),
+        ]);
+
+        let matching = [
+            Condition::array_overlaps("tags", vec!["go".into(), "sql".into()]),
+            Condition::array_contains("tags", vec!["sql".into(), "rust".into()]),
+            Condition::array_contains("tags", vec![]),
+            Condition::equals_any("author", vec!["bob".into(), "ada".into()]),
+        ];
+        for condition in matching {
+            assert!(condition.matches(&post), "{:?}", condition);
+        }
+
+        let failing = [
+            Condition::array_overlaps("tags", vec!["go".into()]),
+            Condition::array_contains("tags", vec!["rust".into(), ConditionValue::Null]),
+            // The NULL element is in no list
+            Condition::array_contained_by("tags", vec!["rust".into(), "sql".into()]),
+            Condition::array_overlaps("missing", vec!["rust".into()]),
+        ];
+        for condition in failing {
+            assert!(!condition.matches(&post), "{:?}", condition);
+        }
+    }
+
+    #[test]
+    fn test_text_search_and_rank_sort() {
+        let post = |id: i64, body: &str| {
+            HashMap::from([
+                ("id".to_string(), ConditionValue::from(id)),
+                ("body".to_string(), body.into()),
+            ])
+        };
+        let posts = [
+            post(1, "Rust and more Rust"),
+            post(2, "Go"),
+            post(3, "Rust"),
+        ];
+        let search = TextSearch::plain("rust");
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::text_search("body", search.clone()))
+            .with_sort(SortOrder::by_rank("body", search));
+
+        let matching: Vec<_> = posts.iter().filter(|p| criteria.matches(*p)).collect();
+        assert_eq!(matching.len(), 2);
+        assert_eq!(
+            super::sort_ordering(&criteria, matching[0], matching[1]),
+            Ordering::Less
+        );
+        assert!(
+            !Condition::text_search("id", TextSearch::plain("1")).matches(&posts[0]),
+            "only text is searched"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_paths_and_operators() {
+        let account = HashMap::from([(
+            "metadata".to_string(),
+            ConditionValue::from(serde_json::json!({
+                "plan": { "tier": "pro", "seats": 12 },
+                "tags": ["beta", "eu"],
+                "trial": null,
+                "2024": { "renewed": true }
+            })),
+        )]);
+
+        let matching = [
+            Condition::eq("metadata->plan->>tier", "pro".into()),
+            Condition::eq("metadata->plan->>seats", "12".into()),
+            Condition::eq("metadata->tags->>[-1]", "eu".into()),
+            Condition::eq("metadata->tags->>[0]", "beta".into()),
+            Condition::eq("metadata->2024->>renewed", "true".into()),
+            Condition::eq("metadata->plan->seats", serde_json::json!(12).into()),
+            Condition::new("metadata->>trial", Operator::IsNull, ConditionValue::Null),
+            Condition::new("metadata->trial", Operator::IsNotNull, ConditionValue::Null),
+            Condition::json_contains("metadata", r#"{"plan":{"tier":"pro"}}"#.into()),
+            Condition::json_contains("metadata->tags", r#""beta""#.into()),
+            Condition::json_contains("metadata->tags", serde_json::json!(["eu"]).into()),
+            Condition::json_has_key("metadata", "plan"),
+            Condition::json_has_key("metadata->tags", "beta"),
+            Condition::json_has_any_key("metadata", ["nope", "trial"]),
+            Condition::json_has_all_keys("metadata", ["plan", "tags"]),
+        ];
+        for condition in matching {
+            assert!(condition.matches(&account), "{:?}", condition);
+        }
+
+        let failing = [
+            Condition::eq("metadata->plan->>tier", "free".into()),
+            Condition::eq("metadata->tags->>0", "beta".into()),
+            Condition::new(
+                "metadata->>missing",
+                Operator::IsNotNull,
+                ConditionValue::Null,
+            ),
+            Condition::json_contains("metadata", r#"{"plan":{"tier":"free"}}"#.into()),
+            Condition::json_has_all_keys("metadata", ["plan", "nope"]),
+            Condition::json_path_exists("metadata", "$.plan"),
+        ];
+        for condition in failing {
+            assert!(!condition.matches(&account), "{:?}", condition);
+        }
+    }
+
+    #[cfg(all(feature = "rust_decimal", feature = "json", feature = "chrono"))]
+    #[test]
+    fn test_typed_values() {
+        use chrono::NaiveDate;
+        use rust_decimal::Decimal;
+
+        let order = HashMap::from([
+            (
+                "total".to_string(),
+                ConditionValue::from(Decimal::new(1990, 2)),
+            ),
+            (
+                "placed_on".to_string(),
+                NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().into(),
+            ),
+            (
+                "meta".to_string(),
+                serde_json::json!({ "gift": true }).into(),
+            ),
+        ]);
+
+        let matching = FilterCriteria::new()
+            .with_condition(Condition::gt("total", 19.into()))
+            .with_condition(Condition::lt("total", 20.5.into()))
+            .with_condition(Condition::gt(
+                "placed_on",
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().into(),
+            ))
+            .with_condition(Condition::eq(
+                "meta",
+                serde_json::json!({ "gift": true }).into(),
+            ));
+        assert!(matching.matches(&order));
+
+        // Values of different kinds are never equal, nor unequal
+        let mismatched =
+            FilterCriteria::new().with_condition(Condition::ne("placed_on", "2024-03-01".into()));
+        assert!(!mismatched.matches(&order));
+
+        let other_meta = FilterCriteria::new().with_condition(Condition::ne(
+            "meta",
+            serde_json::json!({ "gift": false }).into(),
+        ));
+        assert!(other_meta.matches(&order));
+    }
+}
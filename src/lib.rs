@@ -6,8 +6,17 @@
 //! for data access layers, including:
 //!
 //! - **CRUD operations**: Basic create, read, update, and delete functionality
-//! - **Querying**: Advanced filtering, sorting, and pagination
+//! - **Querying**: Advanced filtering, sorting, pagination, and column projection
+//! - **Aggregation**: Grouping with counts, sums, averages, minimums and maximums
+//! - **Relations**: Filtering on the fields of related entities and loading them eagerly
+//! - **Cursor pagination**: Keyset paging with opaque cursors
 //! - **Transactions**: Support for transactional operations
+//! - **Validation**: Whitelisting of filterable and sortable fields
+//! - **Query strings**: Parsing filters and pagination from URL query strings
+//! - **RSQL**: Parsing and printing RSQL/FIQL filter expressions
+//! - **OData**: Parsing OData `$filter`, `$orderby`, `$select`, `$top` and `$skip` options
+//! - **Full-text search**: `tsvector`/`tsquery` conditions and `ts_rank` sorting
+//! - **Testing**: An in-memory repository evaluating filters in process
 //!
 //! ## Usage
 //!
@@ -29,6 +38,14 @@
 //! ## Optional Features
 //!
 //! - **sqlx**: Provides adapters for SQLx with PostgreSQL support
+//! - **derive**: Provides `#[derive(Entity)]` for entity metadata and field access
+//! - **serde**: Provides `Serialize`/`Deserialize` for the query types using the
+//!   versioned JSON format described in [`wire`]
+//! - **axum**: Provides extractors for `FilterCriteria` and `Pagination` and
+//!   `IntoResponse` for `Page` with `Link` headers (implies `serde`)
+//! - **uuid**, **chrono**, **time**, **rust_decimal**, **json**: Add the
+//!   matching [`ConditionValue`] variants, bound with their native Postgres
+//!   types when combined with `sqlx`
 //!
 //! ```toml
 //! [dependencies]
@@ -48,20 +65,46 @@
 //! let users: Vec<User> = pool.filter_entities("users", &criteria).await?;
 //! ```
 
+pub mod aggregate;
+pub mod cursor;
+pub mod entity;
 pub mod error;
+mod eval;
+pub mod memory;
+pub mod odata;
 pub mod query;
+pub mod query_string;
+pub mod relation;
 pub mod repository;
+pub mod rsql;
+pub mod search;
 pub mod transaction;
+pub mod validation;
+#[cfg(feature = "serde")]
+pub mod wire;
 
-#[cfg(feature = "sqlx")]
+#[cfg(any(feature = "sqlx", feature = "axum"))]
 pub mod adapters;
 
+pub use aggregate::{Aggregate, AggregateFunction, AggregateQuery, AggregateRow};
+pub use cursor::{Cursor, CursorDirection, CursorPage, CursorPagination};
+pub use entity::{Column, Entity, FieldValues};
 pub use error::RepositoryError;
+pub use memory::{InMemoryRepository, InMemoryTransaction};
 pub use query::{
-    Condition, ConditionValue, FilterCriteria, Operator, Page, Pagination, SortDirection, SortOrder,
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, Page, Pagination, Projection,
+    SortDirection, SortOrder, ValueKind,
 };
+pub use relation::{Loaded, RelatedRow, Relation, RelationKind};
 pub use repository::{Queryable, Repository};
+pub use search::{SearchMode, TextSearch};
 pub use transaction::Transactional;
+pub use validation::{FieldRule, FilterSchema};
+
+#[cfg(feature = "derive")]
+pub use wyvern_derive::Entity;
 
 #[cfg(feature = "sqlx")]
-pub use adapters::{SqlxAdapter, WyvernSqlxExt};
+pub use adapters::{Identifier, SqlxAdapter, SqlxRepository, WyvernSqlxExt};
+#[cfg(feature = "axum")]
+pub use adapters::{PageResponse, QueryConfig, QueryRejection};
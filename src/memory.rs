@@ -0,0 +1,611 @@
+//! In-memory repository for tests
+//!
+//! [`InMemoryRepository`] keeps entities in a `Vec` and evaluates
+//! `FilterCriteria` in process, following the same semantics as the SQLx
+//! adapter (SQL NULL handling, case-insensitive `Like`, NULL ordering), so
+//! code written against `Repository` and `Queryable` can be unit tested
+//! without a database.
+//!
+//! Entities are stored without their related rows, so conditions and sorts on
+//! the fields of the entity's [`relations`](Entity::relations) are rejected
+//! with `RepositoryError::InvalidInput` rather than silently reading NULL.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use wyvern::{InMemoryRepository, Queryable, Repository};
+//!
+//! let users: InMemoryRepository<User> = InMemoryRepository::new();
+//! users.create(user).await?;
+//! let active = users.filter(criteria).await?;
+//! ```
+
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use async_trait::async_trait;
+
+use crate::entity::Entity;
+use crate::error::RepositoryError;
+use crate::eval;
+use crate::query::{FilterCriteria, Page, Pagination};
+use crate::relation::Relation;
+use crate::repository::{Queryable, Repository};
+use crate::transaction::Transactional;
+
+/// A repository storing entities in memory.
+pub struct InMemoryRepository<T> {
+    entities: RwLock<Vec<T>>,
+}
+
+/// A transaction on an [`InMemoryRepository`].
+///
+/// Holds a snapshot of the repository taken when the transaction began.
+/// Rolling back restores that snapshot, discarding every change made since,
+/// including changes made outside the transaction.
+pub struct InMemoryTransaction<T> {
+    snapshot: Vec<T>,
+}
+
+impl<T> InMemoryRepository<T> {
+    /// Creates an empty repository.
+    pub fn new() -> Self {
+        Self {
+            entities: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of stored entities.
+    pub fn len(&self) -> usize {
+        self.read().map(|e| e.len()).unwrap_or(0)
+    }
+
+    /// Returns whether the repository is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, Vec<T>>, RepositoryError> {
+        self.entities
+            .read()
+            .map_err(|_| RepositoryError::Internal("repository lock poisoned".to_string()))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, Vec<T>>, RepositoryError> {
+        self.entities
+            .write()
+            .map_err(|_| RepositoryError::Internal("repository lock poisoned".to_string()))
+    }
+}
+
+impl<T> Default for InMemoryRepository<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> InMemoryRepository<T>
+where
+    T: Entity + Clone,
+{
+    fn query(&self, criteria: &FilterCriteria) -> Result<Vec<T>, RepositoryError> {
+        Self::check_relations(criteria)?;
+        let (limit, offset) = criteria.bounds()?;
+        let (limit, offset) = (limit.unwrap_or(usize::MAX), offset.unwrap_or(0));
+
+        let mut matching: Vec<T> = self
+            .read()?
+            .iter()
+            .filter(|e| eval::matches(criteria, *e))
+            .cloned()
+            .collect();
+        matching.sort_by(|a, b| eval::sort_ordering(criteria, a, b));
+
+        Ok(matching.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Rejects criteria reading the fields of relations, which are not stored
+    fn check_relations(criteria: &FilterCriteria) -> Result<(), RepositoryError> {
+        let relations = T::relations();
+        let expression = criteria.expression();
+        let fields = expression
+            .conditions()
+            .into_iter()
+            .map(|c| &c.field)
+            .chain(criteria.sort.iter().map(|s| &s.field));
+        for field in fields {
+            if Relation::find(&relations, field).is_some() {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "relation field '{}' is not supported by the in-memory repository",
+                    field
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T> Repository<T> for InMemoryRepository<T>
+where
+    T: Entity + Clone + Send + Sync,
+    T::Id: PartialEq,
+{
+    type Id = T::Id;
+    type Error = RepositoryError;
+
+    async fn create(&self, entity: T) -> Result<T, Self::Error> {
+        let mut entities = self.write()?;
+        let id = entity.id();
+        if entities.iter().any(|e| e.id() == id) {
+            return Err(RepositoryError::ConstraintViolation(format!(
+                "duplicate {} in {}",
+                T::ID_COLUMN,
+                T::TABLE
+            )));
+        }
+        entities.push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn find_by_id(&self, id: Self::Id) -> Result<Option<T>, Self::Error> {
+        Ok(self.read()?.iter().find(|e| e.id() == id).cloned())
+    }
+
+    async fn update(&self, entity: T) -> Result<T, Self::Error> {
+        let mut entities = self.write()?;
+        let id = entity.id();
+        let existing = entities.iter_mut().find(|e| e.id() == id).ok_or_else(|| {
+            RepositoryError::NotFound(format!("no entity to update in {}", T::TABLE))
+        })?;
+        *existing = entity.clone();
+        Ok(entity)
+    }
+
+    async fn delete(&self, id: Self::Id) -> Result<bool, Self::Error> {
+        let mut entities = self.write()?;
+        let before = entities.len();
+        entities.retain(|e| e.id() != id);
+        Ok(entities.len() < before)
+    }
+
+    async fn find_all(&self) -> Result<Vec<T>, Self::Error> {
+        Ok(self.read()?.clone())
+    }
+}
+
+#[async_trait]
+impl<T> Queryable<T> for InMemoryRepository<T>
+where
+    T: Entity + Clone + Send + Sync,
+    T::Id: PartialEq,
+{
+    async fn filter(&self, criteria: FilterCriteria) -> Result<Vec<T>, RepositoryError> {
+        self.query(&criteria)
+    }
+
+    async fn count(&self, criteria: FilterCriteria) -> Result<i64, RepositoryError> {
+        Self::check_relations(&criteria)?;
+        let count = self
+            .read()?
+            .iter()
+            .filter(|e| eval::matches(&criteria, *e))
+            .count();
+        Ok(count as i64)
+    }
+
+    async fn paginate(
+        &self,
+        criteria: FilterCriteria,
+        pagination: Pagination,
+    ) -> Result<Page<T>, RepositoryError> {
+        pagination.validate()?;
+        let total = self.count(criteria.clone()).await?;
+        let items = self.query(&criteria.with_pagination(&pagination))?;
+        Ok(Page::from_pagination(items, &pagination, total))
+    }
+
+    async fn exists(&self, criteria: FilterCriteria) -> Result<bool, RepositoryError> {
+        Self::check_relations(&criteria)?;
+        Ok(self.read()?.iter().any(|e| eval::matches(&criteria, e)))
+    }
+}
+
+#[async_trait]
+impl<T> Transactional for InMemoryRepository<T>
+where
+    T: Clone + Send + Sync,
+{
+    type Transaction = InMemoryTransaction<T>;
+    type Error = RepositoryError;
+
+    async fn begin_transaction(&self) -> Result<Self::Transaction, Self::Error> {
+        Ok(InMemoryTransaction {
+            snapshot: self.read()?.clone(),
+        })
+    }
+
+    async fn commit_transaction(&self, _transaction: Self::Transaction) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn rollback_transaction(
+        &self,
+        transaction: Self::Transaction,
+    ) -> Result<(), Self::Error> {
+        *self.write()? = transaction.snapshot;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::{
+        Aggregate, AggregateQuery, Condition, ConditionValue, Cursor, CursorPage, CursorPagination,
+        FieldValues, FilterExpr, Operator, SortOrder,
+    };
+
+    #[derive(Debug, Clone)]
+    struct User {
+        id: i64,
+        name: String,
+        age: Option<i64>,
+        score: f64,
+        active: bool,
+    }
+
+    impl FieldValues for User {
+        fn field_value(&self, field: &str) -> Option<ConditionValue> {
+            match field {
+                "id" => Some(self.id.into()),
+                "name" => Some(self.name.clone().into()),
+                "age" => Some(self.age.into()),
+                "score" => Some(self.score.into()),
+                "active" => Some(self.active.into()),
+                _ => None,
+            }
+        }
+    }
+
+    impl Entity for User {
+        type Id = i64;
+        const TABLE: &'static str = "users";
+        const ID_COLUMN: &'static str = "id";
+        const COLUMNS: &'static [&'static str] = &["id", "name", "age", "score", "active"];
+
+        fn id(&self) -> i64 {
+            self.id
+        }
+
+        fn relations() -> Vec<Relation> {
+            vec![Relation::many_to_one("team", "teams", "team_id")]
+        }
+    }
+
+    fn user(id: i64, name: &str, age: Option<i64>, score: f64, active: bool) -> User {
+        User {
+            id,
+            name: name.to_string(),
+            age,
+            score,
+            active,
+        }
+    }
+
+    async fn repository() -> InMemoryRepository<User> {
+        let repository = InMemoryRepository::new();
+        for user in [
+            user(1, "Ada", Some(36), 9.5, true),
+            user(2, "Grace", Some(45), 7.0, false),
+            user(3, "Linus", None, 8.25, true),
+            user(4, "Barbara_L", Some(28), 7.0, true),
+        ] {
+            repository.create(user).await.unwrap();
+        }
+        repository
+    }
+
+    async fn ids(repository: &InMemoryRepository<User>, criteria: FilterCriteria) -> Vec<i64> {
+        repository
+            .filter(criteria)
+            .await
+            .unwrap()
+            .iter()
+            .map(|u| u.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_crud() {
+        let repository = repository().await;
+
+        assert!(matches!(
+            repository.create(user(1, "Dup", None, 0.0, false)).await,
+            Err(RepositoryError::ConstraintViolation(_))
+        ));
+
+        let mut ada = repository.find_by_id(1).await.unwrap().unwrap();
+        ada.name = "Ada Lovelace".to_string();
+        repository.update(ada).await.unwrap();
+        assert_eq!(
+            repository.find_by_id(1).await.unwrap().unwrap().name,
+            "Ada Lovelace"
+        );
+
+        assert!(matches!(
+            repository.update(user(9, "Nobody", None, 0.0, false)).await,
+            Err(RepositoryError::NotFound(_))
+        ));
+
+        assert!(repository.delete(2).await.unwrap());
+        assert!(!repository.delete(2).await.unwrap());
+        assert_eq!(repository.find_all().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_comparison_operators() {
+        let repository = repository().await;
+
+        let cases = [
+            (Condition::eq("name", "Ada".into()), vec![1]),
+            (Condition::ne("age", 36.into()), vec![2, 4]),
+            (Condition::gt("score", 7.into()), vec![1, 3]),
+            (
+                Condition::new("age", Operator::GreaterThanOrEqual, 36.into()),
+                vec![1, 2],
+            ),
+            (Condition::lt("age", 36.5.into()), vec![1, 4]),
+            (
+                Condition::new("score", Operator::LessThanOrEqual, 7.0.into()),
+                vec![2, 4],
+            ),
+            (Condition::eq("active", false.into()), vec![2]),
+            (
+                Condition::new("age", Operator::IsNull, ConditionValue::Null),
+                vec![3],
+            ),
+            (
+                Condition::new("age", Operator::IsNotNull, ConditionValue::Null),
+                vec![1, 2, 4],
+            ),
+            (
+                Condition::in_list("name", vec!["Grace".into(), "Linus".into()]),
+                vec![2, 3],
+            ),
+            (Condition::in_list("name", vec![]), vec![]),
+            (Condition::eq("age", ConditionValue::Null), vec![]),
+        ];
+
+        for (condition, expected) in cases {
+            let description = format!("{:?}", condition);
+            let criteria = FilterCriteria::new().with_condition(condition);
+            assert_eq!(
+                ids(&repository, criteria).await,
+                expected,
+                "{}",
+                description
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_like_matches_ilike() {
+        let repository = repository().await;
+
+        let cases = [
+            ("a%", vec![1]),
+            ("%A%", vec![1, 2, 4]),
+            ("_inus", vec![3]),
+            ("%\\_L", vec![4]),
+            ("barbara_l", vec![4]),
+            ("%", vec![1, 2, 3, 4]),
+        ];
+
+        for (pattern, expected) in cases {
+            let criteria = FilterCriteria::new().with_condition(Condition::new(
+                "name",
+                Operator::Like,
+                pattern.into(),
+            ));
+            assert_eq!(ids(&repository, criteria).await, expected, "{}", pattern);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_null_uses_three_valued_logic() {
+        let repository = repository().await;
+
+        // NOT (age > 30) is unknown for Linus, whose age is NULL
+        let criteria = FilterCriteria::new().with_not(Condition::gt("age", 30.into()));
+        assert_eq!(ids(&repository, criteria).await, vec![4]);
+
+        let criteria = FilterCriteria::new().with_any([
+            Condition::gt("age", 40.into()),
+            Condition::eq("name", "Linus".into()),
+        ]);
+        assert_eq!(ids(&repository, criteria).await, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_expressions() {
+        let repository = repository().await;
+
+        let criteria = FilterCriteria::new().with_filter(FilterExpr::or([
+            FilterExpr::from(Condition::eq("name", "Grace".into())),
+            FilterExpr::and([
+                FilterExpr::from(Condition::eq("active", true.into())),
+                FilterExpr::not(Condition::gt("score", 8.into())),
+            ]),
+        ]));
+
+        assert_eq!(ids(&repository, criteria).await, vec![2, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_sort_limit_offset() {
+        let repository = repository().await;
+
+        let criteria = FilterCriteria::new()
+            .with_sort(SortOrder::desc("score"))
+            .with_sort(SortOrder::asc("name"));
+        assert_eq!(ids(&repository, criteria).await, vec![1, 3, 4, 2]);
+
+        // NULLs last ascending, first descending
+        let criteria = FilterCriteria::new().with_sort(SortOrder::asc("age"));
+        assert_eq!(ids(&repository, criteria).await, vec![4, 1, 2, 3]);
+        let criteria = FilterCriteria::new().with_sort(SortOrder::desc("age"));
+        assert_eq!(ids(&repository, criteria).await, vec![3, 2, 1, 4]);
+
+        let criteria = FilterCriteria::new()
+            .with_sort(SortOrder::asc("id"))
+            .with_limit(2)
+            .with_offset(1);
+        assert_eq!(ids(&repository, criteria).await, vec![2, 3]);
+
+        let criteria = FilterCriteria::new().with_limit(-1);
+        assert!(matches!(
+            repository.filter(criteria).await,
+            Err(RepositoryError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_count_paginate_exists() {
+        let repository = repository().await;
+        let active = FilterCriteria::new().with_condition(Condition::eq("active", true.into()));
+
+        assert_eq!(repository.count(active.clone()).await.unwrap(), 3);
+        assert!(repository.exists(active.clone()).await.unwrap());
+        assert!(
+            !repository
+                .exists(FilterCriteria::new().with_condition(Condition::gt("age", 100.into())))
+                .await
+                .unwrap()
+        );
+
+        let page = repository
+            .paginate(
+                active.clone().with_sort(SortOrder::asc("id")),
+                Pagination::new(2, 2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.items.iter().map(|u| u.id).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(page.total_items, 3);
+        assert_eq!(page.total_pages, 2);
+
+        for pagination in [Pagination::new(0, 2), Pagination::new(1, 0)] {
+            assert!(matches!(
+                repository.paginate(active.clone(), pagination).await,
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rollback_and_commit() {
+        let repository = repository().await;
+
+        let transaction = repository.begin_transaction().await.unwrap();
+        repository.delete(1).await.unwrap();
+        repository
+            .create(user(5, "Ken", None, 1.0, true))
+            .await
+            .unwrap();
+        repository.rollback_transaction(transaction).await.unwrap();
+
+        assert!(repository.find_by_id(1).await.unwrap().is_some());
+        assert!(repository.find_by_id(5).await.unwrap().is_none());
+
+        let transaction = repository.begin_transaction().await.unwrap();
+        repository.delete(1).await.unwrap();
+        repository.commit_transaction(transaction).await.unwrap();
+
+        assert!(repository.find_by_id(1).await.unwrap().is_none());
+        assert_eq!(repository.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_relation_fields_are_rejected() {
+        let repository = repository().await;
+        let by_team =
+            FilterCriteria::new().with_condition(Condition::eq("team.name", "core".into()));
+        let sorted = FilterCriteria::new().with_sort(SortOrder::asc("team.name"));
+
+        assert!(matches!(
+            repository.filter(by_team.clone()).await,
+            Err(RepositoryError::InvalidInput(_))
+        ));
+        assert!(repository.count(by_team.clone()).await.is_err());
+        assert!(repository.exists(by_team).await.is_err());
+        assert!(repository.filter(sorted).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate() {
+        let repository = repository().await;
+        let query = AggregateQuery::new(
+            FilterCriteria::new()
+                .with_condition(Condition::ne("name", "Grace".into()))
+                .with_sort(SortOrder::desc("users")),
+        )
+        .with_group_by("active")
+        .with_aggregate(Aggregate::count_all("users"))
+        .with_aggregate(Aggregate::avg("score", "average_score"))
+        .with_aggregate(Aggregate::max("age", "oldest"));
+
+        let rows = repository.aggregate(query.clone()).await.unwrap();
+
+        assert_eq!(
+            rows,
+            vec![HashMap::from([
+                ("active".to_string(), true.into()),
+                ("users".to_string(), 3.into()),
+                ("average_score".to_string(), 8.25.into()),
+                ("oldest".to_string(), 36.into()),
+            ])]
+        );
+
+        let rows = repository
+            .aggregate(query.with_having(Condition::gt("users", 3.into())))
+            .await
+            .unwrap();
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_by_cursor_walks_both_ways() {
+        let repository = repository().await;
+        let criteria = FilterCriteria::new()
+            .with_sort(SortOrder::desc("score"))
+            .with_sort(SortOrder::asc("id"));
+        let page_ids =
+            |page: &CursorPage<User>| page.items.iter().map(|u| u.id).collect::<Vec<_>>();
+
+        let first = repository
+            .paginate_by_cursor(criteria.clone(), CursorPagination::new(2))
+            .await
+            .unwrap();
+        assert_eq!(page_ids(&first), vec![1, 3]);
+        assert!(!first.has_previous());
+
+        let next: Cursor = first.next_cursor.as_deref().unwrap().parse().unwrap();
+        let second = repository
+            .paginate_by_cursor(criteria.clone(), CursorPagination::new(2).with_cursor(next))
+            .await
+            .unwrap();
+        assert_eq!(page_ids(&second), vec![2, 4]);
+        assert!(!second.has_next());
+
+        let prev: Cursor = second.prev_cursor.as_deref().unwrap().parse().unwrap();
+        let back = repository
+            .paginate_by_cursor(criteria, CursorPagination::new(2).with_cursor(prev))
+            .await
+            .unwrap();
+        assert_eq!(page_ids(&back), vec![1, 3]);
+        assert!(!back.has_previous());
+        assert_eq!(back.next_cursor, first.next_cursor);
+    }
+}
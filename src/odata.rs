@@ -0,0 +1,607 @@
+//! OData v4 system query options
+//!
+//! Parses `$filter`, `$orderby`, `$select`, `$top` and `$skip` into
+//! `FilterCriteria`. `$select` takes a comma-separated list of properties.
+//!
+//! # Supported syntax
+//!
+//! - Comparisons `eq`, `ne`, `gt`, `ge`, `lt` and `le` between a property and
+//!   a literal. `eq null` and `ne null` become `IsNull` and `IsNotNull`.
+//! - `in` with a parenthesized list: `role in ('admin', 'owner')`.
+//! - `and`, `or`, `not` and parentheses, with the usual precedence.
+//! - The functions `contains`, `startswith` and `endswith`, which become
+//!   `Contains`, `StartsWith` and `EndsWith` conditions and so match
+//!   case-insensitively.
+//! - Literals: `'strings'` (with `''` escaping a quote), integers, decimals,
+//!   `true`, `false` and `null`. Numbers that would not read back unchanged,
+//!   such as `02134` or `9.50`, and other unquoted literals, such as dates, are
+//!   passed through as strings. [`FilterSchema::coerce`] retypes values by
+//!   their declared field kinds.
+//!
+//! Other functions, operators and query options are reported as
+//! `RepositoryError::InvalidInput`. Errors in `$filter` include the position
+//! of the problem.
+//!
+//! ```rust
+//! use wyvern::odata;
+//!
+//! let criteria = odata::parse(
+//!     "$filter=status eq 'active' and (age gt 18 or contains(name, 'ada'))&$orderby=name desc&$top=20",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(criteria.conditions.len(), 1);
+//! assert_eq!(criteria.filters.len(), 1);
+//! assert_eq!(criteria.limit, Some(20));
+//! ```
+
+use crate::error::RepositoryError;
+use crate::query::{
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, Projection, SortDirection,
+    SortOrder,
+};
+use crate::query_string::parse_value;
+#[cfg(doc)]
+use crate::validation::FilterSchema;
+
+const FILTER_OPTION: &str = "$filter";
+const ORDERBY_OPTION: &str = "$orderby";
+const SELECT_OPTION: &str = "$select";
+const TOP_OPTION: &str = "$top";
+const SKIP_OPTION: &str = "$skip";
+
+/// Parses OData system query options from a URL query string.
+///
+/// A leading `?` is ignored and keys and values are percent-decoded.
+/// Parameters without a `$` prefix are left to the caller and ignored.
+pub fn parse(query: &str) -> Result<FilterCriteria, RepositoryError> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut criteria = FilterCriteria::new();
+    let mut seen = Vec::new();
+
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        if !key.starts_with('$') {
+            continue;
+        }
+        if seen.contains(&key) {
+            return Err(RepositoryError::InvalidInput(format!(
+                "query option '{}' is given more than once",
+                key
+            )));
+        }
+
+        match key.as_ref() {
+            FILTER_OPTION => {
+                let filter = FilterCriteria::from(parse_filter(&value)?);
+                criteria.conditions = filter.conditions;
+                criteria.filters = filter.filters;
+            }
+            ORDERBY_OPTION => criteria.sort = parse_orderby(&value)?,
+            SELECT_OPTION => criteria.projection = parse_select(&value)?,
+            TOP_OPTION => criteria.limit = Some(parse_non_negative(TOP_OPTION, &value)?),
+            SKIP_OPTION => criteria.offset = Some(parse_non_negative(SKIP_OPTION, &value)?),
+            _ => {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "unsupported query option '{}'",
+                    key
+                )));
+            }
+        }
+        seen.push(key);
+    }
+
+    Ok(criteria)
+}
+
+/// Parses a `$filter` expression.
+pub fn parse_filter(filter: &str) -> Result<FilterExpr, RepositoryError> {
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        pos: 0,
+        end: filter.chars().count(),
+    };
+
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(error_at(token.pos, format!("unexpected {}", token.kind)));
+    }
+    Ok(expr)
+}
+
+/// Parses an `$orderby` list such as `name desc, created_at`.
+pub fn parse_orderby(orderby: &str) -> Result<Vec<SortOrder>, RepositoryError> {
+    orderby
+        .split(',')
+        .map(|item| {
+            let mut words = item.split_whitespace();
+            let field = match words.next() {
+                Some(field) if is_identifier(field) => field,
+                _ => {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "malformed $orderby item '{}'",
+                        item.trim()
+                    )));
+                }
+            };
+            let direction = match words.next() {
+                None | Some("asc") => SortDirection::Ascending,
+                Some("desc") => SortDirection::Descending,
+                Some(other) => {
+                    return Err(RepositoryError::InvalidInput(format!(
+                        "unknown sort direction '{}' for '{}'",
+                        other, field
+                    )));
+                }
+            };
+            if let Some(extra) = words.next() {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "unexpected '{}' in $orderby item '{}'",
+                    extra,
+                    item.trim()
+                )));
+            }
+            Ok(SortOrder::new(field, direction))
+        })
+        .collect()
+}
+
+/// Parses a `$select` list such as `id, name`.
+pub fn parse_select(select: &str) -> Result<Vec<Projection>, RepositoryError> {
+    select
+        .split(',')
+        .map(|item| match item.trim() {
+            field if is_identifier(field) => Ok(Projection::new(field)),
+            _ => Err(RepositoryError::InvalidInput(format!(
+                "malformed $select item '{}'",
+                item.trim()
+            ))),
+        })
+        .collect()
+}
+
+fn parse_non_negative(option: &str, value: &str) -> Result<i64, RepositoryError> {
+    match value.parse::<i64>() {
+        Ok(n) if n >= 0 => Ok(n),
+        _ => Err(RepositoryError::InvalidInput(format!(
+            "'{}' must be a non-negative integer, got '{}'",
+            option, value
+        ))),
+    }
+}
+
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn error_at(pos: usize, message: String) -> RepositoryError {
+    RepositoryError::InvalidInput(format!("{} at position {}", message, pos))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    String(String),
+    Number(String),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "'{}'", word),
+            TokenKind::String(s) => write!(f, "string '{}'", s),
+            TokenKind::Number(n) => write!(f, "'{}'", n),
+            TokenKind::OpenParen => write!(f, "'('"),
+            TokenKind::CloseParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    pos: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, RepositoryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                TokenKind::OpenParen
+            }
+            ')' => {
+                i += 1;
+                TokenKind::CloseParen
+            }
+            ',' => {
+                i += 1;
+                TokenKind::Comma
+            }
+            '\'' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error_at(start, "unterminated string".to_string())),
+                        Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                            value.push('\'');
+                            i += 2;
+                        }
+                        Some('\'') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&c) => {
+                            value.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::String(value)
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                i += 1;
+                while chars.get(i).is_some_and(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '.' | ':' | '-' | '+')
+                }) {
+                    i += 1;
+                }
+                TokenKind::Number(chars[start..i].iter().collect())
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while chars
+                    .get(i)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    i += 1;
+                }
+                TokenKind::Word(chars[start..i].iter().collect())
+            }
+            c => return Err(error_at(start, format!("unexpected character '{}'", c))),
+        };
+        tokens.push(Token { kind, pos: start });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn parse_or(&mut self) -> Result<FilterExpr, RepositoryError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat_word("or") {
+            exprs.push(self.parse_and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            FilterExpr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, RepositoryError> {
+        let mut exprs = vec![self.parse_unary()?];
+        while self.eat_word("and") {
+            exprs.push(self.parse_unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            FilterExpr::And(exprs)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, RepositoryError> {
+        if self.eat_word("not") {
+            return Ok(FilterExpr::not(self.parse_unary()?));
+        }
+
+        if self.eat(&TokenKind::OpenParen) {
+            let expr = self.parse_or()?;
+            self.expect(TokenKind::CloseParen)?;
+            return Ok(expr);
+        }
+
+        let (name, pos) = self.expect_identifier("a property or function")?;
+        if self.eat(&TokenKind::OpenParen) {
+            return self.parse_function(name, pos);
+        }
+        self.parse_comparison(name)
+    }
+
+    fn parse_function(&mut self, name: String, pos: usize) -> Result<FilterExpr, RepositoryError> {
+        let operator = match name.as_str() {
+            "contains" => Operator::Contains,
+            "startswith" => Operator::StartsWith,
+            "endswith" => Operator::EndsWith,
+            _ => return Err(error_at(pos, format!("unsupported function '{}'", name))),
+        };
+
+        let (field, _) = self.expect_identifier("a property")?;
+        self.expect(TokenKind::Comma)?;
+        let value = match self.next() {
+            Some(Token {
+                kind: TokenKind::String(value),
+                ..
+            }) => value,
+            other => {
+                return Err(self.unexpected(
+                    other,
+                    format!("function '{}' expects a string literal", name),
+                ));
+            }
+        };
+        self.expect(TokenKind::CloseParen)?;
+
+        Ok(Condition::new(field, operator, value.into()).into())
+    }
+
+    fn parse_comparison(&mut self, field: String) -> Result<FilterExpr, RepositoryError> {
+        let (operator, pos) = self.expect_identifier("a comparison operator")?;
+
+        let operator = match operator.as_str() {
+            "eq" => Operator::Equal,
+            "ne" => Operator::NotEqual,
+            "gt" => Operator::GreaterThan,
+            "ge" => Operator::GreaterThanOrEqual,
+            "lt" => Operator::LessThan,
+            "le" => Operator::LessThanOrEqual,
+            "in" => {
+                self.expect(TokenKind::OpenParen)?;
+                let mut values = vec![self.parse_literal()?];
+                while self.eat(&TokenKind::Comma) {
+                    values.push(self.parse_literal()?);
+                }
+                self.expect(TokenKind::CloseParen)?;
+                return Ok(Condition::in_list(field, values).into());
+            }
+            _ => {
+                return Err(error_at(
+                    pos,
+                    format!("unsupported operator '{}'", operator),
+                ));
+            }
+        };
+
+        let value = self.parse_literal()?;
+        let condition = match (operator, value) {
+            (Operator::Equal, ConditionValue::Null) => {
+                Condition::new(field, Operator::IsNull, ConditionValue::Null)
+            }
+            (Operator::NotEqual, ConditionValue::Null) => {
+                Condition::new(field, Operator::IsNotNull, ConditionValue::Null)
+            }
+            (operator, value) => Condition::new(field, operator, value),
+        };
+        Ok(condition.into())
+    }
+
+    fn parse_literal(&mut self) -> Result<ConditionValue, RepositoryError> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::String(s),
+                ..
+            }) => Ok(ConditionValue::String(s)),
+            Some(Token {
+                kind: TokenKind::Number(n),
+                ..
+            }) => Ok(parse_value(&n)),
+            Some(Token {
+                kind: TokenKind::Word(word),
+                pos,
+            }) => match word.as_str() {
+                "true" => Ok(ConditionValue::Boolean(true)),
+                "false" => Ok(ConditionValue::Boolean(false)),
+                "null" => Ok(ConditionValue::Null),
+                _ => Err(error_at(
+                    pos,
+                    format!("expected a literal, found '{}'", word),
+                )),
+            },
+            other => Err(self.unexpected(other, "expected a literal".to_string())),
+        }
+    }
+
+    fn expect_identifier(&mut self, what: &str) -> Result<(String, usize), RepositoryError> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::Word(word),
+                pos,
+            }) => Ok((word, pos)),
+            other => Err(self.unexpected(other, format!("expected {}", what))),
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), RepositoryError> {
+        if self.eat(&kind) {
+            return Ok(());
+        }
+        let next = self.next();
+        Err(self.unexpected(next, format!("expected {}", kind)))
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().is_some_and(|token| &token.kind == kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        self.eat(&TokenKind::Word(word.to_string()))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(Token {
+            kind: token.kind.clone(),
+            pos: token.pos,
+        })
+    }
+
+    fn unexpected(&self, token: Option<Token>, message: String) -> RepositoryError {
+        match token {
+            Some(token) => error_at(token.pos, format!("{}, found {}", message, token.kind)),
+            None => error_at(self.end, format!("{}, found end of input", message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_options() {
+        let criteria = parse(
+            "?$filter=status%20eq%20'active'%20and%20age%20ge%2018&$orderby=name desc,id&$select=id, name&$top=10&$skip=20&format=json",
+        )
+        .unwrap();
+
+        assert_eq!(
+            criteria,
+            FilterCriteria::new()
+                .with_condition(Condition::eq("status", "active".into()))
+                .with_condition(Condition::new(
+                    "age",
+                    Operator::GreaterThanOrEqual,
+                    18.into()
+                ))
+                .with_sort(SortOrder::desc("name"))
+                .with_sort(SortOrder::asc("id"))
+                .with_columns(["id", "name"])
+                .with_limit(10)
+                .with_offset(20)
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_precedence() {
+        let expr =
+            parse_filter("not deleted eq true and (role in ('admin', 'owner') or score lt -1.5)")
+                .unwrap();
+
+        assert_eq!(
+            expr,
+            FilterExpr::and([
+                FilterExpr::not(Condition::eq("deleted", true.into())),
+                FilterExpr::or([
+                    Condition::in_list("role", vec!["admin".into(), "owner".into()]),
+                    Condition::lt("score", (-1.5).into()),
+                ]),
+            ])
+        );
+
+        assert_eq!(
+            parse_filter("a eq 1 or b eq 2 and c eq 3").unwrap(),
+            FilterExpr::or([
+                FilterExpr::from(Condition::eq("a", 1.into())),
+                FilterExpr::and([Condition::eq("b", 2.into()), Condition::eq("c", 3.into())]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_functions_and_literals() {
+        let expr = parse_filter(
+            "contains(name, '50%_off') and startswith(code, 'A') and endswith(email, '.org') \
+             and note eq 'it''s' and deleted_at eq null and email ne null and born lt 2000-01-01",
+        )
+        .unwrap();
+
+        let conditions: Vec<(Operator, ConditionValue)> = expr
+            .conditions()
+            .into_iter()
+            .map(|c| (c.operator, c.value.clone()))
+            .collect();
+        assert_eq!(
+            conditions,
+            vec![
+                (Operator::Contains, "50%_off".into()),
+                (Operator::StartsWith, "A".into()),
+                (Operator::EndsWith, ".org".into()),
+                (Operator::Equal, "it's".into()),
+                (Operator::IsNull, ConditionValue::Null),
+                (Operator::IsNotNull, ConditionValue::Null),
+                (Operator::LessThan, "2000-01-01".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_errors_report_positions() {
+        let cases = [
+            (
+                "tolower(name) eq 'ada'",
+                "unsupported function 'tolower' at position 0",
+            ),
+            ("age has 1", "unsupported operator 'has' at position 4"),
+            (
+                "status eq active",
+                "expected a literal, found 'active' at position 10",
+            ),
+            ("name eq 'ada", "unterminated string at position 8"),
+            ("(a eq 1", "expected ')', found end of input at position 7"),
+            ("a eq 1)", "unexpected ')' at position 6"),
+            (
+                "a eq 1 and",
+                "expected a property or function, found end of input at position 10",
+            ),
+            (
+                "contains(name, 1)",
+                "function 'contains' expects a string literal, found '1' at position 15",
+            ),
+            ("a eq 1 & b eq 2", "unexpected character '&' at position 7"),
+        ];
+
+        for (input, expected) in cases {
+            match parse_filter(input) {
+                Err(RepositoryError::InvalidInput(message)) => {
+                    assert_eq!(message, expected, "{}", input)
+                }
+                other => panic!("expected an error for '{}', got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_query_options() {
+        assert!(parse("$top=-1").is_err());
+        assert!(parse("$skip=x").is_err());
+        assert!(parse("$expand=orders").is_err());
+        assert!(parse("$select=name,,id").is_err());
+        assert!(parse("$top=1&$top=2").is_err());
+        assert!(parse("$orderby=name sideways").is_err());
+        assert!(parse("$orderby=name,,id").is_err());
+        assert_eq!(parse("").unwrap(), FilterCriteria::new());
+    }
+}
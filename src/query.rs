@@ -1,16 +1,45 @@
 //! Query filtering, sorting, and pagination types
 
+use crate::entity::FieldValues;
+use crate::error::RepositoryError;
+use crate::eval;
+use crate::search::TextSearch;
+
 /// Filter criteria for querying entities.
-#[derive(Debug, Clone, Default)]
+///
+/// With the `serde` feature this serializes to the versioned JSON format
+/// documented in the `wire` module.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::wire::FilterCriteriaWire",
+        try_from = "crate::wire::FilterCriteriaWire"
+    )
+)]
 pub struct FilterCriteria {
     /// Field-value conditions to match
     pub conditions: Vec<Condition>,
+    /// Boolean filter expressions, combined with `conditions` using AND
+    pub filters: Vec<FilterExpr>,
     /// Sort order for results
     pub sort: Vec<SortOrder>,
     /// Optional limit on number of results
     pub limit: Option<i64>,
     /// Optional offset for pagination
     pub offset: Option<i64>,
+    /// Columns to select, or every column when empty
+    ///
+    /// Only queries reading rows into a type of the caller's choosing apply
+    /// the projection (`SqlxRepository::filter_as` and `filter_entities`);
+    /// queries reading entities always select whole entities.
+    pub projection: Vec<Projection>,
+    /// Names of relations whose related rows are loaded with the results
+    ///
+    /// Only `SqlxRepository::filter_loaded` loads them; filtering, counting
+    /// and in-memory evaluation ignore this list.
+    pub include: Vec<String>,
 }
 
 impl FilterCriteria {
@@ -25,6 +54,69 @@ impl FilterCriteria {
         self
     }
 
+    /// Adds a boolean expression to the filter.
+    ///
+    /// The expression is combined with the other conditions and filters using AND.
+    pub fn with_filter(mut self, filter: impl Into<FilterExpr>) -> Self {
+        self.filters.push(filter.into());
+        self
+    }
+
+    /// Adds a group of expressions of which at least one must match.
+    pub fn with_any<I, E>(self, filters: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<FilterExpr>,
+    {
+        self.with_filter(FilterExpr::or(filters))
+    }
+
+    /// Adds an expression that must not match.
+    pub fn with_not(self, filter: impl Into<FilterExpr>) -> Self {
+        self.with_filter(FilterExpr::not(filter))
+    }
+
+    /// Returns the whole filter as a single expression.
+    ///
+    /// Conditions come first, followed by filters, all combined with AND.
+    /// An empty criteria yields an empty `And`, which matches everything.
+    pub fn expression(&self) -> FilterExpr {
+        FilterExpr::And(
+            self.conditions
+                .iter()
+                .cloned()
+                .map(FilterExpr::Leaf)
+                .chain(self.filters.iter().cloned())
+                .collect(),
+        )
+    }
+
+    /// Returns whether an entity satisfies the conditions and filters.
+    ///
+    /// Evaluation follows SQL semantics, so the result agrees with what
+    /// `Queryable::filter` returns for the same criteria: comparisons with NULL
+    /// are unknown and never match, `Like` is case-insensitive, and fields the
+    /// entity does not expose are treated as NULL. Sorting, limit and offset
+    /// are ignored.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use wyvern::{Condition, ConditionValue, FilterCriteria};
+    ///
+    /// let criteria = FilterCriteria::new()
+    ///     .with_condition(Condition::eq("status", "active".into()))
+    ///     .with_condition(Condition::gt("age", 18.into()));
+    ///
+    /// let event = HashMap::from([
+    ///     ("status".to_string(), ConditionValue::from("active")),
+    ///     ("age".to_string(), ConditionValue::from(30)),
+    /// ]);
+    /// assert!(criteria.matches(&event));
+    /// ```
+    pub fn matches<T: FieldValues + ?Sized>(&self, entity: &T) -> bool {
+        eval::matches(self, entity)
+    }
+
     /// Adds a sort order to the filter.
     pub fn with_sort(mut self, sort: SortOrder) -> Self {
         self.sort.push(sort);
@@ -42,16 +134,168 @@ impl FilterCriteria {
         self.offset = Some(offset);
         self
     }
+
+    /// Adds a column to the projection.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection.push(projection);
+        self
+    }
+
+    /// Adds columns to the projection, selected under their own names.
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.projection
+            .extend(columns.into_iter().map(Projection::new));
+        self
+    }
+
+    /// Loads the rows of the named relation together with the results.
+    pub fn with_include(mut self, relation: impl Into<String>) -> Self {
+        self.include.push(relation.into());
+        self
+    }
+
+    /// Sets the limit and offset selecting the page described by `pagination`.
+    pub fn with_pagination(self, pagination: &Pagination) -> Self {
+        self.with_limit(pagination.limit())
+            .with_offset(pagination.offset())
+    }
+
+    /// Returns the limit and offset as counts, rejecting negative values so
+    /// every backend fails the same way.
+    pub(crate) fn bounds(&self) -> Result<(Option<usize>, Option<usize>), RepositoryError> {
+        let bound = |name: &str, value: Option<i64>| {
+            value
+                .map(|v| {
+                    usize::try_from(v).map_err(|_| {
+                        RepositoryError::InvalidInput(format!("{} must not be negative", name))
+                    })
+                })
+                .transpose()
+        };
+        Ok((bound("limit", self.limit)?, bound("offset", self.offset)?))
+    }
+}
+
+impl From<FilterExpr> for FilterCriteria {
+    /// Builds criteria from an expression, splitting a top-level `And` so
+    /// that its plain conditions land in `conditions` and everything else in
+    /// `filters`.
+    fn from(expr: FilterExpr) -> Self {
+        let exprs = match expr {
+            FilterExpr::And(exprs) => exprs,
+            expr => vec![expr],
+        };
+        exprs
+            .into_iter()
+            .fold(FilterCriteria::new(), |criteria, expr| match expr {
+                FilterExpr::Leaf(condition) => criteria.with_condition(condition),
+                expr => criteria.with_filter(expr),
+            })
+    }
+}
+
+/// A boolean expression over filter conditions.
+///
+/// An empty `And` matches everything and an empty `Or` matches nothing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum FilterExpr {
+    /// All sub-expressions must match
+    And(Vec<FilterExpr>),
+    /// At least one sub-expression must match
+    Or(Vec<FilterExpr>),
+    /// The sub-expression must not match
+    Not(Box<FilterExpr>),
+    /// A single condition
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Leaf(Condition),
+}
+
+impl FilterExpr {
+    /// Creates an expression matching when all sub-expressions match.
+    pub fn and<I, E>(exprs: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<FilterExpr>,
+    {
+        Self::And(exprs.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates an expression matching when at least one sub-expression matches.
+    pub fn or<I, E>(exprs: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<FilterExpr>,
+    {
+        Self::Or(exprs.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates an expression matching when the sub-expression does not.
+    pub fn not(expr: impl Into<FilterExpr>) -> Self {
+        Self::Not(Box::new(expr.into()))
+    }
+
+    /// Returns whether an entity satisfies this expression.
+    ///
+    /// See [`FilterCriteria::matches`] for the evaluation rules.
+    pub fn matches<T: FieldValues + ?Sized>(&self, entity: &T) -> bool {
+        eval::evaluate(self, entity) == Some(true)
+    }
+
+    /// Returns every condition in this expression, depth first.
+    pub fn conditions(&self) -> Vec<&Condition> {
+        let mut conditions = Vec::new();
+        self.collect_conditions(&mut conditions);
+        conditions
+    }
+
+    fn collect_conditions<'a>(&'a self, out: &mut Vec<&'a Condition>) {
+        match self {
+            Self::And(exprs) | Self::Or(exprs) => {
+                exprs.iter().for_each(|e| e.collect_conditions(out))
+            }
+            Self::Not(expr) => expr.collect_conditions(out),
+            Self::Leaf(condition) => out.push(condition),
+        }
+    }
+}
+
+impl From<Condition> for FilterExpr {
+    fn from(condition: Condition) -> Self {
+        FilterExpr::Leaf(condition)
+    }
 }
 
 /// A single filter condition.
-#[derive(Debug, Clone)]
+///
+/// The field may address a path inside a JSON column: `metadata->plan`
+/// selects the `plan` key of `metadata` as JSON, and a final `->>` selects it
+/// as text, as in `metadata->plan->>tier`. Keys are object keys, even when
+/// they look like numbers (`metadata->2024`); an integer in brackets indexes
+/// into an array, as in `metadata->tags->>[0]`, counting from the end when
+/// negative.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct Condition {
     /// The field name to filter on
     pub field: String,
     /// The operator to apply
+    #[cfg_attr(feature = "serde", serde(rename = "op"))]
     pub operator: Operator,
     /// The value to compare against
+    #[cfg_attr(feature = "serde", serde(default))]
     pub value: ConditionValue,
 }
 
@@ -65,6 +309,13 @@ impl Condition {
         }
     }
 
+    /// Returns whether an entity satisfies this condition.
+    ///
+    /// See [`FilterCriteria::matches`] for the evaluation rules.
+    pub fn matches<T: FieldValues + ?Sized>(&self, entity: &T) -> bool {
+        eval::evaluate_condition(self, entity) == Some(true)
+    }
+
     /// Creates an equality condition.
     pub fn eq(field: impl Into<String>, value: ConditionValue) -> Self {
         Self::new(field, Operator::Equal, value)
@@ -89,32 +340,467 @@ impl Condition {
     pub fn in_list(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
         Self::new(field, Operator::In, ConditionValue::List(values))
     }
+
+    /// Creates a NOT IN condition.
+    pub fn not_in(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
+        Self::new(field, Operator::NotIn, ConditionValue::List(values))
+    }
+
+    /// Creates an inclusive BETWEEN condition.
+    pub fn between(field: impl Into<String>, low: ConditionValue, high: ConditionValue) -> Self {
+        Self::new(
+            field,
+            Operator::Between,
+            ConditionValue::List(vec![low, high]),
+        )
+    }
+
+    /// Creates a case-insensitive LIKE condition.
+    pub fn like(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::Like,
+            ConditionValue::String(pattern.into()),
+        )
+    }
+
+    /// Creates a case-insensitive NOT LIKE condition.
+    pub fn not_like(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::NotLike,
+            ConditionValue::String(pattern.into()),
+        )
+    }
+
+    /// Creates a case-sensitive LIKE condition.
+    pub fn case_sensitive_like(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::CaseSensitiveLike,
+            ConditionValue::String(pattern.into()),
+        )
+    }
+
+    /// Creates a condition matching values that start with `prefix`.
+    pub fn starts_with(field: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::StartsWith,
+            ConditionValue::String(prefix.into()),
+        )
+    }
+
+    /// Creates a condition matching values that end with `suffix`.
+    pub fn ends_with(field: impl Into<String>, suffix: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::EndsWith,
+            ConditionValue::String(suffix.into()),
+        )
+    }
+
+    /// Creates a condition matching values that contain `text`.
+    pub fn contains(field: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::Contains,
+            ConditionValue::String(text.into()),
+        )
+    }
+
+    /// Creates a JSON containment (`@>`) condition.
+    ///
+    /// The value is a `Json` value or a string holding a JSON document.
+    pub fn json_contains(field: impl Into<String>, value: ConditionValue) -> Self {
+        Self::new(field, Operator::JsonContains, value)
+    }
+
+    /// Creates a condition matching JSON values that have the top-level key (`?`).
+    pub fn json_has_key(field: impl Into<String>, key: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::JsonHasKey,
+            ConditionValue::String(key.into()),
+        )
+    }
+
+    /// Creates a condition matching JSON values that have any of the keys (`?|`).
+    pub fn json_has_any_key<I, K>(field: impl Into<String>, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        let keys = keys.into_iter().map(|k| ConditionValue::String(k.into()));
+        Self::new(
+            field,
+            Operator::JsonHasAnyKey,
+            ConditionValue::List(keys.collect()),
+        )
+    }
+
+    /// Creates a condition matching JSON values that have all of the keys (`?&`).
+    pub fn json_has_all_keys<I, K>(field: impl Into<String>, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        let keys = keys.into_iter().map(|k| ConditionValue::String(k.into()));
+        Self::new(
+            field,
+            Operator::JsonHasAllKeys,
+            ConditionValue::List(keys.collect()),
+        )
+    }
+
+    /// Creates a condition matching JSON values for which the jsonpath returns
+    /// any item (`@?`).
+    pub fn json_path_exists(field: impl Into<String>, path: impl Into<String>) -> Self {
+        Self::new(
+            field,
+            Operator::JsonPathExists,
+            ConditionValue::String(path.into()),
+        )
+    }
+
+    /// Creates a condition matching array columns sharing an element with
+    /// `values` (`&&`).
+    pub fn array_overlaps(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
+        Self::new(field, Operator::ArrayOverlaps, ConditionValue::List(values))
+    }
+
+    /// Creates a condition matching array columns holding every element of
+    /// `values` (`@>`).
+    pub fn array_contains(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
+        Self::new(field, Operator::ArrayContains, ConditionValue::List(values))
+    }
+
+    /// Creates a condition matching array columns whose elements are all in
+    /// `values` (`<@`).
+    pub fn array_contained_by(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
+        Self::new(
+            field,
+            Operator::ArrayContainedBy,
+            ConditionValue::List(values),
+        )
+    }
+
+    /// Creates an `= ANY(...)` condition, binding `values` as one array.
+    pub fn equals_any(field: impl Into<String>, values: Vec<ConditionValue>) -> Self {
+        Self::new(field, Operator::EqualsAny, ConditionValue::List(values))
+    }
+
+    /// Creates a full-text search condition.
+    pub fn text_search(field: impl Into<String>, search: TextSearch) -> Self {
+        Self::new(
+            field,
+            Operator::TextSearch,
+            ConditionValue::TextSearch(search),
+        )
+    }
+}
+
+/// A condition field split into its column and JSON path.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldPath<'a> {
+    pub(crate) column: &'a str,
+    pub(crate) keys: Vec<&'a str>,
+    /// Whether the last key is selected as text (`->>`)
+    pub(crate) as_text: bool,
+}
+
+impl<'a> FieldPath<'a> {
+    /// Splits `column->key->>key` into its parts.
+    pub(crate) fn parse(field: &'a str) -> Result<Self, RepositoryError> {
+        let mut parts = field.split("->");
+        let column = parts.next().unwrap_or_default();
+        let mut keys = Vec::new();
+        let mut as_text = false;
+
+        for part in parts {
+            if as_text {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "'->>' must be the last step of '{}'",
+                    field
+                )));
+            }
+            let key = match part.strip_prefix('>') {
+                Some(key) => {
+                    as_text = true;
+                    key
+                }
+                None => part,
+            };
+            if key.is_empty() {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "empty JSON key in '{}'",
+                    field
+                )));
+            }
+            if key.starts_with('[') && Self::index(key).is_none() {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "invalid array index '{}' in '{}'",
+                    key, field
+                )));
+            }
+            keys.push(key);
+        }
+
+        Ok(Self {
+            column,
+            keys,
+            as_text,
+        })
+    }
+
+    /// Returns the array index a key stands for, if it is a bracketed
+    /// integer such as `[0]`.
+    pub(crate) fn index(key: &str) -> Option<i32> {
+        key.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+    }
 }
 
 /// Comparison operators for filter conditions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Operator {
+    #[cfg_attr(feature = "serde", serde(rename = "eq"))]
     Equal,
+    #[cfg_attr(feature = "serde", serde(rename = "ne"))]
     NotEqual,
+    #[cfg_attr(feature = "serde", serde(rename = "gt"))]
     GreaterThan,
+    #[cfg_attr(feature = "serde", serde(rename = "gte"))]
     GreaterThanOrEqual,
+    #[cfg_attr(feature = "serde", serde(rename = "lt"))]
     LessThan,
+    #[cfg_attr(feature = "serde", serde(rename = "lte"))]
     LessThanOrEqual,
+    /// Case-insensitive pattern match (`ILIKE`)
     Like,
+    /// Negated case-insensitive pattern match (`NOT ILIKE`)
+    NotLike,
+    /// Case-sensitive pattern match (`LIKE`)
+    CaseSensitiveLike,
     In,
+    NotIn,
+    /// Inclusive range; the value is a list of the lower and upper bound
+    Between,
+    /// Case-insensitive prefix match; `%` and `_` in the value match literally
+    StartsWith,
+    /// Case-insensitive suffix match; `%` and `_` in the value match literally
+    EndsWith,
+    /// Case-insensitive substring match; `%` and `_` in the value match literally
+    Contains,
+    /// JSON containment (`@>`)
+    JsonContains,
+    /// JSON top-level key existence (`?`)
+    JsonHasKey,
+    /// JSON existence of any of a list of keys (`?|`)
+    JsonHasAnyKey,
+    /// JSON existence of all of a list of keys (`?&`)
+    JsonHasAllKeys,
+    /// jsonpath predicate (`@?`); not evaluated in process
+    JsonPathExists,
+    /// Array overlap (`&&`): the array shares an element with the list
+    ArrayOverlaps,
+    /// Array containment (`@>`): the array holds every element of the list
+    ArrayContains,
+    /// Array containment (`<@`): every element of the array is in the list
+    ArrayContainedBy,
+    /// `= ANY(...)`: like `In`, with the list bound as a single array
+    EqualsAny,
+    /// Full-text search (`@@`); the value is a `TextSearch`
+    TextSearch,
     IsNull,
     IsNotNull,
 }
 
 /// Values used in filter conditions.
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature values map to plain JSON values: strings, numbers,
+/// booleans, arrays and `null`. UUIDs, dates, times and decimals serialize as
+/// strings, bytes as an array of numbers, and JSON values as themselves;
+/// deserializing always yields the plain variants, except that objects with
+/// a single `search` key holding a search (`{"search": {"query": ...,
+/// "mode": ...}}`) become `TextSearch` values and other JSON objects become
+/// `Json` values.
+///
+/// The variants beyond the plain JSON ones are enabled by the `uuid`,
+/// `chrono`, `time`, `rust_decimal` and `json` features, and are bound with
+/// their native Postgres types by the SQLx adapter.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
 pub enum ConditionValue {
     String(String),
     Integer(i64),
     Float(f64),
     Boolean(bool),
     List(Vec<ConditionValue>),
+    #[default]
     Null,
+    /// A `bytea` value.
+    Bytes(Vec<u8>),
+    /// A `uuid` value.
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    /// A `date` value.
+    #[cfg(feature = "chrono")]
+    NaiveDate(chrono::NaiveDate),
+    /// A `time` value.
+    #[cfg(feature = "chrono")]
+    NaiveTime(chrono::NaiveTime),
+    /// A `timestamp` value.
+    #[cfg(feature = "chrono")]
+    NaiveDateTime(chrono::NaiveDateTime),
+    /// A `timestamptz` value.
+    #[cfg(feature = "chrono")]
+    DateTime(chrono::DateTime<chrono::Utc>),
+    /// A `date` value.
+    #[cfg(feature = "time")]
+    Date(time::Date),
+    /// A `time` value.
+    #[cfg(feature = "time")]
+    Time(time::Time),
+    /// A `timestamp` value.
+    #[cfg(feature = "time")]
+    PrimitiveDateTime(time::PrimitiveDateTime),
+    /// A `timestamptz` value.
+    #[cfg(feature = "time")]
+    OffsetDateTime(time::OffsetDateTime),
+    /// A `numeric` value.
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+    /// A full-text search query, for the `TextSearch` operator.
+    #[cfg_attr(feature = "serde", serde(with = "crate::search::tagged"))]
+    TextSearch(TextSearch),
+    /// A `jsonb` value.
+    #[cfg(feature = "json")]
+    Json(serde_json::Value),
+}
+
+impl ConditionValue {
+    /// Returns the kind of this value.
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            ConditionValue::String(_) => ValueKind::String,
+            ConditionValue::Integer(_) => ValueKind::Integer,
+            ConditionValue::Float(_) => ValueKind::Float,
+            ConditionValue::Boolean(_) => ValueKind::Boolean,
+            ConditionValue::List(_) => ValueKind::List,
+            ConditionValue::Null => ValueKind::Null,
+            ConditionValue::Bytes(_) => ValueKind::Bytes,
+            #[cfg(feature = "uuid")]
+            ConditionValue::Uuid(_) => ValueKind::Uuid,
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDate(_) => ValueKind::Date,
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveTime(_) => ValueKind::Time,
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDateTime(_) => ValueKind::Timestamp,
+            #[cfg(feature = "chrono")]
+            ConditionValue::DateTime(_) => ValueKind::TimestampTz,
+            #[cfg(feature = "time")]
+            ConditionValue::Date(_) => ValueKind::Date,
+            #[cfg(feature = "time")]
+            ConditionValue::Time(_) => ValueKind::Time,
+            #[cfg(feature = "time")]
+            ConditionValue::PrimitiveDateTime(_) => ValueKind::Timestamp,
+            #[cfg(feature = "time")]
+            ConditionValue::OffsetDateTime(_) => ValueKind::TimestampTz,
+            #[cfg(feature = "rust_decimal")]
+            ConditionValue::Decimal(_) => ValueKind::Decimal,
+            ConditionValue::TextSearch(_) => ValueKind::TextSearch,
+            #[cfg(feature = "json")]
+            ConditionValue::Json(_) => ValueKind::Json,
+        }
+    }
+
+    /// Renders a scalar as text, for formats that carry values as strings.
+    ///
+    /// Floats keep their fractional part (`9.0`) so they read back as floats,
+    /// and dates and times use ISO 8601. Lists, NULL, bytes and non-finite
+    /// floats have no text form.
+    pub(crate) fn to_text(&self) -> Option<String> {
+        match self {
+            ConditionValue::String(s) => Some(s.clone()),
+            ConditionValue::Integer(i) => Some(i.to_string()),
+            ConditionValue::Float(f) if f.is_finite() => Some(format!("{:?}", f)),
+            ConditionValue::Boolean(b) => Some(b.to_string()),
+            #[cfg(feature = "uuid")]
+            ConditionValue::Uuid(u) => Some(u.to_string()),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDate(d) => Some(d.to_string()),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveTime(t) => Some(t.to_string()),
+            #[cfg(feature = "chrono")]
+            ConditionValue::NaiveDateTime(dt) => {
+                Some(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+            }
+            #[cfg(feature = "chrono")]
+            ConditionValue::DateTime(dt) => Some(dt.to_rfc3339()),
+            #[cfg(feature = "time")]
+            ConditionValue::Date(d) => Some(d.to_string()),
+            #[cfg(feature = "time")]
+            ConditionValue::Time(t) => Some(t.to_string()),
+            #[cfg(feature = "time")]
+            ConditionValue::PrimitiveDateTime(dt) => Some(format!("{}T{}", dt.date(), dt.time())),
+            #[cfg(feature = "time")]
+            ConditionValue::OffsetDateTime(dt) => Some(format!(
+                "{}T{}{}",
+                dt.date(),
+                dt.time(),
+                format_offset(dt.offset())
+            )),
+            #[cfg(feature = "rust_decimal")]
+            ConditionValue::Decimal(d) => Some(d.to_string()),
+            #[cfg(feature = "json")]
+            ConditionValue::Json(v) => Some(v.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a UTC offset as `+hh:mm[:ss]`
+#[cfg(feature = "time")]
+fn format_offset(offset: time::UtcOffset) -> String {
+    let (hours, minutes, seconds) = offset.as_hms();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    let mut text = format!("{}{:02}:{:02}", sign, hours.abs(), minutes.abs());
+    if seconds != 0 {
+        text.push_str(&format!(":{:02}", seconds.abs()));
+    }
+    text
+}
+
+/// The kind of a `ConditionValue`, without its payload.
+///
+/// Date and time kinds are shared by the `chrono` and `time` variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    List,
+    Null,
+    Bytes,
+    Uuid,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Decimal,
+    Json,
+    TextSearch,
 }
 
 impl From<String> for ConditionValue {
@@ -147,17 +833,171 @@ impl From<f64> for ConditionValue {
     }
 }
 
+impl From<i16> for ConditionValue {
+    fn from(i: i16) -> Self {
+        ConditionValue::Integer(i as i64)
+    }
+}
+
+impl From<f32> for ConditionValue {
+    fn from(f: f32) -> Self {
+        ConditionValue::Float(f as f64)
+    }
+}
+
+impl<T: Into<ConditionValue>> From<Option<T>> for ConditionValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(ConditionValue::Null, Into::into)
+    }
+}
+
 impl From<bool> for ConditionValue {
     fn from(b: bool) -> Self {
         ConditionValue::Boolean(b)
     }
 }
 
+impl From<Vec<u8>> for ConditionValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        ConditionValue::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for ConditionValue {
+    fn from(bytes: &[u8]) -> Self {
+        ConditionValue::Bytes(bytes.to_vec())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for ConditionValue {
+    fn from(u: uuid::Uuid) -> Self {
+        ConditionValue::Uuid(u)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for ConditionValue {
+    fn from(d: chrono::NaiveDate) -> Self {
+        ConditionValue::NaiveDate(d)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveTime> for ConditionValue {
+    fn from(t: chrono::NaiveTime) -> Self {
+        ConditionValue::NaiveTime(t)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for ConditionValue {
+    fn from(dt: chrono::NaiveDateTime) -> Self {
+        ConditionValue::NaiveDateTime(dt)
+    }
+}
+
+/// Converts to UTC; the instant is kept, the original offset is not.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for ConditionValue {
+    fn from(dt: chrono::DateTime<Tz>) -> Self {
+        ConditionValue::DateTime(dt.with_timezone(&chrono::Utc))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Date> for ConditionValue {
+    fn from(d: time::Date) -> Self {
+        ConditionValue::Date(d)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Time> for ConditionValue {
+    fn from(t: time::Time) -> Self {
+        ConditionValue::Time(t)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::PrimitiveDateTime> for ConditionValue {
+    fn from(dt: time::PrimitiveDateTime) -> Self {
+        ConditionValue::PrimitiveDateTime(dt)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for ConditionValue {
+    fn from(dt: time::OffsetDateTime) -> Self {
+        ConditionValue::OffsetDateTime(dt)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for ConditionValue {
+    fn from(d: rust_decimal::Decimal) -> Self {
+        ConditionValue::Decimal(d)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for ConditionValue {
+    fn from(v: serde_json::Value) -> Self {
+        ConditionValue::Json(v)
+    }
+}
+
+/// A selected column, optionally renamed in the result.
+///
+/// The field may follow a JSON path like a condition field
+/// (`metadata->>plan`); give such columns an alias to name them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Projection {
+    pub field: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub alias: Option<String>,
+}
+
+impl Projection {
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            alias: None,
+        }
+    }
+
+    /// Selects the column under another name (`AS alias`).
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+}
+
 /// Sort order specification.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct SortOrder {
     pub field: String,
     pub direction: SortDirection,
+    /// When set, sorts by how well `field` matches the search (`ts_rank`)
+    /// instead of by its value
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub rank: Option<TextSearch>,
 }
 
 impl SortOrder {
@@ -165,6 +1005,15 @@ impl SortOrder {
         Self {
             field: field.into(),
             direction,
+            rank: None,
+        }
+    }
+
+    /// Sorts by relevance to the search, best matches first.
+    pub fn by_rank(field: impl Into<String>, search: TextSearch) -> Self {
+        Self {
+            rank: Some(search),
+            ..Self::desc(field)
         }
     }
 
@@ -179,13 +1028,20 @@ impl SortOrder {
 
 /// Sort direction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortDirection {
+    #[cfg_attr(feature = "serde", serde(rename = "asc"))]
     Ascending,
+    #[cfg_attr(feature = "serde", serde(rename = "desc"))]
     Descending,
 }
 
 /// Pagination parameters.
-#[derive(Debug, Clone, Copy)]
+///
+/// `new` accepts any values so that struct literals and defaults stay cheap;
+/// use [`Pagination::try_new`] or [`Pagination::validate`] on client input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pagination {
     pub page: i64,
     pub per_page: i64,
@@ -196,8 +1052,49 @@ impl Pagination {
         Self { page, per_page }
     }
 
+    /// Creates pagination, rejecting a `page` or `per_page` below 1 with
+    /// `RepositoryError::InvalidInput`.
+    pub fn try_new(page: i64, per_page: i64) -> Result<Self, RepositoryError> {
+        let pagination = Self::new(page, per_page);
+        pagination.validate()?;
+        Ok(pagination)
+    }
+
+    /// Checks that `page` and `per_page` are at least 1.
+    pub fn validate(&self) -> Result<(), RepositoryError> {
+        if self.page < 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "'page' must be at least 1, got {}",
+                self.page
+            )));
+        }
+        if self.per_page < 1 {
+            return Err(RepositoryError::InvalidInput(format!(
+                "'per_page' must be at least 1, got {}",
+                self.per_page
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the pagination as [`Pagination::validate`] does, and that
+    /// `per_page` is at most `max_per_page`.
+    pub fn validate_max(&self, max_per_page: i64) -> Result<(), RepositoryError> {
+        self.validate()?;
+        if self.per_page > max_per_page {
+            return Err(RepositoryError::InvalidInput(format!(
+                "'per_page' must be at most {}, got {}",
+                max_per_page, self.per_page
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of rows to skip.
+    ///
+    /// Never negative, and saturates instead of overflowing for huge pages.
     pub fn offset(&self) -> i64 {
-        (self.page - 1) * self.per_page
+        (self.page - 1).max(0).saturating_mul(self.per_page.max(0))
     }
 
     pub fn limit(&self) -> i64 {
@@ -215,7 +1112,8 @@ impl Default for Pagination {
 }
 
 /// A page of results with metadata.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page<T> {
     pub items: Vec<T>,
     pub page: i64,
@@ -225,8 +1123,16 @@ pub struct Page<T> {
 }
 
 impl<T> Page<T> {
+    /// Creates a page.
+    ///
+    /// A `per_page` below 1 or a negative total yields zero pages rather than
+    /// panicking.
     pub fn new(items: Vec<T>, page: i64, per_page: i64, total_items: i64) -> Self {
-        let total_pages = (total_items + per_page - 1) / per_page;
+        let total_pages = if per_page > 0 && total_items > 0 {
+            total_items / per_page + i64::from(total_items % per_page != 0)
+        } else {
+            0
+        };
         Self {
             items,
             page,
@@ -236,6 +1142,11 @@ impl<T> Page<T> {
         }
     }
 
+    /// Creates the page described by `pagination` out of `total_items`.
+    pub fn from_pagination(items: Vec<T>, pagination: &Pagination, total_items: i64) -> Self {
+        Self::new(items, pagination.page, pagination.per_page, total_items)
+    }
+
     pub fn has_next(&self) -> bool {
         self.page < self.total_pages
     }
@@ -260,3 +1171,160 @@ impl<T> Page<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_path_parsing() {
+        assert_eq!(
+            FieldPath::parse("metadata->plan->>tier").unwrap(),
+            FieldPath {
+                column: "metadata",
+                keys: vec!["plan", "tier"],
+                as_text: true,
+            }
+        );
+        assert_eq!(FieldPath::parse("users.email").unwrap().keys.len(), 0);
+        assert_eq!(FieldPath::index("[-1]"), Some(-1));
+        assert_eq!(FieldPath::index("2024"), None);
+        assert_eq!(FieldPath::index("plan"), None);
+
+        for field in [
+            "metadata->>plan->tier",
+            "metadata->",
+            "metadata->>",
+            "metadata->tags->[first]",
+        ] {
+            assert!(FieldPath::parse(field).is_err(), "{}", field);
+        }
+    }
+
+    #[test]
+    fn test_pagination_validation() {
+        assert_eq!(Pagination::try_new(2, 10).unwrap(), Pagination::new(2, 10));
+        assert!(matches!(
+            Pagination::try_new(0, 10),
+            Err(RepositoryError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            Pagination::try_new(1, 0),
+            Err(RepositoryError::InvalidInput(_))
+        ));
+
+        assert!(Pagination::new(1, 100).validate_max(100).is_ok());
+        assert!(Pagination::new(1, 101).validate_max(100).is_err());
+        assert!(Pagination::new(0, 50).validate_max(100).is_err());
+    }
+
+    #[test]
+    fn test_offset_never_negative_or_overflowing() {
+        assert_eq!(Pagination::new(3, 25).offset(), 50);
+        assert_eq!(Pagination::new(0, 25).offset(), 0);
+        assert_eq!(Pagination::new(-4, 25).offset(), 0);
+        assert_eq!(Pagination::new(2, -5).offset(), 0);
+        assert_eq!(Pagination::new(i64::MAX, i64::MAX).offset(), i64::MAX);
+    }
+
+    #[test]
+    fn test_page_handles_degenerate_inputs() {
+        assert_eq!(Page::<()>::new(vec![], 1, 10, 95).total_pages, 10);
+        assert_eq!(Page::<()>::new(vec![], 1, 10, 100).total_pages, 10);
+        assert_eq!(Page::<()>::new(vec![], 1, 0, 100).total_pages, 0);
+        assert_eq!(Page::<()>::new(vec![], 1, 10, -1).total_pages, 0);
+        assert_eq!(
+            Page::<()>::new(vec![], 1, 1, i64::MAX).total_pages,
+            i64::MAX
+        );
+        assert_eq!(
+            Page::<()>::new(vec![], 1, 2, i64::MAX).total_pages,
+            i64::MAX / 2 + 1
+        );
+        assert!(!Page::<()>::new(vec![], 1, 0, 100).has_next());
+    }
+
+    #[test]
+    fn test_criteria_and_page_from_pagination() {
+        let pagination = Pagination::new(3, 20);
+        let criteria = FilterCriteria::new().with_pagination(&pagination);
+        assert_eq!((criteria.limit, criteria.offset), (Some(20), Some(40)));
+
+        let page = Page::from_pagination(vec![1, 2], &pagination, 45);
+        assert_eq!((page.page, page.per_page, page.total_pages), (3, 20, 3));
+    }
+
+    #[test]
+    fn test_bytes_values() {
+        let value = ConditionValue::from(vec![0xde_u8, 0xad]);
+        assert_eq!(value, ConditionValue::Bytes(vec![0xde, 0xad]));
+        assert_eq!(value.kind(), ValueKind::Bytes);
+        assert_eq!(value.to_text(), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_values() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let at = FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 1, 12, 30, 0)
+            .unwrap();
+
+        assert_eq!(ConditionValue::from(date).kind(), ValueKind::Date);
+        assert_eq!(
+            ConditionValue::from(date.and_hms_opt(8, 0, 0).unwrap()).to_text(),
+            Some("2024-03-01T08:00:00".to_string())
+        );
+
+        let value = ConditionValue::from(at);
+        assert_eq!(value.kind(), ValueKind::TimestampTz);
+        assert_eq!(
+            value.to_text(),
+            Some("2024-03-01T10:30:00+00:00".to_string())
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_values() {
+        use time::{Date, Month, Time, UtcOffset};
+
+        let date = Date::from_calendar_date(2024, Month::March, 1).unwrap();
+        let at = date
+            .with_time(Time::from_hms(12, 30, 0).unwrap())
+            .assume_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+
+        assert_eq!(
+            ConditionValue::from(date).to_text(),
+            Some("2024-03-01".to_string())
+        );
+        assert_eq!(ConditionValue::from(at).kind(), ValueKind::TimestampTz);
+        assert_eq!(
+            ConditionValue::from(at).to_text(),
+            Some("2024-03-01T12:30:00.0-05:00".to_string())
+        );
+    }
+
+    #[cfg(all(feature = "uuid", feature = "rust_decimal", feature = "json"))]
+    #[test]
+    fn test_uuid_decimal_and_json_values() {
+        let id = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        assert_eq!(ConditionValue::from(id).kind(), ValueKind::Uuid);
+        assert_eq!(
+            ConditionValue::from(Some(id)).to_text(),
+            Some("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string())
+        );
+
+        let price: rust_decimal::Decimal = "19.90".parse().unwrap();
+        assert_eq!(
+            ConditionValue::from(price).to_text(),
+            Some("19.90".to_string())
+        );
+
+        let tags = serde_json::json!({ "tier": "gold" });
+        assert_eq!(ConditionValue::from(tags).kind(), ValueKind::Json);
+    }
+}
@@ -0,0 +1,581 @@
+//! URL query string parsing and serialization
+//!
+//! Turns query strings such as
+//!
+//! ```text
+//! ?status=active&age[gt]=18&role[in]=admin,owner&sort=-created_at&page=2
+//! ```
+//!
+//! into `FilterCriteria` and `Pagination`, and back.
+//!
+//! # Format
+//!
+//! - `field=value` adds an equality condition. `field[op]=value` uses the
+//!   operator `op`, one of `eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `like`,
+//!   `not_like`, `case_sensitive_like`, `starts_with`, `ends_with`,
+//!   `contains`, `in`, `not_in`, `between`, `json_contains`, `json_has_key`,
+//!   `json_has_any_key`, `json_has_all_keys`, `json_path_exists`,
+//!   `array_overlaps`, `array_contains`, `array_contained_by`, `equals_any`,
+//!   `text_search`, `is_null` and `is_not_null`.
+//! - `in`, `not_in`, the `json_has_*_key(s)`, `array_*` and `equals_any`
+//!   operators take a comma-separated list, and `between` the two bounds as
+//!   `low,high`. `text_search` takes a query in search engine syntax (see
+//!   [`SearchMode::Websearch`]). `is_null` and `is_not_null` take no value
+//!   (or `true`).
+//! - `sort` is a comma-separated list of fields; a `-` prefix sorts
+//!   descending.
+//! - `page` and `per_page` set the pagination, defaulting to
+//!   `Pagination::default()`.
+//! - Values are typed by their text: `true`/`false` become booleans, and
+//!   integers and floats become numbers when they read back unchanged, so
+//!   `02134`, `+5` and `9.50` stay strings. Anything else is a string. When
+//!   the field types matter, declare them in a [`FilterSchema`] and pass the
+//!   criteria through [`FilterSchema::coerce`].
+//!
+//! Repeated keys add one condition each, so `age[gte]=18&age[lt]=65` is a
+//! range.
+//!
+//! ```rust
+//! use wyvern::query_string;
+//!
+//! let query = "status=active&age[gt]=18&sort=-created_at&page=2";
+//! let (criteria, pagination) = query_string::parse(query).unwrap();
+//!
+//! assert_eq!(criteria.conditions.len(), 2);
+//! assert_eq!(pagination.page, 2);
+//! ```
+
+use crate::error::RepositoryError;
+use crate::query::{
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, Pagination, SortDirection,
+    SortOrder,
+};
+use crate::search::{SearchMode, TextSearch};
+#[cfg(doc)]
+use crate::validation::FilterSchema;
+
+const SORT_KEY: &str = "sort";
+const PAGE_KEY: &str = "page";
+pub(crate) const PER_PAGE_KEY: &str = "per_page";
+
+/// Parses a query string into filter criteria and pagination.
+///
+/// A leading `?` is ignored. Keys and values are percent-decoded. Malformed
+/// input is reported as `RepositoryError::InvalidInput`.
+pub fn parse(query: &str) -> Result<(FilterCriteria, Pagination), RepositoryError> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut criteria = FilterCriteria::new();
+    let mut pagination = Pagination::default();
+
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            SORT_KEY => {
+                for field in value.split(',').filter(|f| !f.is_empty()) {
+                    criteria = criteria.with_sort(parse_sort(field));
+                }
+            }
+            PAGE_KEY => pagination.page = parse_positive(PAGE_KEY, &value)?,
+            PER_PAGE_KEY => pagination.per_page = parse_positive(PER_PAGE_KEY, &value)?,
+            _ => criteria = criteria.with_condition(parse_condition(&key, &value)?),
+        }
+    }
+
+    Ok((criteria, pagination))
+}
+
+/// Serializes filter criteria and pagination into a query string.
+///
+/// This is the inverse of [`parse`] and is meant for building pagination
+/// links. Only criteria that the format can express are accepted: conditions,
+/// filters made of AND-ed conditions, and sort orders. OR and NOT groups, list
+/// values outside `in`, and `in` items containing commas are reported as
+/// `RepositoryError::InvalidInput`. `limit` and `offset` are not serialized, as
+/// `pagination` describes the page instead, and neither are `projection` and
+/// `include`.
+///
+/// Strings that look like numbers or booleans are parsed back as such. UUIDs,
+/// dates, times, decimals and JSON values are written as text and parsed back
+/// as strings; bytes cannot be expressed.
+pub fn serialize(
+    criteria: &FilterCriteria,
+    pagination: &Pagination,
+) -> Result<String, RepositoryError> {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+    let mut conditions: Vec<&Condition> = criteria.conditions.iter().collect();
+    for filter in &criteria.filters {
+        collect_and_conditions(filter, &mut conditions)?;
+    }
+
+    for condition in conditions {
+        let key = match condition.operator {
+            Operator::Equal => condition.field.clone(),
+            operator => format!("{}[{}]", condition.field, operator_name(operator)),
+        };
+        serializer.append_pair(&key, &format_condition_value(condition)?);
+    }
+
+    if !criteria.sort.is_empty() {
+        let sort: Vec<String> = criteria
+            .sort
+            .iter()
+            .map(|s| match (&s.rank, s.direction) {
+                (Some(_), _) => Err(RepositoryError::InvalidInput(format!(
+                    "rank sort on '{}' cannot be expressed in a query string",
+                    s.field
+                ))),
+                (None, SortDirection::Ascending) => Ok(s.field.clone()),
+                (None, SortDirection::Descending) => Ok(format!("-{}", s.field)),
+            })
+            .collect::<Result<_, _>>()?;
+        serializer.append_pair(SORT_KEY, &sort.join(","));
+    }
+
+    serializer.append_pair(PAGE_KEY, &pagination.page.to_string());
+    serializer.append_pair(PER_PAGE_KEY, &pagination.per_page.to_string());
+
+    Ok(serializer.finish())
+}
+
+fn parse_sort(field: &str) -> SortOrder {
+    match field.strip_prefix('-') {
+        Some(field) => SortOrder::desc(field),
+        None => SortOrder::asc(field.strip_prefix('+').unwrap_or(field)),
+    }
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<i64, RepositoryError> {
+    match value.parse::<i64>() {
+        Ok(n) if n >= 1 => Ok(n),
+        _ => Err(RepositoryError::InvalidInput(format!(
+            "'{}' must be a positive integer, got '{}'",
+            key, value
+        ))),
+    }
+}
+
+fn parse_condition(key: &str, value: &str) -> Result<Condition, RepositoryError> {
+    let (field, operator) = match key.split_once('[') {
+        None => (key, Operator::Equal),
+        Some((field, rest)) => {
+            let name = rest.strip_suffix(']').ok_or_else(|| {
+                RepositoryError::InvalidInput(format!("malformed filter key '{}'", key))
+            })?;
+            let operator = parse_operator(name).ok_or_else(|| {
+                RepositoryError::InvalidInput(format!("unknown operator '{}' in '{}'", name, key))
+            })?;
+            (field, operator)
+        }
+    };
+
+    if field.is_empty() {
+        return Err(RepositoryError::InvalidInput(format!(
+            "missing field name in '{}'",
+            key
+        )));
+    }
+
+    let value = match operator {
+        Operator::IsNull | Operator::IsNotNull => {
+            if !value.is_empty() && value != "true" {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "'{}' takes no value, got '{}'",
+                    key, value
+                )));
+            }
+            ConditionValue::Null
+        }
+        Operator::In
+        | Operator::NotIn
+        | Operator::ArrayOverlaps
+        | Operator::ArrayContains
+        | Operator::ArrayContainedBy
+        | Operator::EqualsAny => ConditionValue::List(value.split(',').map(parse_value).collect()),
+        // Keys are always text, even when they look like numbers
+        Operator::JsonHasAnyKey | Operator::JsonHasAllKeys => {
+            ConditionValue::List(value.split(',').map(ConditionValue::from).collect())
+        }
+        Operator::JsonHasKey | Operator::JsonContains | Operator::JsonPathExists => {
+            ConditionValue::String(value.to_string())
+        }
+        Operator::TextSearch => ConditionValue::TextSearch(TextSearch::websearch(value)),
+        Operator::Between => {
+            let bounds: Vec<ConditionValue> = value.split(',').map(parse_value).collect();
+            if bounds.len() != 2 {
+                return Err(RepositoryError::InvalidInput(format!(
+                    "'{}' takes two comma-separated bounds, got '{}'",
+                    key, value
+                )));
+            }
+            ConditionValue::List(bounds)
+        }
+        _ => parse_value(value),
+    };
+
+    Ok(Condition::new(field, operator, value))
+}
+
+fn parse_operator(name: &str) -> Option<Operator> {
+    let operator = match name {
+        "eq" => Operator::Equal,
+        "ne" => Operator::NotEqual,
+        "gt" => Operator::GreaterThan,
+        "gte" => Operator::GreaterThanOrEqual,
+        "lt" => Operator::LessThan,
+        "lte" => Operator::LessThanOrEqual,
+        "like" => Operator::Like,
+        "not_like" => Operator::NotLike,
+        "case_sensitive_like" => Operator::CaseSensitiveLike,
+        "starts_with" => Operator::StartsWith,
+        "ends_with" => Operator::EndsWith,
+        "contains" => Operator::Contains,
+        "in" => Operator::In,
+        "not_in" => Operator::NotIn,
+        "between" => Operator::Between,
+        "json_contains" => Operator::JsonContains,
+        "json_has_key" => Operator::JsonHasKey,
+        "json_has_any_key" => Operator::JsonHasAnyKey,
+        "json_has_all_keys" => Operator::JsonHasAllKeys,
+        "json_path_exists" => Operator::JsonPathExists,
+        "array_overlaps" => Operator::ArrayOverlaps,
+        "array_contains" => Operator::ArrayContains,
+        "array_contained_by" => Operator::ArrayContainedBy,
+        "equals_any" => Operator::EqualsAny,
+        "text_search" => Operator::TextSearch,
+        "is_null" => Operator::IsNull,
+        "is_not_null" => Operator::IsNotNull,
+        _ => return None,
+    };
+    Some(operator)
+}
+
+fn operator_name(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Equal => "eq",
+        Operator::NotEqual => "ne",
+        Operator::GreaterThan => "gt",
+        Operator::GreaterThanOrEqual => "gte",
+        Operator::LessThan => "lt",
+        Operator::LessThanOrEqual => "lte",
+        Operator::Like => "like",
+        Operator::NotLike => "not_like",
+        Operator::CaseSensitiveLike => "case_sensitive_like",
+        Operator::StartsWith => "starts_with",
+        Operator::EndsWith => "ends_with",
+        Operator::Contains => "contains",
+        Operator::In => "in",
+        Operator::NotIn => "not_in",
+        Operator::Between => "between",
+        Operator::JsonContains => "json_contains",
+        Operator::JsonHasKey => "json_has_key",
+        Operator::JsonHasAnyKey => "json_has_any_key",
+        Operator::JsonHasAllKeys => "json_has_all_keys",
+        Operator::JsonPathExists => "json_path_exists",
+        Operator::ArrayOverlaps => "array_overlaps",
+        Operator::ArrayContains => "array_contains",
+        Operator::ArrayContainedBy => "array_contained_by",
+        Operator::EqualsAny => "equals_any",
+        Operator::TextSearch => "text_search",
+        Operator::IsNull => "is_null",
+        Operator::IsNotNull => "is_not_null",
+    }
+}
+
+/// Types an unquoted textual value: booleans, integers, finite floats, else a
+/// string
+///
+/// Numbers are only typed when they render back to the same text, so no
+/// digits are lost for fields such as zip codes that are really strings.
+pub(crate) fn parse_value(value: &str) -> ConditionValue {
+    match value {
+        "true" => ConditionValue::Boolean(true),
+        "false" => ConditionValue::Boolean(false),
+        _ => {
+            if let Some(i) = value.parse::<i64>().ok().filter(|i| i.to_string() == value) {
+                ConditionValue::Integer(i)
+            } else if let Some(f) = value
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite() && format!("{:?}", f) == value)
+            {
+                ConditionValue::Float(f)
+            } else {
+                ConditionValue::String(value.to_string())
+            }
+        }
+    }
+}
+
+fn collect_and_conditions<'a>(
+    filter: &'a FilterExpr,
+    conditions: &mut Vec<&'a Condition>,
+) -> Result<(), RepositoryError> {
+    match filter {
+        FilterExpr::Leaf(condition) => conditions.push(condition),
+        FilterExpr::And(exprs) => {
+            for expr in exprs {
+                collect_and_conditions(expr, conditions)?;
+            }
+        }
+        FilterExpr::Or(_) | FilterExpr::Not(_) => {
+            return Err(RepositoryError::InvalidInput(
+                "OR and NOT filters cannot be expressed in a query string".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn format_condition_value(condition: &Condition) -> Result<String, RepositoryError> {
+    match (&condition.operator, &condition.value) {
+        (Operator::IsNull | Operator::IsNotNull, _) => Ok(String::new()),
+        (
+            Operator::In
+            | Operator::NotIn
+            | Operator::Between
+            | Operator::JsonHasAnyKey
+            | Operator::JsonHasAllKeys
+            | Operator::ArrayOverlaps
+            | Operator::ArrayContains
+            | Operator::ArrayContainedBy
+            | Operator::EqualsAny,
+            ConditionValue::List(values),
+        ) => {
+            let items = values
+                .iter()
+                .map(|v| match v.to_text() {
+                    Some(item) if !item.contains(',') => Ok(item),
+                    _ => Err(RepositoryError::InvalidInput(format!(
+                        "value of '{}' cannot be expressed in a query string list",
+                        condition.field
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(items.join(","))
+        }
+        (_, ConditionValue::TextSearch(search))
+            if search.mode == SearchMode::Websearch
+                && search.config.is_none()
+                && !search.tsvector =>
+        {
+            Ok(search.query.clone())
+        }
+        (_, value) => value.to_text().ok_or_else(|| {
+            RepositoryError::InvalidInput(format!(
+                "value of '{}' cannot be expressed in a query string",
+                condition.field
+            ))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conditions_sort_and_pagination() {
+        let (criteria, pagination) = parse(
+            "?status=active&age[gt]=18&score[lte]=9.5&verified=true&sort=-created_at,name&page=2&per_page=50",
+        )
+        .unwrap();
+
+        assert_eq!(
+            criteria,
+            FilterCriteria::new()
+                .with_condition(Condition::eq("status", "active".into()))
+                .with_condition(Condition::gt("age", 18.into()))
+                .with_condition(Condition::new(
+                    "score",
+                    Operator::LessThanOrEqual,
+                    9.5.into()
+                ))
+                .with_condition(Condition::eq("verified", true.into()))
+                .with_sort(SortOrder::desc("created_at"))
+                .with_sort(SortOrder::asc("name"))
+        );
+        assert_eq!(pagination, Pagination::new(2, 50));
+    }
+
+    #[test]
+    fn test_parse_every_operator() {
+        let cases = [
+            ("a[eq]=1", Operator::Equal),
+            ("a[ne]=1", Operator::NotEqual),
+            ("a[gt]=1", Operator::GreaterThan),
+            ("a[gte]=1", Operator::GreaterThanOrEqual),
+            ("a[lt]=1", Operator::LessThan),
+            ("a[lte]=1", Operator::LessThanOrEqual),
+            ("a[like]=%25x%25", Operator::Like),
+            ("a[not_like]=%25x", Operator::NotLike),
+            ("a[case_sensitive_like]=X%25", Operator::CaseSensitiveLike),
+            ("a[starts_with]=x", Operator::StartsWith),
+            ("a[ends_with]=x", Operator::EndsWith),
+            ("a[contains]=x", Operator::Contains),
+            ("a[in]=1,2", Operator::In),
+            ("a[not_in]=1,2", Operator::NotIn),
+            ("a[between]=1,2", Operator::Between),
+            ("a[json_contains]=%7B%7D", Operator::JsonContains),
+            ("a[json_has_key]=x", Operator::JsonHasKey),
+            ("a[json_has_any_key]=x,y", Operator::JsonHasAnyKey),
+            ("a[json_has_all_keys]=x,y", Operator::JsonHasAllKeys),
+            ("a[json_path_exists]=%24.x", Operator::JsonPathExists),
+            ("a[array_overlaps]=x,y", Operator::ArrayOverlaps),
+            ("a[array_contains]=x,y", Operator::ArrayContains),
+            ("a[array_contained_by]=x,y", Operator::ArrayContainedBy),
+            ("a[equals_any]=1,2", Operator::EqualsAny),
+            ("a[text_search]=x", Operator::TextSearch),
+            ("a[is_null]", Operator::IsNull),
+            ("a[is_not_null]=true", Operator::IsNotNull),
+        ];
+
+        for (query, operator) in cases {
+            let (criteria, _) = parse(query).unwrap();
+            assert_eq!(criteria.conditions[0].operator, operator, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let (criteria, _) = parse(
+            "role[in]=admin,owner,7&name=Ada+Lovelace&email[like]=%25%40example.com&code=007x",
+        )
+        .unwrap();
+
+        assert_eq!(
+            criteria.conditions[0].value,
+            ConditionValue::List(vec!["admin".into(), "owner".into(), 7.into()])
+        );
+        assert_eq!(criteria.conditions[1].value, "Ada Lovelace".into());
+        assert_eq!(criteria.conditions[2].value, "%@example.com".into());
+        assert_eq!(criteria.conditions[3].value, "007x".into());
+    }
+
+    #[test]
+    fn test_numbers_that_would_lose_digits_stay_strings() {
+        let (criteria, _) = parse("zip=02134&n=-3&price=9.5&total=9.50&delta=%2B5").unwrap();
+        let values: Vec<_> = criteria.conditions.iter().map(|c| &c.value).collect();
+
+        assert_eq!(
+            values,
+            [
+                &"02134".into(),
+                &(-3).into(),
+                &9.5.into(),
+                &"9.50".into(),
+                &"+5".into()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_search_round_trip() {
+        let criteria = FilterCriteria::new().with_condition(Condition::text_search(
+            "body",
+            TextSearch::websearch("\"async runtime\" -python"),
+        ));
+
+        let query = serialize(&criteria, &Pagination::default()).unwrap();
+        let (parsed, _) = parse(&query).unwrap();
+
+        assert_eq!(parsed, criteria);
+    }
+
+    #[test]
+    fn test_percent_encoded_keys() {
+        let (criteria, _) = parse("age%5Bgte%5D=21").unwrap();
+
+        assert_eq!(
+            criteria.conditions[0],
+            Condition::new("age", Operator::GreaterThanOrEqual, 21.into())
+        );
+    }
+
+    #[test]
+    fn test_defaults() {
+        let (criteria, pagination) = parse("").unwrap();
+
+        assert_eq!(criteria, FilterCriteria::new());
+        assert_eq!(pagination, Pagination::default());
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        for query in [
+            "age[gt=18",
+            "age[between]=1",
+            "age[between]=1,2,3",
+            "age[regex]=1",
+            "[gt]=1",
+            "deleted_at[is_null]=false",
+            "page=0",
+            "page=two",
+            "per_page=-5",
+        ] {
+            assert!(
+                matches!(parse(query), Err(RepositoryError::InvalidInput(_))),
+                "expected '{}' to be rejected",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("status", "active".into()))
+            .with_condition(Condition::in_list(
+                "role",
+                vec!["admin".into(), "owner".into()],
+            ))
+            .with_condition(Condition::new(
+                "deleted_at",
+                Operator::IsNull,
+                ConditionValue::Null,
+            ))
+            .with_filter(FilterExpr::and([Condition::new(
+                "email",
+                Operator::Like,
+                "%@example.com".into(),
+            )]))
+            .with_sort(SortOrder::desc("created_at"));
+        let pagination = Pagination::new(3, 25);
+
+        let query = serialize(&criteria, &pagination).unwrap();
+
+        assert_eq!(
+            query,
+            "status=active&role%5Bin%5D=admin%2Cowner&deleted_at%5Bis_null%5D=&email%5Blike%5D=%25%40example.com&sort=-created_at&page=3&per_page=25"
+        );
+
+        let (parsed, parsed_pagination) = parse(&query).unwrap();
+        assert_eq!(parsed.conditions.len(), 4);
+        assert_eq!(parsed.conditions[..3], criteria.conditions[..]);
+        assert_eq!(parsed.sort, criteria.sort);
+        assert_eq!(parsed_pagination, pagination);
+    }
+
+    #[test]
+    fn test_serialize_rejects_unrepresentable_criteria() {
+        let pagination = Pagination::default();
+
+        let or = FilterCriteria::new()
+            .with_any([Condition::eq("a", 1.into()), Condition::eq("b", 2.into())]);
+        let comma =
+            FilterCriteria::new().with_condition(Condition::in_list("a", vec!["x,y".into()]));
+        let null = FilterCriteria::new().with_condition(Condition::eq("a", ConditionValue::Null));
+        let configured = FilterCriteria::new().with_condition(Condition::text_search(
+            "body",
+            TextSearch::websearch("rust").with_config("english"),
+        ));
+        let rank =
+            FilterCriteria::new().with_sort(SortOrder::by_rank("body", TextSearch::plain("rust")));
+
+        for criteria in [or, comma, null, configured, rank] {
+            assert!(matches!(
+                serialize(&criteria, &pagination),
+                Err(RepositoryError::InvalidInput(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Relations between entities
+//!
+//! A [`Relation`] declares how rows of another table relate to the rows of an
+//! entity's table. Conditions can then reference fields of related rows as
+//! `relation.field`, such as `customer.country` on orders.
+//!
+//! ```rust
+//! use wyvern::{Condition, FilterCriteria, Relation};
+//!
+//! // orders.customer_id references customers.id
+//! let customer = Relation::many_to_one("customer", "customers", "customer_id");
+//! // order_lines.order_id references orders.id
+//! let lines = Relation::one_to_many("lines", "order_lines", "order_id");
+//! // order_tags links orders and tags
+//! let tags = Relation::many_to_many("tags", "tags", "order_tags", "order_id", "tag_id");
+//!
+//! let criteria = FilterCriteria::new()
+//!     .with_condition(Condition::eq("customer.country", "FR".into()))
+//!     .with_condition(Condition::eq("tags.name", "gift".into()));
+//! ```
+//!
+//! The SQLx adapter renders each condition on a related field as an `EXISTS`
+//! subquery, so rows of the root table are never duplicated: for to-many
+//! relations a condition holds when at least one related row satisfies it,
+//! and each condition is checked on its own. `IsNull` on a many-to-one field
+//! also holds when there is no related row, like a left join would. The part
+//! after the relation's name must be a single column, optionally followed by
+//! a JSON path. Sorting is supported on the fields of many-to-one relations.
+//!
+//! Only the SQLx adapter resolves relations. The in-memory repository rejects
+//! conditions and sorts on the fields of an entity's relations, as it stores
+//! no related rows. [`FilterCriteria::matches`](crate::FilterCriteria::matches)
+//! looks dotted names up through [`FieldValues`](crate::FieldValues) like any
+//! other field, so it only sees related values that the type provides itself.
+//!
+//! # Eager loading
+//!
+//! Relations named with
+//! [`FilterCriteria::with_include`](crate::FilterCriteria::with_include) are
+//! loaded together with the results by `SqlxRepository::filter_loaded`, which
+//! returns each entity as a [`Loaded`] holding its related rows. Every
+//! included relation costs one query for the whole result, matching the keys
+//! of all entities at once with `= ANY($1)`, instead of one query per entity.
+//!
+//! Only the columns declared with [`Relation::with_columns`] (or
+//! [`Relation::with_entity_columns`]) are loaded, so a relation without
+//! declared columns cannot be loaded eagerly.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::entity::Entity;
+use crate::query::ConditionValue;
+
+/// A related row, as a map from column names to values
+pub type RelatedRow = HashMap<String, ConditionValue>;
+
+/// How the rows of a [`Relation`] are linked to the root rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelationKind {
+    /// Each root row references one related row:
+    /// `root.foreign_key = related.target_key`
+    ManyToOne { foreign_key: String },
+    /// Related rows reference the root row:
+    /// `related.foreign_key = root.source_key`
+    OneToMany { foreign_key: String },
+    /// A join table references both sides:
+    /// `join_table.source_foreign_key = root.source_key` and
+    /// `join_table.target_foreign_key = related.target_key`
+    ManyToMany {
+        join_table: String,
+        source_foreign_key: String,
+        target_foreign_key: String,
+    },
+}
+
+/// A named relation from an entity's table to another table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relation {
+    /// The name conditions use as a prefix (`customer` in `customer.country`)
+    pub name: String,
+    /// The related table
+    pub table: String,
+    pub kind: RelationKind,
+    /// The key of the root table referenced by the relation, `id` by default
+    pub source_key: String,
+    /// The key of the related table referenced by the relation, `id` by default
+    pub target_key: String,
+    /// The columns of the related table read when loading it eagerly
+    pub columns: Vec<String>,
+}
+
+impl Relation {
+    /// Creates a relation of the given kind referencing both `id` keys.
+    pub fn new(name: impl Into<String>, table: impl Into<String>, kind: RelationKind) -> Self {
+        Self {
+            name: name.into(),
+            table: table.into(),
+            kind,
+            source_key: "id".to_string(),
+            target_key: "id".to_string(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Declares that the root table's `foreign_key` references the related table.
+    pub fn many_to_one(
+        name: impl Into<String>,
+        table: impl Into<String>,
+        foreign_key: impl Into<String>,
+    ) -> Self {
+        let foreign_key = foreign_key.into();
+        Self::new(name, table, RelationKind::ManyToOne { foreign_key })
+    }
+
+    /// Declares that the related table's `foreign_key` references the root table.
+    pub fn one_to_many(
+        name: impl Into<String>,
+        table: impl Into<String>,
+        foreign_key: impl Into<String>,
+    ) -> Self {
+        let foreign_key = foreign_key.into();
+        Self::new(name, table, RelationKind::OneToMany { foreign_key })
+    }
+
+    /// Declares that `join_table` links the tables, its `source_foreign_key`
+    /// referencing the root table and its `target_foreign_key` the related one.
+    pub fn many_to_many(
+        name: impl Into<String>,
+        table: impl Into<String>,
+        join_table: impl Into<String>,
+        source_foreign_key: impl Into<String>,
+        target_foreign_key: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            name,
+            table,
+            RelationKind::ManyToMany {
+                join_table: join_table.into(),
+                source_foreign_key: source_foreign_key.into(),
+                target_foreign_key: target_foreign_key.into(),
+            },
+        )
+    }
+
+    /// Sets the key of the root table the relation references.
+    pub fn with_source_key(mut self, key: impl Into<String>) -> Self {
+        self.source_key = key.into();
+        self
+    }
+
+    /// Sets the key of the related table the relation references.
+    pub fn with_target_key(mut self, key: impl Into<String>) -> Self {
+        self.target_key = key.into();
+        self
+    }
+
+    /// Adds columns of the related table to read when loading it eagerly.
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Reads the columns of the entity `E` when loading the relation eagerly.
+    pub fn with_entity_columns<E: Entity>(self) -> Self {
+        self.with_columns(E::COLUMNS.iter().copied())
+    }
+
+    /// The column of the root table the relation's link refers to: the
+    /// foreign key of a many-to-one relation, the source key otherwise.
+    pub fn root_key(&self) -> &str {
+        match &self.kind {
+            RelationKind::ManyToOne { foreign_key } => foreign_key,
+            _ => &self.source_key,
+        }
+    }
+
+    /// Whether each root row has at most one related row.
+    pub fn is_to_one(&self) -> bool {
+        matches!(self.kind, RelationKind::ManyToOne { .. })
+    }
+
+    /// Splits `field` into the relation it traverses and the field of the
+    /// related table, if its prefix names one of `relations`.
+    pub(crate) fn find<'a, 'f>(
+        relations: &'a [Relation],
+        field: &'f str,
+    ) -> Option<(&'a Relation, &'f str)> {
+        let (name, rest) = field.split_once('.')?;
+        relations
+            .iter()
+            .find(|relation| relation.name == name)
+            .map(|relation| (relation, rest))
+    }
+}
+
+/// An entity together with the rows of its included relations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<T> {
+    pub entity: T,
+    /// The related rows by relation name, with an entry for every included
+    /// relation
+    pub related: HashMap<String, Vec<RelatedRow>>,
+}
+
+impl<T> Loaded<T> {
+    /// Returns the rows of `relation`, empty when it was not included.
+    pub fn related(&self, relation: &str) -> &[RelatedRow] {
+        self.related.get(relation).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the row of a many-to-one relation, if there is one.
+    pub fn related_one(&self, relation: &str) -> Option<&RelatedRow> {
+        self.related(relation).first()
+    }
+}
+
+/// Returns a hashable encoding of a key, or `None` for NULL, which is
+/// related to nothing
+fn key_hash(key: &ConditionValue) -> Option<String> {
+    match key {
+        ConditionValue::Null => None,
+        key => Some(format!("{:?}", key)),
+    }
+}
+
+/// Returns the distinct non-NULL keys of the root rows, in order
+#[cfg_attr(not(feature = "sqlx"), allow(dead_code))]
+pub(crate) fn distinct_keys(parent_keys: &[ConditionValue]) -> Vec<ConditionValue> {
+    let mut seen = HashSet::new();
+    parent_keys
+        .iter()
+        .filter(|key| key_hash(key).is_some_and(|hash| seen.insert(hash)))
+        .cloned()
+        .collect()
+}
+
+/// Groups related rows by the root rows they belong to
+///
+/// `parent_keys` holds the key of each root row and `row_keys` the root key
+/// of each related row. Returns, for each root row, the indices of its
+/// related rows; a related row belongs to every root row sharing its key.
+#[cfg_attr(not(feature = "sqlx"), allow(dead_code))]
+pub(crate) fn group_by_parent(
+    parent_keys: &[ConditionValue],
+    row_keys: &[ConditionValue],
+) -> Vec<Vec<usize>> {
+    let mut rows_by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, key) in row_keys.iter().enumerate() {
+        if let Some(hash) = key_hash(key) {
+            rows_by_key.entry(hash).or_default().push(i);
+        }
+    }
+
+    parent_keys
+        .iter()
+        .map(|key| {
+            key_hash(key)
+                .and_then(|hash| rows_by_key.get(&hash))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_splits_relation_fields() {
+        let relations = [
+            Relation::many_to_one("customer", "customers", "customer_id").with_target_key("uuid"),
+            Relation::one_to_many("lines", "order_lines", "order_id"),
+        ];
+
+        let (relation, field) = Relation::find(&relations, "customer.country").unwrap();
+        assert_eq!(relation.name, "customer");
+        assert_eq!(relation.target_key, "uuid");
+        assert_eq!(relation.source_key, "id");
+        assert_eq!(field, "country");
+        assert!(relation.is_to_one());
+        assert!(
+            !Relation::find(&relations, "lines.sku")
+                .unwrap()
+                .0
+                .is_to_one()
+        );
+
+        // Other qualified names and plain fields are not relation fields
+        assert!(Relation::find(&relations, "orders.status").is_none());
+        assert!(Relation::find(&relations, "customer").is_none());
+    }
+
+    #[test]
+    fn test_root_key() {
+        let customer = Relation::many_to_one("customer", "customers", "customer_id");
+        let lines = Relation::one_to_many("lines", "order_lines", "order_id");
+        let tags = Relation::many_to_many("tags", "tags", "order_tags", "order_uuid", "tag_id")
+            .with_source_key("uuid");
+
+        assert_eq!(customer.root_key(), "customer_id");
+        assert_eq!(lines.root_key(), "id");
+        assert_eq!(tags.root_key(), "uuid");
+    }
+
+    #[test]
+    fn test_loaded_accessors() {
+        let customer = RelatedRow::from([("name".to_string(), ConditionValue::from("Ada"))]);
+        let loaded = Loaded {
+            entity: 1,
+            related: HashMap::from([
+                ("customer".to_string(), vec![customer.clone()]),
+                ("lines".to_string(), Vec::new()),
+            ]),
+        };
+
+        assert_eq!(loaded.related_one("customer"), Some(&customer));
+        assert!(loaded.related("lines").is_empty());
+        assert!(loaded.related_one("lines").is_none());
+        assert!(loaded.related("tags").is_empty());
+    }
+
+    #[test]
+    fn test_group_one_to_many() {
+        // Orders 1 and 2; lines of order 2, 1 and 2
+        let parents = [1.into(), 2.into()];
+        let rows = [2.into(), 1.into(), 2.into()];
+
+        assert_eq!(distinct_keys(&parents), parents.to_vec());
+        assert_eq!(group_by_parent(&parents, &rows), vec![vec![1], vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_group_many_to_many_with_shared_children() {
+        // Both orders are linked to the tag of row 0; only order 7 to row 1
+        let parents = [7.into(), 8.into()];
+        let rows = [7.into(), 7.into(), 8.into()];
+
+        assert_eq!(group_by_parent(&parents, &rows), vec![vec![0, 1], vec![2]]);
+
+        // Many-to-one: orders sharing a customer share its row
+        let parents = ["c1".into(), "c2".into(), "c1".into()];
+        let rows = ["c1".into(), "c2".into()];
+
+        assert_eq!(distinct_keys(&parents), vec!["c1".into(), "c2".into()]);
+        assert_eq!(
+            group_by_parent(&parents, &rows),
+            vec![vec![0], vec![1], vec![0]]
+        );
+    }
+
+    #[test]
+    fn test_null_keys_relate_to_nothing() {
+        let parents = [ConditionValue::Null, 1.into(), ConditionValue::Null];
+        let rows = [1.into(), ConditionValue::Null];
+
+        assert_eq!(distinct_keys(&parents), vec![1.into()]);
+        assert_eq!(
+            group_by_parent(&parents, &rows),
+            vec![vec![], vec![0], vec![]]
+        );
+        assert!(distinct_keys(&[ConditionValue::Null]).is_empty());
+    }
+}
@@ -3,6 +3,10 @@
 use async_trait::async_trait;
 use std::error::Error;
 
+use crate::aggregate::{self, AggregateQuery, AggregateRow};
+use crate::cursor::{CursorPage, CursorPagination, CursorQuery};
+use crate::entity::FieldValues;
+use crate::error::RepositoryError;
 use crate::query::{FilterCriteria, Page, Pagination};
 
 /// Base repository trait providing standard CRUD operations.
@@ -48,7 +52,7 @@ pub trait Repository<T>: Send + Sync {
 /// Trait for repositories that support advanced querying capabilities.
 ///
 /// This trait extends basic CRUD operations with filtering, sorting,
-/// pagination, and counting capabilities.
+/// pagination, counting and aggregation capabilities.
 #[async_trait]
 pub trait Queryable<T>: Repository<T> {
     /// Executes a query with the given criteria.
@@ -72,4 +76,50 @@ pub trait Queryable<T>: Repository<T> {
         &self,
         criteria: FilterCriteria,
     ) -> Result<bool, <Self as Repository<T>>::Error>;
+
+    /// Executes a keyset-paginated query.
+    ///
+    /// `criteria` must be sorted, and the sort must be total over non-null
+    /// fields (see [`crate::cursor`]). Limit and offset of the criteria are
+    /// replaced by the pagination.
+    ///
+    /// The default implementation seeks with plain conditions through
+    /// [`Queryable::filter`]; backends may render the seek natively.
+    async fn paginate_by_cursor(
+        &self,
+        criteria: FilterCriteria,
+        pagination: CursorPagination,
+    ) -> Result<CursorPage<T>, <Self as Repository<T>>::Error>
+    where
+        T: FieldValues,
+        <Self as Repository<T>>::Error: From<RepositoryError>,
+    {
+        let query = CursorQuery::new(criteria, pagination, &[])?;
+        let mut criteria = query.criteria.clone();
+        if let Some(seek) = &query.seek {
+            criteria = criteria.with_filter(seek.expression());
+        }
+        let items = self.filter(criteria).await?;
+        Ok(query.finish(items)?)
+    }
+
+    /// Groups the entities matching the query's criteria and computes its
+    /// aggregates, returning one row per group.
+    ///
+    /// The default implementation aggregates in process over the result of
+    /// [`Queryable::filter`]; backends may aggregate natively.
+    async fn aggregate(
+        &self,
+        query: AggregateQuery,
+    ) -> Result<Vec<AggregateRow>, <Self as Repository<T>>::Error>
+    where
+        T: FieldValues,
+        <Self as Repository<T>>::Error: From<RepositoryError>,
+    {
+        query.check()?;
+        let entities = self
+            .filter(FilterCriteria::from(query.criteria.expression()))
+            .await?;
+        Ok(aggregate::evaluate(&query, &entities)?)
+    }
 }
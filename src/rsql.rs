@@ -0,0 +1,680 @@
+//! RSQL/FIQL filter expressions
+//!
+//! Parses expressions such as `status==active;age=gt=18,role=in=(admin,owner)`
+//! into `FilterCriteria`, and prints criteria back to RSQL so filters can be
+//! carried in pagination links.
+//!
+//! # Syntax
+//!
+//! - `;` (or `and`) combines constraints with AND, `,` (or `or`) with OR. AND
+//!   binds tighter than OR, and parentheses group.
+//! - A constraint is `selector operator argument`, where the argument is a
+//!   value or a parenthesized, comma-separated list of values.
+//! - Values may be quoted with `'` or `"`, using `\` to escape. Unquoted
+//!   values are typed like query string values (booleans, and integers and
+//!   floats that read back unchanged, else strings); quoted values are always
+//!   strings. [`FilterSchema::coerce`] retypes values by their declared field
+//!   kinds.
+//!
+//! | RSQL                  | Condition                               |
+//! |-----------------------|-----------------------------------------|
+//! | `==`                  | `Equal`                                 |
+//! | `!=`                  | `NotEqual`                              |
+//! | `=gt=` or `>`         | `GreaterThan`                           |
+//! | `=ge=` or `>=`        | `GreaterThanOrEqual`                    |
+//! | `=lt=` or `<`         | `LessThan`                              |
+//! | `=le=` or `<=`        | `LessThanOrEqual`                       |
+//! | `=like=`              | `Like`                                  |
+//! | `=notlike=`           | `NotLike`                               |
+//! | `=cslike=`            | `CaseSensitiveLike`                     |
+//! | `=startswith=`        | `StartsWith`                            |
+//! | `=endswith=`          | `EndsWith`                              |
+//! | `=contains=`          | `Contains`                              |
+//! | `=in=`                | `In`                                    |
+//! | `=out=`               | `NotIn`                                 |
+//! | `=between=(low,high)` | `Between`                               |
+//! | `=isnull=true/false`  | `IsNull` / `IsNotNull`                  |
+//!
+//! ```rust
+//! use wyvern::rsql;
+//!
+//! let criteria = rsql::parse("status==active;age=gt=18,role=in=(admin,owner)").unwrap();
+//! assert_eq!(
+//!     rsql::serialize(&criteria).unwrap(),
+//!     "status==active;age=gt=18,role=in=(admin,owner)"
+//! );
+//! ```
+
+use crate::error::RepositoryError;
+use crate::query::{Condition, ConditionValue, FilterCriteria, FilterExpr, Operator};
+use crate::query_string::parse_value;
+#[cfg(doc)]
+use crate::validation::FilterSchema;
+
+/// Characters that cannot appear in selectors or unquoted values
+const RESERVED: &[char] = &['"', '\'', '(', ')', ';', ',', '=', '!', '~', '<', '>'];
+
+/// Parses an RSQL expression into filter criteria.
+///
+/// Top-level AND-ed constraints become conditions; any other expression
+/// becomes a filter. An empty input yields empty criteria. Syntax errors are
+/// reported as `RepositoryError::InvalidInput` with the zero-based character
+/// position of the problem.
+pub fn parse(input: &str) -> Result<FilterCriteria, RepositoryError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+
+    parser.skip_whitespace();
+    if parser.at_end() {
+        return Ok(FilterCriteria::new());
+    }
+
+    let expr = parser.parse_or()?;
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(format!("unexpected character '{}'", c)));
+    }
+
+    Ok(FilterCriteria::from(expr))
+}
+
+/// Prints the conditions and filters of the criteria as RSQL.
+///
+/// Sorting, limit and offset are not part of RSQL and are ignored. Empty
+/// criteria print as an empty string. `NOT` is only expressible around `In`
+/// (as `=out=`), `Equal` and `NotEqual`; other negations, and values RSQL
+/// cannot carry (nested lists, non-finite floats, `NULL` outside
+/// `IsNull`/`IsNotNull`), are reported as `RepositoryError::InvalidInput`.
+pub fn serialize(criteria: &FilterCriteria) -> Result<String, RepositoryError> {
+    match criteria.expression() {
+        FilterExpr::And(exprs) if exprs.is_empty() => Ok(String::new()),
+        expr => print_expr(&expr),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse_or(&mut self) -> Result<FilterExpr, RepositoryError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat_separator(',', "or") {
+            exprs.push(self.parse_and()?);
+        }
+        Ok(Self::group(exprs, FilterExpr::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, RepositoryError> {
+        let mut exprs = vec![self.parse_constraint()?];
+        while self.eat_separator(';', "and") {
+            exprs.push(self.parse_constraint()?);
+        }
+        Ok(Self::group(exprs, FilterExpr::And))
+    }
+
+    fn group(mut exprs: Vec<FilterExpr>, make: fn(Vec<FilterExpr>) -> FilterExpr) -> FilterExpr {
+        if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            make(exprs)
+        }
+    }
+
+    fn parse_constraint(&mut self) -> Result<FilterExpr, RepositoryError> {
+        self.skip_whitespace();
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(expr);
+        }
+
+        let selector = self.take_unreserved();
+        if selector.is_empty() {
+            return Err(self.error("expected a selector".to_string()));
+        }
+
+        self.skip_whitespace();
+        let operator_pos = self.pos;
+        let operator = self.parse_operator()?;
+
+        self.skip_whitespace();
+        let argument_pos = self.pos;
+        let argument = self.parse_argument()?;
+
+        let at = |pos: usize, message: String| {
+            RepositoryError::InvalidInput(format!("{} at position {}", message, pos))
+        };
+        let single = |argument: Vec<ConditionValue>| -> Result<ConditionValue, RepositoryError> {
+            match <[ConditionValue; 1]>::try_from(argument) {
+                Ok([value]) => Ok(value),
+                Err(_) => Err(at(
+                    argument_pos,
+                    format!("operator '{}' takes a single value", operator),
+                )),
+            }
+        };
+
+        let condition = |op: Operator, value: ConditionValue| {
+            FilterExpr::Leaf(Condition::new(selector.clone(), op, value))
+        };
+
+        let expr = match operator.as_str() {
+            "==" => condition(Operator::Equal, single(argument)?),
+            "!=" => condition(Operator::NotEqual, single(argument)?),
+            "=gt=" | ">" => condition(Operator::GreaterThan, single(argument)?),
+            "=ge=" | ">=" => condition(Operator::GreaterThanOrEqual, single(argument)?),
+            "=lt=" | "<" => condition(Operator::LessThan, single(argument)?),
+            "=le=" | "<=" => condition(Operator::LessThanOrEqual, single(argument)?),
+            "=like=" => condition(Operator::Like, single(argument)?),
+            "=notlike=" => condition(Operator::NotLike, single(argument)?),
+            "=cslike=" => condition(Operator::CaseSensitiveLike, single(argument)?),
+            "=startswith=" => condition(Operator::StartsWith, single(argument)?),
+            "=endswith=" => condition(Operator::EndsWith, single(argument)?),
+            "=contains=" => condition(Operator::Contains, single(argument)?),
+            "=in=" => condition(Operator::In, ConditionValue::List(argument)),
+            "=out=" => condition(Operator::NotIn, ConditionValue::List(argument)),
+            "=between=" => {
+                if argument.len() != 2 {
+                    return Err(at(
+                        argument_pos,
+                        "operator '=between=' takes two values".to_string(),
+                    ));
+                }
+                condition(Operator::Between, ConditionValue::List(argument))
+            }
+            "=isnull=" => match single(argument)? {
+                ConditionValue::Boolean(true) => condition(Operator::IsNull, ConditionValue::Null),
+                ConditionValue::Boolean(false) => {
+                    condition(Operator::IsNotNull, ConditionValue::Null)
+                }
+                _ => {
+                    return Err(at(
+                        argument_pos,
+                        "operator '=isnull=' takes true or false".to_string(),
+                    ));
+                }
+            },
+            _ => {
+                return Err(at(
+                    operator_pos,
+                    format!(
+                        "unknown operator '{}' for selector '{}'",
+                        operator, selector
+                    ),
+                ));
+            }
+        };
+
+        Ok(expr)
+    }
+
+    fn parse_operator(&mut self) -> Result<String, RepositoryError> {
+        let rest: String = self.chars[self.pos..].iter().take(2).collect();
+        for symbol in ["==", "!=", "<=", ">="] {
+            if rest == symbol {
+                self.pos += 2;
+                return Ok(symbol.to_string());
+            }
+        }
+
+        match self.peek() {
+            Some(c @ ('<' | '>')) => {
+                self.pos += 1;
+                Ok(c.to_string())
+            }
+            Some('=') => {
+                let start = self.pos;
+                self.pos += 1;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphabetic() || c == '-') {
+                    self.pos += 1;
+                }
+                if self.peek() != Some('=') || self.pos == start + 1 {
+                    self.pos = start;
+                    return Err(self.error("expected a comparison operator".to_string()));
+                }
+                self.pos += 1;
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+            _ => Err(self.error("expected a comparison operator".to_string())),
+        }
+    }
+
+    fn parse_argument(&mut self) -> Result<Vec<ConditionValue>, RepositoryError> {
+        if self.peek() != Some('(') {
+            return Ok(vec![self.parse_value()?]);
+        }
+
+        self.pos += 1;
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(values);
+                }
+                _ => return Err(self.error("expected ',' or ')' in value list".to_string())),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<ConditionValue, RepositoryError> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let start = self.pos;
+                self.pos += 1;
+                let mut value = String::new();
+                loop {
+                    match self.peek() {
+                        None => {
+                            self.pos = start;
+                            return Err(self.error("unterminated quoted value".to_string()));
+                        }
+                        Some('\\') => {
+                            self.pos += 1;
+                            match self.peek() {
+                                Some(c) => value.push(c),
+                                None => {
+                                    self.pos = start;
+                                    return Err(self.error("unterminated quoted value".to_string()));
+                                }
+                            }
+                        }
+                        Some(c) if c == quote => {
+                            self.pos += 1;
+                            return Ok(ConditionValue::String(value));
+                        }
+                        Some(c) => value.push(c),
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                let value = self.take_unreserved();
+                if value.is_empty() {
+                    return Err(self.error("expected a value".to_string()));
+                }
+                Ok(parse_value(&value))
+            }
+        }
+    }
+
+    fn eat_separator(&mut self, symbol: char, keyword: &str) -> bool {
+        let start = self.pos;
+        self.skip_whitespace();
+        if self.peek() == Some(symbol) {
+            self.pos += 1;
+            return true;
+        }
+
+        // Keywords must be surrounded by whitespace
+        if self.pos > start {
+            let end = self.pos + keyword.len();
+            let word: String = self.chars[self.pos..end.min(self.chars.len())]
+                .iter()
+                .collect();
+            if word == keyword && self.chars.get(end).is_some_and(|c| c.is_whitespace()) {
+                self.pos = end;
+                return true;
+            }
+        }
+
+        self.pos = start;
+        false
+    }
+
+    fn take_unreserved(&mut self) -> String {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if !c.is_whitespace() && !RESERVED.contains(&c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), RepositoryError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn error(&self, message: String) -> RepositoryError {
+        let found = match self.peek() {
+            Some(c) => format!("'{}'", c),
+            None => "end of input".to_string(),
+        };
+        RepositoryError::InvalidInput(format!(
+            "{} at position {} (found {})",
+            message, self.pos, found
+        ))
+    }
+}
+
+fn print_expr(expr: &FilterExpr) -> Result<String, RepositoryError> {
+    match expr {
+        FilterExpr::And(exprs) => print_group(exprs, ";", true),
+        FilterExpr::Or(exprs) => print_group(exprs, ",", false),
+        FilterExpr::Not(inner) => match inner.as_ref() {
+            FilterExpr::Leaf(condition) => {
+                let operator = match condition.operator {
+                    Operator::In => "=out=",
+                    Operator::NotIn => "=in=",
+                    Operator::Equal => "!=",
+                    Operator::NotEqual => "==",
+                    Operator::Like => "=notlike=",
+                    Operator::NotLike => "=like=",
+                    _ => return Err(unsupported_negation()),
+                };
+                print_comparison(condition, operator)
+            }
+            _ => Err(unsupported_negation()),
+        },
+        FilterExpr::Leaf(condition) => print_condition(condition),
+    }
+}
+
+fn print_group(
+    exprs: &[FilterExpr],
+    separator: &str,
+    is_and: bool,
+) -> Result<String, RepositoryError> {
+    if exprs.is_empty() {
+        return Err(RepositoryError::InvalidInput(
+            "empty groups cannot be expressed in RSQL".to_string(),
+        ));
+    }
+
+    let parts = exprs
+        .iter()
+        .map(|expr| {
+            let printed = print_expr(expr)?;
+            // Only OR groups inside AND need parentheses, since AND binds tighter
+            let needs_parens = is_and
+                && exprs.len() > 1
+                && matches!(expr, FilterExpr::Or(inner) if inner.len() > 1);
+            Ok(if needs_parens {
+                format!("({})", printed)
+            } else {
+                printed
+            })
+        })
+        .collect::<Result<Vec<_>, RepositoryError>>()?;
+    Ok(parts.join(separator))
+}
+
+fn print_condition(condition: &Condition) -> Result<String, RepositoryError> {
+    let operator = match condition.operator {
+        Operator::Equal => "==",
+        Operator::NotEqual => "!=",
+        Operator::GreaterThan => "=gt=",
+        Operator::GreaterThanOrEqual => "=ge=",
+        Operator::LessThan => "=lt=",
+        Operator::LessThanOrEqual => "=le=",
+        Operator::Like => "=like=",
+        Operator::NotLike => "=notlike=",
+        Operator::CaseSensitiveLike => "=cslike=",
+        Operator::StartsWith => "=startswith=",
+        Operator::EndsWith => "=endswith=",
+        Operator::Contains => "=contains=",
+        Operator::In => "=in=",
+        Operator::NotIn => "=out=",
+        Operator::Between => "=between=",
+        Operator::JsonContains
+        | Operator::JsonHasKey
+        | Operator::JsonHasAnyKey
+        | Operator::JsonHasAllKeys
+        | Operator::JsonPathExists
+        | Operator::ArrayOverlaps
+        | Operator::ArrayContains
+        | Operator::ArrayContainedBy
+        | Operator::EqualsAny
+        | Operator::TextSearch => {
+            return Err(RepositoryError::InvalidInput(format!(
+                "operator {:?} cannot be expressed in RSQL",
+                condition.operator
+            )));
+        }
+        Operator::IsNull => return Ok(format!("{}=isnull=true", condition.field)),
+        Operator::IsNotNull => return Ok(format!("{}=isnull=false", condition.field)),
+    };
+    print_comparison(condition, operator)
+}
+
+fn print_comparison(condition: &Condition, operator: &str) -> Result<String, RepositoryError> {
+    let argument = match (&condition.operator, &condition.value) {
+        (Operator::In | Operator::NotIn | Operator::Between, ConditionValue::List(values))
+            if !values.is_empty() =>
+        {
+            let values = values
+                .iter()
+                .map(|v| print_value(&condition.field, v))
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("({})", values.join(","))
+        }
+        (Operator::In | Operator::NotIn | Operator::Between, ConditionValue::List(_)) => {
+            return Err(RepositoryError::InvalidInput(format!(
+                "empty list for '{}' cannot be expressed in RSQL",
+                condition.field
+            )));
+        }
+        (_, value) => print_value(&condition.field, value)?,
+    };
+    Ok(format!("{}{}{}", condition.field, operator, argument))
+}
+
+fn print_value(field: &str, value: &ConditionValue) -> Result<String, RepositoryError> {
+    let text = value.to_text().ok_or_else(|| {
+        RepositoryError::InvalidInput(format!("value of '{}' cannot be expressed in RSQL", field))
+    })?;
+
+    // Strings are also quoted when they would read back as another type
+    let needs_quotes = text.is_empty()
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || RESERVED.contains(&c))
+        || (matches!(value, ConditionValue::String(_))
+            && !matches!(parse_value(&text), ConditionValue::String(_)));
+    if needs_quotes {
+        Ok(format!(
+            "\"{}\"",
+            text.replace('\\', "\\\\").replace('"', "\\\"")
+        ))
+    } else {
+        Ok(text)
+    }
+}
+
+fn unsupported_negation() -> RepositoryError {
+    RepositoryError::InvalidInput(
+        "NOT can only be expressed in RSQL around equality, LIKE and IN constraints".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_or_precedence() {
+        let criteria = parse("status==active;age=gt=18,role=in=(admin,owner)").unwrap();
+
+        assert_eq!(
+            criteria,
+            FilterCriteria::new().with_filter(FilterExpr::or([
+                FilterExpr::and([
+                    Condition::eq("status", "active".into()),
+                    Condition::gt("age", 18.into()),
+                ]),
+                FilterExpr::from(Condition::in_list(
+                    "role",
+                    vec!["admin".into(), "owner".into()]
+                )),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_top_level_and_into_conditions() {
+        let criteria = parse("status==active and (role==admin or role==owner)").unwrap();
+
+        assert_eq!(
+            criteria,
+            FilterCriteria::new()
+                .with_condition(Condition::eq("status", "active".into()))
+                .with_any([
+                    Condition::eq("role", "admin".into()),
+                    Condition::eq("role", "owner".into()),
+                ])
+        );
+    }
+
+    #[test]
+    fn test_parse_operators_and_values() {
+        let criteria = parse(
+            "a>1;b>=2.5;c<3;d<=4;e=ge=5;f=le=6;g=lt=7;h!=x;name=like='%Ada Love%';\
+             tag=out=(x,y);deleted=isnull=true;email=isnull=false;code==\"007\";ok==true;\
+             i=notlike=%x;j=cslike=Ada%;k=startswith=ab;l=endswith=yz;m=contains='50%';\
+             n=between=(1,10)",
+        )
+        .unwrap();
+
+        let operators: Vec<Operator> = criteria.conditions.iter().map(|c| c.operator).collect();
+        assert_eq!(
+            operators,
+            vec![
+                Operator::GreaterThan,
+                Operator::GreaterThanOrEqual,
+                Operator::LessThan,
+                Operator::LessThanOrEqual,
+                Operator::GreaterThanOrEqual,
+                Operator::LessThanOrEqual,
+                Operator::LessThan,
+                Operator::NotEqual,
+                Operator::Like,
+                Operator::NotIn,
+                Operator::IsNull,
+                Operator::IsNotNull,
+                Operator::Equal,
+                Operator::Equal,
+                Operator::NotLike,
+                Operator::CaseSensitiveLike,
+                Operator::StartsWith,
+                Operator::EndsWith,
+                Operator::Contains,
+                Operator::Between,
+            ]
+        );
+        assert_eq!(criteria.conditions[1].value, 2.5.into());
+        assert_eq!(criteria.conditions[8].value, "%Ada Love%".into());
+        assert_eq!(
+            criteria.conditions[9].value,
+            ConditionValue::List(vec!["x".into(), "y".into()])
+        );
+        assert_eq!(criteria.conditions[12].value, "007".into());
+        assert_eq!(criteria.conditions[13].value, true.into());
+        assert_eq!(criteria.conditions[18].value, "50%".into());
+        assert_eq!(
+            criteria.conditions[19].value,
+            ConditionValue::List(vec![1.into(), 10.into()])
+        );
+        assert!(criteria.filters.is_empty());
+    }
+
+    #[test]
+    fn test_errors_report_positions() {
+        let cases = [
+            ("status==", "expected a value at position 8"),
+            (
+                "status=foo=bar",
+                "unknown operator '=foo=' for selector 'status' at position 6",
+            ),
+            ("==active", "expected a selector at position 0"),
+            ("a==1;", "expected a selector at position 5"),
+            ("(a==1", "expected ')' at position 5"),
+            ("a==1)", "unexpected character ')' at position 4"),
+            (
+                "a=in=(1,2",
+                "expected ',' or ')' in value list at position 9",
+            ),
+            ("a=='open", "unterminated quoted value at position 3"),
+            ("a~1", "expected a comparison operator at position 1"),
+            (
+                "a=gt=(1,2)",
+                "operator '=gt=' takes a single value at position 5",
+            ),
+            (
+                "a=isnull=yes",
+                "operator '=isnull=' takes true or false at position 9",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            match parse(input) {
+                Err(RepositoryError::InvalidInput(message)) => {
+                    assert!(message.starts_with(expected), "{}: {}", input, message)
+                }
+                other => panic!("expected an error for '{}', got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let inputs = [
+            "status==active;age=gt=18,role=in=(admin,owner)",
+            "status==active;deleted=isnull=true;(role==admin,role==owner)",
+            "name==\"Ada Lovelace\";code==\"7\";note==\"say \\\"hi\\\"\";score=le=9.0",
+            "email=isnull=false;name=like=%a%;tag=out=(x,y)",
+            "price=between=(1,9.5);name=notlike=%bot%;code=cslike=AB_%;email=endswith=.org;\
+             title=contains=\"50% off\";sku=startswith=A-",
+        ];
+
+        for input in inputs {
+            let criteria = parse(input).unwrap();
+            let printed = serialize(&criteria).unwrap();
+            assert_eq!(printed, input);
+            assert_eq!(parse(&printed).unwrap(), criteria);
+        }
+    }
+
+    #[test]
+    fn test_serialize_empty_and_unsupported() {
+        assert_eq!(serialize(&FilterCriteria::new()).unwrap(), "");
+        assert_eq!(parse("  ").unwrap(), FilterCriteria::new());
+
+        let negated_group = FilterCriteria::new().with_not(FilterExpr::or([
+            Condition::eq("a", 1.into()),
+            Condition::eq("b", 2.into()),
+        ]));
+        let null_value =
+            FilterCriteria::new().with_condition(Condition::eq("a", ConditionValue::Null));
+
+        assert!(serialize(&negated_group).is_err());
+        assert!(serialize(&null_value).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Full-text search
+//!
+//! A [`TextSearch`] is a query for PostgreSQL's text search. It is used as the
+//! value of an [`Operator::TextSearch`](crate::Operator::TextSearch)
+//! condition, which the SQLx adapter renders as
+//! `to_tsvector(field) @@ websearch_to_tsquery(query)` (with the function of
+//! the search's [`SearchMode`]), and as the rank of a
+//! [`SortOrder`](crate::SortOrder), which orders by `ts_rank`.
+//!
+//! ```rust
+//! use wyvern::{Condition, FilterCriteria, SortOrder, TextSearch};
+//!
+//! let search = TextSearch::websearch("\"async runtime\" -python").with_config("english");
+//! let criteria = FilterCriteria::new()
+//!     .with_condition(Condition::text_search("body", search.clone()))
+//!     .with_sort(SortOrder::by_rank("body", search));
+//! ```
+//!
+//! A field that already holds a `tsvector`, such as a generated column kept
+//! under a GIN index, is matched as-is when the search is built with
+//! [`TextSearch::on_tsvector`].
+//!
+//! The in-memory repository approximates text search by matching lowercase
+//! words, without the stemming and stop words of a text search configuration.
+
+/// How the query text of a [`TextSearch`] is turned into a `tsquery`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SearchMode {
+    /// Every word must match (`plainto_tsquery`)
+    Plain,
+    /// The words must match in sequence (`phraseto_tsquery`)
+    Phrase,
+    /// Search engine syntax: `"quoted phrases"`, `or` and `-excluded` words
+    /// (`websearch_to_tsquery`)
+    Websearch,
+}
+
+impl SearchMode {
+    /// Returns the Postgres function turning text into a `tsquery`.
+    #[cfg_attr(not(feature = "sqlx"), allow(dead_code))]
+    pub(crate) fn function(&self) -> &'static str {
+        match self {
+            SearchMode::Plain => "plainto_tsquery",
+            SearchMode::Phrase => "phraseto_tsquery",
+            SearchMode::Websearch => "websearch_to_tsquery",
+        }
+    }
+}
+
+/// A full-text search query.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct TextSearch {
+    /// The text of the query
+    pub query: String,
+    /// How the text is parsed
+    pub mode: SearchMode,
+    /// The text search configuration (such as `english`); the database's
+    /// `default_text_search_config` when `None`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub config: Option<String>,
+    /// Whether the searched field is a `tsvector` column, used as-is instead
+    /// of through `to_tsvector`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub tsvector: bool,
+}
+
+impl TextSearch {
+    /// Creates a search in the given mode.
+    pub fn new(query: impl Into<String>, mode: SearchMode) -> Self {
+        Self {
+            query: query.into(),
+            mode,
+            config: None,
+            tsvector: false,
+        }
+    }
+
+    /// Creates a search matching every word of `query`.
+    pub fn plain(query: impl Into<String>) -> Self {
+        Self::new(query, SearchMode::Plain)
+    }
+
+    /// Creates a search matching the words of `query` in sequence.
+    pub fn phrase(query: impl Into<String>) -> Self {
+        Self::new(query, SearchMode::Phrase)
+    }
+
+    /// Creates a search using search engine syntax.
+    pub fn websearch(query: impl Into<String>) -> Self {
+        Self::new(query, SearchMode::Websearch)
+    }
+
+    /// Sets the text search configuration.
+    pub fn with_config(mut self, config: impl Into<String>) -> Self {
+        self.config = Some(config.into());
+        self
+    }
+
+    /// Matches the field as a `tsvector` column instead of converting its text.
+    pub fn on_tsvector(mut self) -> Self {
+        self.tsvector = true;
+        self
+    }
+}
+
+/// Serializes a search condition value as `{"search": {...}}`
+///
+/// The key keeps search values apart from the JSON objects that the untagged
+/// [`ConditionValue`](crate::ConditionValue) also accepts.
+#[cfg(feature = "serde")]
+pub(crate) mod tagged {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::TextSearch;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Tagged<T> {
+        search: T,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        search: &TextSearch,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Tagged { search }.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TextSearch, D::Error> {
+        Tagged::deserialize(deserializer).map(|tagged| tagged.search)
+    }
+}
+
+/// A sequence of words that must (or, when negated, must not) appear
+struct Term {
+    words: Vec<String>,
+    negated: bool,
+}
+
+/// Splits text into lowercase words
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Parses the query into alternatives of terms that must all hold
+fn parse(search: &TextSearch) -> Vec<Vec<Term>> {
+    let term = |text: &str, negated| Term {
+        words: words(text),
+        negated,
+    };
+
+    let groups = match search.mode {
+        SearchMode::Plain => vec![
+            words(&search.query)
+                .into_iter()
+                .map(|word| Term {
+                    words: vec![word],
+                    negated: false,
+                })
+                .collect(),
+        ],
+        SearchMode::Phrase => vec![vec![term(&search.query, false)]],
+        SearchMode::Websearch => {
+            let mut groups = vec![Vec::new()];
+            // Odd-numbered pieces between double quotes are phrases
+            for (i, piece) in search.query.split('"').enumerate() {
+                if i % 2 == 1 {
+                    groups.last_mut().unwrap().push(term(piece, false));
+                    continue;
+                }
+                for token in piece.split_whitespace() {
+                    if token.eq_ignore_ascii_case("or") {
+                        groups.push(Vec::new());
+                    } else if let Some(excluded) = token.strip_prefix('-') {
+                        groups.last_mut().unwrap().push(term(excluded, true));
+                    } else {
+                        groups.last_mut().unwrap().push(term(token, false));
+                    }
+                }
+            }
+            groups
+        }
+    };
+
+    groups
+        .into_iter()
+        .map(|group: Vec<Term>| group.into_iter().filter(|t| !t.words.is_empty()).collect())
+        .filter(|group: &Vec<Term>| !group.is_empty())
+        .collect()
+}
+
+/// Counts the occurrences of `phrase` in `document`
+fn occurrences(document: &[String], phrase: &[String]) -> usize {
+    document
+        .windows(phrase.len())
+        .filter(|w| *w == phrase)
+        .count()
+}
+
+/// Returns whether the document text matches the search.
+///
+/// Like PostgreSQL, a query without any words matches nothing.
+pub(crate) fn matches(document: &str, search: &TextSearch) -> bool {
+    let document = words(document);
+    parse(search).iter().any(|group| {
+        group
+            .iter()
+            .all(|term| (occurrences(&document, &term.words) > 0) != term.negated)
+    })
+}
+
+/// Ranks the document by how often the wanted terms occur in it.
+pub(crate) fn rank(document: &str, search: &TextSearch) -> f64 {
+    let document = words(document);
+    parse(search)
+        .iter()
+        .flatten()
+        .filter(|term| !term.negated)
+        .map(|term| occurrences(&document, &term.words))
+        .sum::<usize>() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes() {
+        let document = "Fearless concurrency with the Tokio async runtime";
+
+        assert!(matches(document, &TextSearch::plain("runtime ASYNC")));
+        assert!(!matches(document, &TextSearch::phrase("runtime async")));
+        assert!(matches(document, &TextSearch::phrase("tokio async")));
+        assert!(matches(
+            document,
+            &TextSearch::websearch("\"async runtime\" -python")
+        ));
+        assert!(!matches(document, &TextSearch::websearch("async -tokio")));
+        assert!(matches(document, &TextSearch::websearch("python or tokio")));
+        assert!(!matches(document, &TextSearch::plain(" - ")));
+    }
+
+    #[test]
+    fn test_rank_counts_occurrences() {
+        let search = TextSearch::plain("rust");
+
+        assert_eq!(rank("Rust, rust and more rust", &search), 3.0);
+        assert_eq!(rank("Go", &search), 0.0);
+    }
+}
@@ -0,0 +1,623 @@
+//! Schema-based validation of filter criteria
+//!
+//! When filters come from API clients, a [`FilterSchema`] restricts which fields
+//! may be filtered, sorted on or selected, which operators each field allows,
+//! which kinds of values it accepts, which relations may be included, and how
+//! large `limit` and `offset` may be.
+//!
+//! ```rust
+//! use wyvern::{Condition, FieldRule, FilterCriteria, FilterSchema, Operator, ValueKind};
+//!
+//! let schema = FilterSchema::new()
+//!     .field(
+//!         "status",
+//!         FieldRule::new()
+//!             .operators([Operator::Equal, Operator::In])
+//!             .value_kinds([ValueKind::String]),
+//!     )
+//!     .field("created_at", FieldRule::new().sortable())
+//!     .max_limit(100);
+//!
+//! let criteria = FilterCriteria::new()
+//!     .with_condition(Condition::eq("status", "active".into()))
+//!     .with_limit(50);
+//!
+//! assert!(schema.validate(&criteria).is_ok());
+//! ```
+//!
+//! Query strings, RSQL and OData only type values by their text, so a zip code
+//! of `12345` arrives as an integer. [`FilterSchema::coerce`] retypes such
+//! values into the kinds their fields accept before validating:
+//!
+//! ```rust
+//! use wyvern::{query_string, FieldRule, FilterSchema, ValueKind};
+//!
+//! let schema = FilterSchema::new()
+//!     .field("zip", FieldRule::new().value_kinds([ValueKind::String]));
+//!
+//! let (criteria, _) = query_string::parse("zip=12345").unwrap();
+//! let criteria = schema.coerce(criteria);
+//!
+//! assert_eq!(criteria.conditions[0].value, "12345".into());
+//! assert!(schema.validate(&criteria).is_ok());
+//! ```
+
+use std::collections::HashMap;
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+use time::format_description::well_known::{Iso8601, Rfc3339};
+
+use crate::aggregate::AggregateQuery;
+use crate::error::RepositoryError;
+use crate::query::{
+    Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, SortOrder, ValueKind,
+};
+
+/// Rules for a single field of a [`FilterSchema`].
+///
+/// By default a field is filterable with any operator and value kind, and is
+/// not sortable.
+#[derive(Debug, Clone)]
+pub struct FieldRule {
+    filterable: bool,
+    sortable: bool,
+    operators: Option<Vec<Operator>>,
+    value_kinds: Option<Vec<ValueKind>>,
+}
+
+impl FieldRule {
+    /// Creates a rule for a filterable, non-sortable field.
+    pub fn new() -> Self {
+        Self {
+            filterable: true,
+            sortable: false,
+            operators: None,
+            value_kinds: None,
+        }
+    }
+
+    /// Allows sorting on this field.
+    pub fn sortable(mut self) -> Self {
+        self.sortable = true;
+        self
+    }
+
+    /// Disallows filtering on this field, e.g. for sort-only fields.
+    pub fn not_filterable(mut self) -> Self {
+        self.filterable = false;
+        self
+    }
+
+    /// Restricts the operators allowed on this field.
+    pub fn operators(mut self, operators: impl IntoIterator<Item = Operator>) -> Self {
+        self.operators = Some(operators.into_iter().collect());
+        self
+    }
+
+    /// Restricts the value kinds accepted by this field.
+    ///
+    /// Elements of a `List` value are checked individually, so a field accepting
+    /// `Integer` also accepts a list of integers for `In`.
+    pub fn value_kinds(mut self, kinds: impl IntoIterator<Item = ValueKind>) -> Self {
+        self.value_kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    fn check_value(&self, field: &str, value: &ConditionValue, violations: &mut Vec<String>) {
+        let Some(kinds) = &self.value_kinds else {
+            return;
+        };
+
+        match value {
+            ConditionValue::List(values) => values
+                .iter()
+                .for_each(|v| self.check_value(field, v, violations)),
+            value if !kinds.contains(&value.kind()) => violations.push(format!(
+                "field '{}' does not accept {:?} values",
+                field,
+                value.kind()
+            )),
+            _ => {}
+        }
+    }
+
+    /// Retypes a value, or each element of a list, into an accepted kind
+    fn coerce_value(&self, value: &mut ConditionValue) {
+        let Some(kinds) = &self.value_kinds else {
+            return;
+        };
+
+        match value {
+            ConditionValue::List(values) => values.iter_mut().for_each(|v| self.coerce_value(v)),
+            value if !kinds.contains(&value.kind()) => {
+                if let Some(coerced) = kinds.iter().find_map(|kind| convert(value, *kind)) {
+                    *value = coerced;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads a string, number or boolean as another kind, if its text is one
+fn convert(value: &ConditionValue, kind: ValueKind) -> Option<ConditionValue> {
+    let text = match value {
+        ConditionValue::String(_)
+        | ConditionValue::Integer(_)
+        | ConditionValue::Float(_)
+        | ConditionValue::Boolean(_) => value.to_text()?,
+        _ => return None,
+    };
+
+    match kind {
+        ValueKind::String => Some(ConditionValue::String(text)),
+        ValueKind::Integer => text.parse().ok().map(ConditionValue::Integer),
+        ValueKind::Float => text
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(ConditionValue::Float),
+        ValueKind::Boolean => text.parse().ok().map(ConditionValue::Boolean),
+        #[cfg(feature = "uuid")]
+        ValueKind::Uuid => text.parse().ok().map(ConditionValue::Uuid),
+        #[cfg(feature = "chrono")]
+        ValueKind::Date => text.parse().ok().map(ConditionValue::NaiveDate),
+        #[cfg(feature = "chrono")]
+        ValueKind::Time => text.parse().ok().map(ConditionValue::NaiveTime),
+        #[cfg(feature = "chrono")]
+        ValueKind::Timestamp => text.parse().ok().map(ConditionValue::NaiveDateTime),
+        #[cfg(feature = "chrono")]
+        ValueKind::TimestampTz => text.parse().ok().map(ConditionValue::DateTime),
+        // Like decoding, `chrono` is preferred when both features are enabled
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        ValueKind::Date => time::Date::parse(&text, &Iso8601::DATE)
+            .ok()
+            .map(ConditionValue::Date),
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        ValueKind::Time => time::Time::parse(&text, &Iso8601::TIME)
+            .ok()
+            .map(ConditionValue::Time),
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        ValueKind::Timestamp => time::PrimitiveDateTime::parse(&text, &Iso8601::DATE_TIME)
+            .ok()
+            .map(ConditionValue::PrimitiveDateTime),
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        ValueKind::TimestampTz => time::OffsetDateTime::parse(&text, &Rfc3339)
+            .ok()
+            .map(ConditionValue::OffsetDateTime),
+        #[cfg(feature = "rust_decimal")]
+        ValueKind::Decimal => text.parse().ok().map(ConditionValue::Decimal),
+        _ => None,
+    }
+}
+
+impl Default for FieldRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A whitelist of fields and bounds that `FilterCriteria` must satisfy.
+///
+/// Fields that are not declared can neither be filtered, sorted on nor
+/// selected; every declared field may be selected. Relations can only be
+/// included once allowed with [`allow_include`](Self::allow_include).
+#[derive(Debug, Clone, Default)]
+pub struct FilterSchema {
+    fields: HashMap<String, FieldRule>,
+    includes: Vec<String>,
+    max_limit: Option<i64>,
+    max_offset: Option<i64>,
+}
+
+impl FilterSchema {
+    /// Creates an empty schema that rejects every field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a field and its rules.
+    pub fn field(mut self, name: impl Into<String>, rule: FieldRule) -> Self {
+        self.fields.insert(name.into(), rule);
+        self
+    }
+
+    /// Allows loading the named relation through the criteria's `include`.
+    pub fn allow_include(mut self, relation: impl Into<String>) -> Self {
+        self.includes.push(relation.into());
+        self
+    }
+
+    /// Sets the largest accepted `limit`.
+    pub fn max_limit(mut self, max_limit: i64) -> Self {
+        self.max_limit = Some(max_limit);
+        self
+    }
+
+    /// Sets the largest accepted `offset`.
+    pub fn max_offset(mut self, max_offset: i64) -> Self {
+        self.max_offset = Some(max_offset);
+        self
+    }
+
+    /// Retypes condition values into the kinds their fields accept.
+    ///
+    /// Strings, numbers and booleans whose kind a declared field does not
+    /// accept are read as the first accepted kind their text parses as, so
+    /// `"42"` becomes an integer for an integer field and `12345` a string for
+    /// a string field. Dates, times and timestamps are read from ISO 8601 text
+    /// with the `chrono` feature, UUIDs with `uuid` and decimals with
+    /// `rust_decimal`. Values that cannot be converted are left for
+    /// [`validate`](Self::validate) to report.
+    pub fn coerce(&self, mut criteria: FilterCriteria) -> FilterCriteria {
+        for condition in &mut criteria.conditions {
+            self.coerce_condition(condition);
+        }
+        for filter in &mut criteria.filters {
+            self.coerce_expr(filter);
+        }
+        criteria
+    }
+
+    /// Validates the criteria, reporting every violation in a single
+    /// `RepositoryError::InvalidInput`.
+    pub fn validate(&self, criteria: &FilterCriteria) -> Result<(), RepositoryError> {
+        let violations = self.violations(criteria);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(RepositoryError::InvalidInput(violations.join("; ")))
+        }
+    }
+
+    /// Returns a description of every way the criteria violates this schema.
+    pub fn violations(&self, criteria: &FilterCriteria) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        for condition in criteria.expression().conditions() {
+            self.check_condition(condition, &mut violations);
+        }
+
+        for sort in &criteria.sort {
+            self.check_sort(sort, &mut violations);
+        }
+
+        for projection in &criteria.projection {
+            if !self.fields.contains_key(&projection.field) {
+                violations.push(format!("unknown projection field '{}'", projection.field));
+            }
+        }
+
+        for relation in &criteria.include {
+            if !self.includes.contains(relation) {
+                violations.push(format!("relation '{}' cannot be included", relation));
+            }
+        }
+
+        self.check_bounds(criteria, &mut violations);
+        violations
+    }
+
+    /// Validates an aggregation query like [`validate`](Self::validate).
+    pub fn validate_aggregate(&self, query: &AggregateQuery) -> Result<(), RepositoryError> {
+        let violations = self.aggregate_violations(query);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(RepositoryError::InvalidInput(violations.join("; ")))
+        }
+    }
+
+    /// Returns a description of every way an aggregation query violates this
+    /// schema.
+    ///
+    /// Group and aggregated fields must be declared. `having` conditions and
+    /// sorts on aggregate aliases are accepted; on group fields they follow
+    /// the field's rules like any filter or sort.
+    pub fn aggregate_violations(&self, query: &AggregateQuery) -> Vec<String> {
+        let mut violations = Vec::new();
+        let is_alias = |field: &str| query.aggregates.iter().any(|a| a.alias == field);
+
+        for condition in query.criteria.expression().conditions() {
+            self.check_condition(condition, &mut violations);
+        }
+
+        for field in &query.group_by {
+            if !self.fields.contains_key(field) {
+                violations.push(format!("unknown group field '{}'", field));
+            }
+        }
+
+        for field in query.aggregates.iter().filter_map(|a| a.field.as_ref()) {
+            if !self.fields.contains_key(field) {
+                violations.push(format!("unknown aggregate field '{}'", field));
+            }
+        }
+
+        for condition in FilterExpr::And(query.having.clone()).conditions() {
+            if !is_alias(&condition.field) {
+                self.check_condition(condition, &mut violations);
+            }
+        }
+
+        for sort in &query.criteria.sort {
+            if !is_alias(&sort.field) {
+                self.check_sort(sort, &mut violations);
+            }
+        }
+
+        self.check_bounds(&query.criteria, &mut violations);
+        violations
+    }
+
+    fn check_sort(&self, sort: &SortOrder, violations: &mut Vec<String>) {
+        match self.fields.get(&sort.field) {
+            Some(rule) if rule.sortable => {}
+            Some(_) => violations.push(format!("field '{}' is not sortable", sort.field)),
+            None => violations.push(format!("unknown sort field '{}'", sort.field)),
+        }
+    }
+
+    fn check_bounds(&self, criteria: &FilterCriteria, violations: &mut Vec<String>) {
+        if let Some(limit) = criteria.limit {
+            if limit < 0 {
+                violations.push(format!("limit {} must not be negative", limit));
+            } else if let Some(max) = self.max_limit.filter(|max| limit > *max) {
+                violations.push(format!("limit {} exceeds the maximum of {}", limit, max));
+            }
+        }
+
+        if let Some(offset) = criteria.offset {
+            if offset < 0 {
+                violations.push(format!("offset {} must not be negative", offset));
+            } else if let Some(max) = self.max_offset.filter(|max| offset > *max) {
+                violations.push(format!("offset {} exceeds the maximum of {}", offset, max));
+            }
+        }
+    }
+
+    fn coerce_expr(&self, expr: &mut FilterExpr) {
+        match expr {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => {
+                exprs.iter_mut().for_each(|e| self.coerce_expr(e))
+            }
+            FilterExpr::Not(expr) => self.coerce_expr(expr),
+            FilterExpr::Leaf(condition) => self.coerce_condition(condition),
+        }
+    }
+
+    fn coerce_condition(&self, condition: &mut Condition) {
+        if let Some(rule) = self.fields.get(&condition.field)
+            && !matches!(condition.operator, Operator::IsNull | Operator::IsNotNull)
+        {
+            rule.coerce_value(&mut condition.value);
+        }
+    }
+
+    fn check_condition(&self, condition: &Condition, violations: &mut Vec<String>) {
+        let field = &condition.field;
+        let Some(rule) = self.fields.get(field) else {
+            violations.push(format!("unknown filter field '{}'", field));
+            return;
+        };
+
+        if !rule.filterable {
+            violations.push(format!("field '{}' is not filterable", field));
+            return;
+        }
+
+        if let Some(operators) = &rule.operators
+            && !operators.contains(&condition.operator)
+        {
+            violations.push(format!(
+                "operator {:?} is not allowed on field '{}'",
+                condition.operator, field
+            ));
+        }
+
+        if !matches!(condition.operator, Operator::IsNull | Operator::IsNotNull) {
+            rule.check_value(field, &condition.value, violations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Aggregate;
+
+    fn schema() -> FilterSchema {
+        FilterSchema::new()
+            .field(
+                "status",
+                FieldRule::new()
+                    .operators([Operator::Equal, Operator::In])
+                    .value_kinds([ValueKind::String]),
+            )
+            .field(
+                "age",
+                FieldRule::new()
+                    .value_kinds([ValueKind::Integer])
+                    .sortable(),
+            )
+            .field("created_at", FieldRule::new().not_filterable().sortable())
+            .allow_include("team")
+            .max_limit(100)
+    }
+
+    #[test]
+    fn test_valid_criteria() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::in_list("status", vec!["a".into(), "b".into()]))
+            .with_condition(Condition::gt("age", 18.into()))
+            .with_condition(Condition::new(
+                "age",
+                Operator::IsNull,
+                ConditionValue::Null,
+            ))
+            .with_sort(SortOrder::desc("created_at"))
+            .with_include("team")
+            .with_limit(100)
+            .with_offset(0);
+
+        assert!(schema().validate(&criteria).is_ok());
+    }
+
+    #[test]
+    fn test_reports_every_violation() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("password", "x".into()))
+            .with_condition(Condition::gt("status", "a".into()))
+            .with_condition(Condition::in_list("age", vec![1.into(), "two".into()]))
+            .with_condition(Condition::eq("created_at", 1.into()))
+            .with_sort(SortOrder::asc("status"))
+            .with_sort(SortOrder::asc("secret"))
+            .with_columns(["status", "password_hash"])
+            .with_include("credentials")
+            .with_limit(500)
+            .with_offset(-1);
+
+        let violations = schema().violations(&criteria);
+
+        assert_eq!(
+            violations,
+            vec![
+                "unknown filter field 'password'",
+                "operator GreaterThan is not allowed on field 'status'",
+                "field 'age' does not accept String values",
+                "field 'created_at' is not filterable",
+                "field 'status' is not sortable",
+                "unknown sort field 'secret'",
+                "unknown projection field 'password_hash'",
+                "relation 'credentials' cannot be included",
+                "limit 500 exceeds the maximum of 100",
+                "offset -1 must not be negative",
+            ]
+        );
+        assert!(matches!(
+            schema().validate(&criteria),
+            Err(RepositoryError::InvalidInput(msg)) if msg.contains("; ")
+        ));
+    }
+
+    #[test]
+    fn test_coerce_retypes_values_by_field_kinds() {
+        let schema = schema().field("zip", FieldRule::new().value_kinds([ValueKind::String]));
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("zip", 2134.into()))
+            .with_condition(Condition::in_list("age", vec!["18".into(), 21.into()]))
+            .with_filter(FilterExpr::not(Condition::gt("age", "x".into())))
+            .with_filter(FilterExpr::from(Condition::eq("other", "1".into())));
+
+        let coerced = schema.coerce(criteria);
+
+        assert_eq!(coerced.conditions[0].value, "2134".into());
+        assert_eq!(
+            coerced.conditions[1].value,
+            ConditionValue::List(vec![18.into(), 21.into()])
+        );
+        // Unconvertible values and undeclared fields are left to validation
+        assert_eq!(
+            coerced.filters[0],
+            FilterExpr::not(Condition::gt("age", "x".into()))
+        );
+        assert_eq!(
+            coerced.filters[1],
+            FilterExpr::from(Condition::eq("other", "1".into()))
+        );
+    }
+
+    #[cfg(all(feature = "time", not(feature = "chrono")))]
+    #[test]
+    fn test_coerce_reads_time_values() {
+        use time::{Date, Month, PrimitiveDateTime, Time, UtcOffset};
+
+        let schema = FilterSchema::new()
+            .field("born", FieldRule::new().value_kinds([ValueKind::Date]))
+            .field("opens", FieldRule::new().value_kinds([ValueKind::Time]))
+            .field("seen", FieldRule::new().value_kinds([ValueKind::Timestamp]))
+            .field(
+                "created",
+                FieldRule::new().value_kinds([ValueKind::TimestampTz]),
+            );
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::lt("born", "2000-01-01".into()))
+            .with_condition(Condition::eq("opens", "09:30:00".into()))
+            .with_condition(Condition::gt("seen", "2024-01-01T12:00:00".into()))
+            .with_condition(Condition::gt("created", "2024-01-01T12:00:00+02:00".into()))
+            .with_condition(Condition::eq("born", "yesterday".into()));
+
+        let coerced = schema.coerce(criteria);
+
+        let date = Date::from_calendar_date(2000, Month::January, 1).unwrap();
+        let noon = PrimitiveDateTime::new(
+            Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+            Time::from_hms(12, 0, 0).unwrap(),
+        );
+        assert_eq!(coerced.conditions[0].value, date.into());
+        assert_eq!(
+            coerced.conditions[1].value,
+            Time::from_hms(9, 30, 0).unwrap().into()
+        );
+        assert_eq!(coerced.conditions[2].value, noon.into());
+        assert_eq!(
+            coerced.conditions[3].value,
+            noon.assume_offset(UtcOffset::from_hms(2, 0, 0).unwrap())
+                .into()
+        );
+        assert_eq!(coerced.conditions[4].value, "yesterday".into());
+        assert!(schema.validate(&coerced).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_violations() {
+        let valid = AggregateQuery::new(
+            FilterCriteria::new()
+                .with_condition(Condition::eq("status", "active".into()))
+                .with_sort(SortOrder::desc("total"))
+                .with_sort(SortOrder::asc("age"))
+                .with_limit(10),
+        )
+        .with_group_by("age")
+        .with_aggregate(Aggregate::count_all("orders"))
+        .with_aggregate(Aggregate::sum("age", "total"))
+        .with_having(Condition::gt("orders", 1.into()))
+        .with_having(Condition::lt("age", 65.into()));
+        assert!(schema().validate_aggregate(&valid).is_ok());
+
+        let invalid = AggregateQuery::new(
+            FilterCriteria::new()
+                .with_condition(Condition::eq("password", "x".into()))
+                .with_sort(SortOrder::asc("status"))
+                .with_limit(500),
+        )
+        .with_group_by("department")
+        .with_aggregate(Aggregate::max("salary", "top"))
+        .with_having(Condition::eq("status", 1.into()));
+
+        assert_eq!(
+            schema().aggregate_violations(&invalid),
+            vec![
+                "unknown filter field 'password'",
+                "unknown group field 'department'",
+                "unknown aggregate field 'salary'",
+                "field 'status' does not accept Integer values",
+                "field 'status' is not sortable",
+                "limit 500 exceeds the maximum of 100",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checks_nested_filters() {
+        let criteria = FilterCriteria::new().with_filter(FilterExpr::or([
+            FilterExpr::from(Condition::eq("status", "active".into())),
+            FilterExpr::not(Condition::eq("role", "admin".into())),
+        ]));
+
+        assert_eq!(
+            schema().violations(&criteria),
+            vec!["unknown filter field 'role'"]
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Stable JSON wire format for filter criteria
+//!
+//! With the `serde` feature, [`FilterCriteria`] and its parts serialize to the
+//! JSON shape below. The shape is versioned: documents carry a `version`
+//! field, which defaults to the current [`FORMAT_VERSION`] when omitted, and
+//! documents with any other version are rejected. Unknown fields are rejected
+//! as well, so a misspelled key fails loudly instead of silently matching
+//! everything.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "conditions": [
+//!     { "field": "age", "op": "gt", "value": 18 },
+//!     { "field": "deleted_at", "op": "is_null" }
+//!   ],
+//!   "filters": [
+//!     { "or": [
+//!       { "field": "status", "op": "eq", "value": "active" },
+//!       { "and": [
+//!         { "field": "role", "op": "in", "value": ["admin", "owner"] },
+//!         { "not": { "field": "banned", "op": "eq", "value": true } }
+//!       ] }
+//!     ] }
+//!   ],
+//!   "sort": [{ "field": "created_at", "direction": "desc" }],
+//!   "limit": 20,
+//!   "offset": 40,
+//!   "projection": [{ "field": "id" }, { "field": "email", "alias": "contact" }],
+//!   "include": ["customer", "lines"]
+//! }
+//! ```
+//!
+//! - `conditions`, `filters`, `sort`, `projection` and `include` default to
+//!   empty (an empty projection selects every column); `limit` and `offset`
+//!   are omitted when unset.
+//! - A projection item has a `field` and an optional `alias`.
+//! - `include` lists the names of relations to load with the results.
+//! - `op` is one of `eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `like`, `not_like`,
+//!   `case_sensitive_like`, `starts_with`, `ends_with`, `contains`, `in`,
+//!   `not_in`, `between`, `json_contains`, `json_has_key`, `json_has_any_key`,
+//!   `json_has_all_keys`, `json_path_exists`, `array_overlaps`,
+//!   `array_contains`, `array_contained_by`, `equals_any`, `text_search`,
+//!   `is_null`, `is_not_null`.
+//!   `value` defaults to `null`; `between` takes a `[low, high]` array and
+//!   `text_search` a search object wrapped as `{"search": {...}}`.
+//! - A search object has a `query` string, a `mode` of `plain`, `phrase` or
+//!   `websearch`, an optional `config` such as `"english"`, and an optional
+//!   `tsvector` flag for fields that are `tsvector` columns.
+//! - Values are plain JSON: strings, integers, floats, booleans, arrays and
+//!   `null`. Floats that are not finite cannot be represented. UUIDs, dates,
+//!   times and decimals are written as strings and read back as strings; JSON
+//!   objects are read as [`ConditionValue::Json`](crate::ConditionValue) when
+//!   the `json` feature is enabled.
+//! - A filter is either a condition or a single-key object `and` / `or`
+//!   (arrays of filters) or `not` (one filter).
+//! - `direction` is `asc` or `desc`. A sort with a `rank` search object (not
+//!   wrapped) orders by the `ts_rank` of the field against the search.
+//!
+//! [`Pagination`](crate::Pagination) serializes as `{"page": 2, "per_page": 50}`
+//! and [`Page`](crate::Page) as `{"items": [...], "page": 2, "per_page": 50,
+//! "total_items": 120, "total_pages": 3}`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::query::{Condition, FilterCriteria, FilterExpr, Projection, SortOrder};
+
+/// The current version of the wire format.
+pub const FORMAT_VERSION: u32 = 1;
+
+fn current_version() -> u32 {
+    FORMAT_VERSION
+}
+
+/// Serialized form of `FilterCriteria`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FilterCriteriaWire {
+    #[serde(default = "current_version")]
+    version: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    conditions: Vec<Condition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    filters: Vec<FilterExpr>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sort: Vec<SortOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    projection: Vec<Projection>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    include: Vec<String>,
+}
+
+impl From<FilterCriteria> for FilterCriteriaWire {
+    fn from(criteria: FilterCriteria) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            conditions: criteria.conditions,
+            filters: criteria.filters,
+            sort: criteria.sort,
+            limit: criteria.limit,
+            offset: criteria.offset,
+            projection: criteria.projection,
+            include: criteria.include,
+        }
+    }
+}
+
+impl TryFrom<FilterCriteriaWire> for FilterCriteria {
+    type Error = String;
+
+    fn try_from(wire: FilterCriteriaWire) -> Result<Self, Self::Error> {
+        if wire.version != FORMAT_VERSION {
+            return Err(format!(
+                "unsupported filter format version {} (expected {})",
+                wire.version, FORMAT_VERSION
+            ));
+        }
+
+        Ok(Self {
+            conditions: wire.conditions,
+            filters: wire.filters,
+            sort: wire.sort,
+            limit: wire.limit,
+            offset: wire.offset,
+            projection: wire.projection,
+            include: wire.include,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        Condition, ConditionValue, FilterCriteria, FilterExpr, Operator, Page, Pagination,
+        Projection, SortOrder, TextSearch,
+    };
+
+    fn criteria() -> FilterCriteria {
+        FilterCriteria::new()
+            .with_condition(Condition::gt("age", 18.into()))
+            .with_condition(Condition::new(
+                "score",
+                Operator::LessThanOrEqual,
+                9.5.into(),
+            ))
+            .with_condition(Condition::new(
+                "deleted_at",
+                Operator::IsNull,
+                ConditionValue::Null,
+            ))
+            .with_filter(FilterExpr::or([
+                FilterExpr::from(Condition::eq("status", "active".into())),
+                FilterExpr::and([
+                    FilterExpr::from(Condition::in_list(
+                        "role",
+                        vec!["admin".into(), "owner".into()],
+                    )),
+                    FilterExpr::not(Condition::eq("banned", true.into())),
+                ]),
+            ]))
+            .with_sort(SortOrder::desc("created_at"))
+            .with_limit(20)
+            .with_offset(40)
+            .with_columns(["id"])
+            .with_projection(Projection::new("email").with_alias("contact"))
+            .with_include("customer")
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let value = serde_json::to_value(criteria()).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "version": 1,
+                "conditions": [
+                    { "field": "age", "op": "gt", "value": 18 },
+                    { "field": "score", "op": "lte", "value": 9.5 },
+                    { "field": "deleted_at", "op": "is_null", "value": null }
+                ],
+                "filters": [
+                    { "or": [
+                        { "field": "status", "op": "eq", "value": "active" },
+                        { "and": [
+                            { "field": "role", "op": "in", "value": ["admin", "owner"] },
+                            { "not": { "field": "banned", "op": "eq", "value": true } }
+                        ] }
+                    ] }
+                ],
+                "sort": [{ "field": "created_at", "direction": "desc" }],
+                "limit": 20,
+                "offset": 40,
+                "projection": [{ "field": "id" }, { "field": "email", "alias": "contact" }],
+                "include": ["customer"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let json = serde_json::to_string(&criteria()).unwrap();
+        let decoded: FilterCriteria = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, criteria());
+    }
+
+    #[test]
+    fn test_minimal_document() {
+        let decoded: FilterCriteria =
+            serde_json::from_str(r#"{"conditions":[{"field":"email","op":"is_not_null"}]}"#)
+                .unwrap();
+
+        assert_eq!(
+            decoded,
+            FilterCriteria::new().with_condition(Condition::new(
+                "email",
+                Operator::IsNotNull,
+                ConditionValue::Null
+            ))
+        );
+        assert_eq!(
+            serde_json::to_value(FilterCriteria::new()).unwrap(),
+            json!({ "version": 1 })
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_version_and_fields() {
+        assert!(serde_json::from_str::<FilterCriteria>(r#"{"version":2}"#).is_err());
+        assert!(serde_json::from_str::<FilterCriteria>(r#"{"condition":[]}"#).is_err());
+        assert!(
+            serde_json::from_str::<FilterCriteria>(
+                r#"{"conditions":[{"field":"a","op":"regex","value":1}]}"#
+            )
+            .is_err()
+        );
+
+        // A misspelled key must not turn into a NULL value or a default
+        for json in [
+            r#"{"conditions":[{"field":"id","op":"eq","valeu":1}]}"#,
+            r#"{"filters":[{"or":[{"not":{"field":"id","op":"eq","valeu":1}}]}]}"#,
+            r#"{"sort":[{"field":"id","direction":"asc","rnak":null}]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<FilterCriteria>(json).is_err(),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_pagination_and_page_round_trip() {
+        let pagination = Pagination::new(2, 50);
+        let page = Page::new(vec!["a".to_string(), "b".to_string()], 2, 50, 120);
+
+        assert_eq!(
+            serde_json::to_value(pagination).unwrap(),
+            json!({ "page": 2, "per_page": 50 })
+        );
+        assert_eq!(
+            serde_json::to_value(&page).unwrap(),
+            json!({
+                "items": ["a", "b"],
+                "page": 2,
+                "per_page": 50,
+                "total_items": 120,
+                "total_pages": 3
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<Pagination>(json!({ "page": 2, "per_page": 50 })).unwrap(),
+            pagination
+        );
+        assert_eq!(
+            serde_json::from_str::<Page<String>>(&serde_json::to_string(&page).unwrap()).unwrap(),
+            page
+        );
+    }
+
+    #[test]
+    fn test_text_search_shape() {
+        let search = TextSearch::websearch("rust -go").with_config("english");
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::text_search("body", search.clone()))
+            .with_sort(SortOrder::by_rank("body", search));
+
+        let value = serde_json::to_value(&criteria).unwrap();
+        let search = json!({ "query": "rust -go", "mode": "websearch", "config": "english" });
+
+        assert_eq!(
+            value,
+            json!({
+                "version": 1,
+                "conditions": [{
+                    "field": "body",
+                    "op": "text_search",
+                    "value": { "search": search }
+                }],
+                "sort": [{ "field": "body", "direction": "desc", "rank": search }]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<FilterCriteria>(value).unwrap(),
+            criteria
+        );
+    }
+
+    #[test]
+    fn test_search_values_are_tagged() {
+        let search = TextSearch::plain("rust").on_tsvector();
+        let condition = Condition::text_search("search_vector", search);
+
+        assert_eq!(
+            serde_json::to_value(&condition).unwrap(),
+            json!({
+                "field": "search_vector",
+                "op": "text_search",
+                "value": { "search": { "query": "rust", "mode": "plain", "tsvector": true } }
+            })
+        );
+
+        // A JSON object that merely looks like a search is not one
+        let value = serde_json::from_value(json!({ "query": "rust", "mode": "plain" }));
+        assert!(!matches!(value, Ok(ConditionValue::TextSearch(_))));
+    }
+
+    #[cfg(all(
+        feature = "uuid",
+        feature = "chrono",
+        feature = "time",
+        feature = "rust_decimal"
+    ))]
+    #[test]
+    fn test_typed_values_serialize_as_strings() {
+        let criteria = FilterCriteria::new()
+            .with_condition(Condition::eq("id", uuid::Uuid::nil().into()))
+            .with_condition(Condition::gt(
+                "day",
+                chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().into(),
+            ))
+            .with_condition(Condition::lt(
+                "until",
+                time::Date::from_calendar_date(2024, time::Month::April, 1)
+                    .unwrap()
+                    .into(),
+            ))
+            .with_condition(Condition::eq(
+                "price",
+                rust_decimal::Decimal::new(1990, 2).into(),
+            ));
+
+        let value = serde_json::to_value(criteria).unwrap();
+        let values: Vec<&serde_json::Value> = value["conditions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| &c["value"])
+            .collect();
+
+        assert_eq!(
+            values,
+            [
+                &json!("00000000-0000-0000-0000-000000000000"),
+                &json!("2024-03-01"),
+                &json!("2024-04-01"),
+                &json!("19.90"),
+            ]
+        );
+    }
+}
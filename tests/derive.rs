@@ -0,0 +1,238 @@
+#![cfg(feature = "derive")]
+
+use wyvern::{
+    Condition, ConditionValue, Entity, FieldValues, FilterCriteria, Operator, Relation,
+    SortDirection, SortOrder,
+};
+
+#[derive(Clone, Entity)]
+#[wyvern(table = "accounts")]
+struct Account {
+    #[wyvern(id)]
+    account_id: i64,
+    email: String,
+    #[wyvern(column = "display_name")]
+    name: Option<String>,
+    #[wyvern(skip)]
+    #[allow(dead_code)]
+    cached_score: f64,
+}
+
+#[derive(Clone, Entity)]
+struct AuditLog {
+    id: String,
+    level: i32,
+}
+
+#[derive(Clone, Entity)]
+#[allow(clippy::upper_case_acronyms)]
+struct HTTPLog {
+    id: i64,
+    r#type: String,
+}
+
+// Fields whose constants would hide the `Entity` constants must rename them
+#[derive(Clone, Entity)]
+struct Seating {
+    id: i64,
+    #[wyvern(constant = "TABLE_NUMBER")]
+    table: i32,
+    #[wyvern(constant = "COLUMN_COUNT")]
+    columns: i32,
+}
+
+#[cfg(feature = "sqlx")]
+#[derive(Clone, Entity, sqlx::FromRow)]
+#[wyvern(table = "users")]
+struct User {
+    id: i64,
+    #[sqlx(rename = "created")]
+    created_at: String,
+    #[sqlx(default, rename = "mail")]
+    #[wyvern(column = "mail")]
+    email: String,
+}
+
+#[derive(Clone, Entity)]
+#[wyvern(table = "customers")]
+struct Customer {
+    id: i64,
+    name: String,
+}
+
+#[derive(Clone, Entity)]
+#[wyvern(table = "orders")]
+#[wyvern(many_to_one(name = "customer", entity = Customer, foreign_key = "customer_id"))]
+#[wyvern(one_to_many(
+    name = "lines",
+    table = "order_lines",
+    foreign_key = "order_id",
+    columns = "sku, quantity"
+))]
+#[wyvern(many_to_many(
+    name = "tags",
+    table = "tags",
+    join_table = "order_tags",
+    source_foreign_key = "order_id",
+    target_foreign_key = "tag_code",
+    target_key = "code"
+))]
+struct Order {
+    id: i64,
+    customer_id: i64,
+}
+
+fn account() -> Account {
+    Account {
+        account_id: 7,
+        email: "a@b.c".to_string(),
+        name: None,
+        cached_score: 1.0,
+    }
+}
+
+#[test]
+fn test_entity_metadata() {
+    assert_eq!(Account::TABLE, "accounts");
+    assert_eq!(Account::ID_COLUMN, "account_id");
+    assert_eq!(Account::COLUMNS, &["account_id", "email", "display_name"]);
+    assert_eq!(account().id(), 7);
+}
+
+#[test]
+fn test_defaults() {
+    let log = AuditLog {
+        id: "evt-1".to_string(),
+        level: 3,
+    };
+
+    assert_eq!(AuditLog::TABLE, "audit_log");
+    assert_eq!(AuditLog::ID_COLUMN, "id");
+    assert_eq!(log.id(), "evt-1");
+    assert!(matches!(
+        log.field_value("level"),
+        Some(ConditionValue::Integer(3))
+    ));
+}
+
+#[test]
+fn test_acronyms_and_raw_identifiers() {
+    let log = HTTPLog {
+        id: 1,
+        r#type: "GET".to_string(),
+    };
+
+    assert_eq!(HTTPLog::TABLE, "http_log");
+    assert_eq!(HTTPLog::COLUMNS, &["id", "type"]);
+    assert_eq!(HTTPLog::TYPE.name(), "type");
+    assert!(matches!(
+        log.field_value("type"),
+        Some(ConditionValue::String(s)) if s == "GET"
+    ));
+}
+
+#[test]
+fn test_renamed_constants_keep_entity_constants() {
+    let seating = Seating {
+        id: 1,
+        table: 12,
+        columns: 3,
+    };
+
+    assert_eq!(Seating::TABLE, "seating");
+    assert_eq!(Seating::COLUMNS, &["id", "table", "columns"]);
+    assert_eq!(Seating::TABLE_NUMBER.name(), "table");
+    assert_eq!(Seating::COLUMN_COUNT.name(), "columns");
+    assert_eq!(seating.id(), 1);
+    assert!(matches!(
+        Seating::TABLE_NUMBER.eq(seating.table).value,
+        ConditionValue::Integer(12)
+    ));
+}
+
+#[cfg(feature = "sqlx")]
+#[test]
+fn test_sqlx_renames_name_columns() {
+    assert_eq!(User::COLUMNS, &["id", "created", "mail"]);
+    assert_eq!(User::CREATED_AT.name(), "created");
+}
+
+#[test]
+fn test_field_values() {
+    let account = account();
+
+    assert!(matches!(
+        account.field_value("email"),
+        Some(ConditionValue::String(s)) if s == "a@b.c"
+    ));
+    assert!(matches!(
+        account.field_value("display_name"),
+        Some(ConditionValue::Null)
+    ));
+    assert!(account.field_value("name").is_none());
+    assert!(account.field_value("cached_score").is_none());
+}
+
+#[test]
+fn test_column_constants() {
+    let criteria = FilterCriteria::new()
+        .with_condition(Condition::eq(Account::EMAIL, "a@b.c".into()))
+        .with_sort(SortOrder::asc(Account::NAME));
+
+    assert_eq!(Account::ACCOUNT_ID.name(), "account_id");
+    assert_eq!(criteria.conditions[0].field, "email");
+    assert_eq!(criteria.sort[0].field, "display_name");
+}
+
+#[test]
+fn test_typed_columns() {
+    let criteria = FilterCriteria::new()
+        .with_condition(AuditLog::LEVEL.gte(2))
+        .with_condition(Account::NAME.eq("Ada"))
+        .with_condition(Account::EMAIL.like("%@example.com"))
+        .with_condition(AuditLog::ID.in_list(["a", "b"]))
+        .with_condition(Account::NAME.is_null())
+        .with_condition(AuditLog::LEVEL.between(1, 3))
+        .with_condition(Account::EMAIL.ends_with(".org"))
+        .with_sort(Account::ACCOUNT_ID.desc());
+
+    let conditions = &criteria.conditions;
+    assert_eq!(conditions[0].operator, Operator::GreaterThanOrEqual);
+    assert!(matches!(conditions[0].value, ConditionValue::Integer(2)));
+    assert_eq!(conditions[1].field, "display_name");
+    assert!(matches!(&conditions[1].value, ConditionValue::String(s) if s == "Ada"));
+    assert_eq!(conditions[2].operator, Operator::Like);
+    assert!(matches!(&conditions[3].value, ConditionValue::List(values) if values.len() == 2));
+    assert_eq!(conditions[4].operator, Operator::IsNull);
+    assert_eq!(conditions[5].operator, Operator::Between);
+    assert!(matches!(&conditions[5].value, ConditionValue::List(values) if values.len() == 2));
+    assert_eq!(conditions[6].operator, Operator::EndsWith);
+    assert_eq!(criteria.sort[0].field, "account_id");
+    assert_eq!(criteria.sort[0].direction, SortDirection::Descending);
+}
+
+#[test]
+fn test_relations() {
+    assert!(Account::relations().is_empty());
+    assert_eq!(
+        Order::relations(),
+        vec![
+            Relation::many_to_one("customer", "customers", "customer_id")
+                .with_columns(["id", "name"]),
+            Relation::one_to_many("lines", "order_lines", "order_id")
+                .with_columns(["sku", "quantity"]),
+            Relation::many_to_many("tags", "tags", "order_tags", "order_id", "tag_code")
+                .with_target_key("code"),
+        ]
+    );
+
+    let order = Order {
+        id: 1,
+        customer_id: 9,
+    };
+    assert_eq!(order.id(), 1);
+    assert!(matches!(
+        order.field_value("customer_id"),
+        Some(ConditionValue::Integer(9))
+    ));
+}
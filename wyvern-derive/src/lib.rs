@@ -0,0 +1,412 @@
+//! Derive macros for wyvern
+//!
+//! This crate is re-exported by `wyvern` when its `derive` feature is enabled;
+//! depend on `wyvern` rather than on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{Data, DeriveInput, Fields, Ident, LitStr, Token, Type, parse_macro_input};
+
+/// Derives `wyvern::Entity` and `wyvern::FieldValues` for a struct with named fields.
+///
+/// # Attributes
+///
+/// On the struct:
+///
+/// - `#[wyvern(table = "users")]`: the table name (defaults to the struct name in snake_case)
+/// - `#[wyvern(many_to_one(name = "customer", table = "customers", foreign_key = "customer_id"))]`:
+///   a relation whose `foreign_key` column is on this table
+/// - `#[wyvern(one_to_many(name = "lines", table = "order_lines", foreign_key = "order_id"))]`:
+///   a relation whose `foreign_key` column is on the related table
+/// - `#[wyvern(many_to_many(name = "tags", table = "tags", join_table = "order_tags",
+///   source_foreign_key = "order_id", target_foreign_key = "tag_id"))]`: a relation through
+///   a join table
+///
+/// Relations also accept `source_key` and `target_key` for keys other than `id`
+/// and are returned by `Entity::relations`. The columns loaded eagerly are
+/// those of `entity = Customer`, an entity type that also provides the table
+/// when `table` is omitted, and those listed in `columns = "id, name"`.
+///
+/// On fields:
+///
+/// - `#[wyvern(id)]`: marks the primary key (defaults to the field named `id`)
+/// - `#[wyvern(column = "name")]`: the column name (defaults to the field name)
+/// - `#[wyvern(skip)]`: excludes the field from the column list
+/// - `#[wyvern(constant = "NAME")]`: the name of the field's column constant
+///
+/// A field's `#[sqlx(rename = "name")]`, as read by `sqlx::FromRow`, also
+/// names its column, and a `column` that disagrees with it is an error so the
+/// queries and the row decoding agree. The struct-level `#[sqlx(rename_all)]`
+/// is not read; give such columns an explicit name.
+///
+/// For every column the derive also generates an associated constant named
+/// after the field in upper case (e.g. `User::CREATED_AT`) holding a typed
+/// `wyvern::Column`. Its value type is the field type, with `Option` removed
+/// for nullable fields. A constant named `TABLE`, `COLUMNS` or `ID_COLUMN`
+/// would hide the `Entity` constant of that name, so fields such as `table`
+/// must name theirs with `constant`.
+#[proc_macro_derive(Entity, attributes(wyvern))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_entity(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Column {
+    ident: Ident,
+    ty: Type,
+    name: String,
+    constant: Ident,
+}
+
+/// The associated constants of `wyvern::Entity`, which column constants must
+/// not hide
+const ENTITY_CONSTANTS: &[&str] = &["TABLE", "COLUMNS", "ID_COLUMN"];
+
+fn expand_entity(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+
+    let mut table = to_snake_case(&struct_name.unraw().to_string());
+    let mut relations = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("wyvern")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else if let Some(kind) = meta.path.get_ident().filter(|kind| {
+                *kind == "many_to_one" || *kind == "one_to_many" || *kind == "many_to_many"
+            }) {
+                relations.push(parse_relation(&meta, kind)?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `table = \"...\"`, `many_to_one(...)`, `one_to_many(...)` \
+                     or `many_to_many(...)`",
+                ))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    struct_name,
+                    "Entity can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                struct_name,
+                "Entity can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut columns = Vec::new();
+    let mut id: Option<usize> = None;
+    for field in fields {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = None;
+        let mut constant = None;
+        let mut is_id = false;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("wyvern")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("id") {
+                    is_id = true;
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("column") {
+                    name = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("constant") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    constant = Some(lit.parse::<Ident>()?);
+                    Ok(())
+                } else {
+                    Err(meta
+                        .error("expected `id`, `skip`, `column = \"...\"` or `constant = \"...\"`"))
+                }
+            })?;
+        }
+
+        let rename = sqlx_rename(field)?;
+        let name = match (name, rename) {
+            (Some(name), Some(rename)) if name != rename.value() => {
+                return Err(syn::Error::new_spanned(
+                    rename,
+                    format!(
+                        "`#[sqlx(rename)]` disagrees with `#[wyvern(column = \"{}\")]`",
+                        name
+                    ),
+                ));
+            }
+            (Some(name), _) => name,
+            (None, Some(rename)) => rename.value(),
+            (None, None) => ident.unraw().to_string(),
+        };
+
+        if skip {
+            if is_id {
+                return Err(syn::Error::new_spanned(
+                    &ident,
+                    "the id field cannot be skipped",
+                ));
+            }
+            continue;
+        }
+
+        if is_id {
+            if id.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &ident,
+                    "only one field can be marked `#[wyvern(id)]`",
+                ));
+            }
+            id = Some(columns.len());
+        }
+
+        let constant = constant
+            .unwrap_or_else(|| format_ident!("{}", ident.unraw().to_string().to_uppercase()));
+        if ENTITY_CONSTANTS.iter().any(|c| constant == c) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!(
+                    "the column constant `{}` would hide `Entity::{}`; \
+                     name it with `#[wyvern(constant = \"...\")]`",
+                    constant, constant
+                ),
+            ));
+        }
+
+        columns.push(Column {
+            ident,
+            ty: field.ty.clone(),
+            name,
+            constant,
+        });
+    }
+
+    let id = match id.or_else(|| columns.iter().position(|c| c.ident == "id")) {
+        Some(index) => &columns[index],
+        None => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "no id field: name a field `id` or mark one with `#[wyvern(id)]`",
+            ));
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let id_ident = &id.ident;
+    let id_ty = &id.ty;
+    let id_name = &id.name;
+    let column_names = columns.iter().map(|c| &c.name);
+    let value_arms = columns.iter().map(|c| {
+        let ident = &c.ident;
+        let name = &c.name;
+        quote! {
+            #name => ::std::option::Option::Some(
+                ::wyvern::ConditionValue::from(::std::clone::Clone::clone(&self.#ident)),
+            ),
+        }
+    });
+    let column_consts = columns.iter().map(|c| {
+        let const_name = &c.constant;
+        let ty = option_inner(&c.ty).unwrap_or(&c.ty);
+        let name = &c.name;
+        let doc = format!("The `{}` column.", name);
+        quote! {
+            #[doc = #doc]
+            pub const #const_name: ::wyvern::Column<Self, #ty> = ::wyvern::Column::new(#name);
+        }
+    });
+
+    let relations_fn = (!relations.is_empty()).then(|| {
+        quote! {
+            fn relations() -> ::std::vec::Vec<::wyvern::Relation> {
+                ::std::vec![#(#relations),*]
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::wyvern::Entity for #struct_name #ty_generics #where_clause {
+            type Id = #id_ty;
+
+            const TABLE: &'static str = #table;
+            const ID_COLUMN: &'static str = #id_name;
+            const COLUMNS: &'static [&'static str] = &[#(#column_names),*];
+
+            fn id(&self) -> Self::Id {
+                ::std::clone::Clone::clone(&self.#id_ident)
+            }
+
+            #relations_fn
+        }
+
+        impl #impl_generics ::wyvern::FieldValues for #struct_name #ty_generics #where_clause {
+            fn field_value(&self, field: &str) -> ::std::option::Option<::wyvern::ConditionValue> {
+                match field {
+                    #(#value_arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#column_consts)*
+        }
+    })
+}
+
+/// Parses a relation attribute such as `many_to_one(name = "...", ...)` into
+/// the expression constructing the `wyvern::Relation`
+fn parse_relation(meta: &ParseNestedMeta, kind: &Ident) -> syn::Result<proc_macro2::TokenStream> {
+    let required: &[&str] = if kind == "many_to_many" {
+        &[
+            "name",
+            "table",
+            "join_table",
+            "source_foreign_key",
+            "target_foreign_key",
+        ]
+    } else {
+        &["name", "table", "foreign_key"]
+    };
+    let optional = ["source_key", "target_key", "columns"];
+
+    let mut values: HashMap<String, LitStr> = HashMap::new();
+    let mut entity: Option<Type> = None;
+    meta.parse_nested_meta(|inner| {
+        let key = inner
+            .path
+            .get_ident()
+            .map(Ident::to_string)
+            .unwrap_or_default();
+        if key == "entity" {
+            entity = Some(inner.value()?.parse()?);
+            Ok(())
+        } else if required.contains(&key.as_str()) || optional.contains(&key.as_str()) {
+            values.insert(key, inner.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(inner.error(format!(
+                "expected one of `{}`, `entity`, `{}`",
+                required.join("`, `"),
+                optional.join("`, `")
+            )))
+        }
+    })?;
+
+    let mut args = Vec::with_capacity(required.len());
+    for key in required {
+        match (values.get(*key), &entity) {
+            (Some(value), _) => args.push(quote! { #value }),
+            // The related entity provides the table
+            (None, Some(entity)) if *key == "table" => {
+                args.push(quote! { <#entity as ::wyvern::Entity>::TABLE })
+            }
+            (None, _) => return Err(meta.error(format!("missing `{} = \"...\"`", key))),
+        }
+    }
+
+    let mut relation = quote! { ::wyvern::Relation::#kind(#(#args),*) };
+    if let Some(key) = values.get("source_key") {
+        relation = quote! { #relation.with_source_key(#key) };
+    }
+    if let Some(key) = values.get("target_key") {
+        relation = quote! { #relation.with_target_key(#key) };
+    }
+    if let Some(entity) = &entity {
+        relation = quote! { #relation.with_entity_columns::<#entity>() };
+    }
+    if let Some(columns) = values.get("columns") {
+        let columns = columns
+            .value()
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        relation = quote! { #relation.with_columns([#(#columns),*]) };
+    }
+    Ok(relation)
+}
+
+/// Returns the name in a field's `#[sqlx(rename = "...")]`, skipping the
+/// other `sqlx` field attributes
+fn sqlx_rename(field: &syn::Field) -> syn::Result<Option<LitStr>> {
+    let mut rename = None;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("sqlx")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<proc_macro2::TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rename)
+}
+
+/// Returns `T` if `ty` is `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Converts a type name to snake_case, keeping acronyms together: `AuditLog`
+/// becomes `audit_log` and `HTTPLog` becomes `http_log`
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // A word starts after a lowercase letter or digit, or at the last
+            // capital of an acronym followed by a lowercase letter
+            let previous = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let starts_word = match previous {
+                Some(p) if p.is_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+            if starts_word {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}